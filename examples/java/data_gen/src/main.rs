@@ -0,0 +1,38 @@
+use project_gen_java::{
+    BraceStyle, FormatOptions, IndentStyle, JavaCodeStyle, JavaDataGenerator, JavaSourceStructure, JavaVersion, POMWriter,
+    RegenerationMode,
+};
+use project_gen_parser::{ApiDefinition, DataDefinition, Model};
+
+fn main() {
+    let data_yaml = concat!(env!("CARGO_MANIFEST_DIR"), "/../../../demo/data.yaml");
+    let api_yaml = concat!(env!("CARGO_MANIFEST_DIR"), "/../../../demo/api.yaml");
+    let model = match DataDefinition::parse(data_yaml)
+        .and_then(|data| ApiDefinition::parse(api_yaml, &data).map(|api| Model::new(&data, Some(&api)))) {
+        Ok(model) => model,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let root_dir = std::env::current_dir().unwrap();
+    let java_structure = JavaSourceStructure::new(root_dir.clone());
+    let mut generator = JavaDataGenerator::new("com.example.demo");
+    for arg in std::env::args().skip(1) {
+        if arg == "--lombok" {
+            generator.set_style(JavaCodeStyle::Lombok);
+        } else if let Some(version) = arg.strip_prefix("--java=").and_then(JavaVersion::parse) {
+            generator.set_java_version(version);
+        } else if arg == "--generation-gap" {
+            generator.set_regeneration_mode(RegenerationMode::GenerationGap);
+        } else if arg == "--format=allman" {
+            let mut format = FormatOptions::new();
+            format.set_indent(IndentStyle::Tabs, 4);
+            format.set_max_width(120);
+            format.set_brace_style(BraceStyle::NextLine);
+            generator.set_format(format);
+        }
+    }
+    generator.write(&java_structure, &model).unwrap();
+    POMWriter::default().write_to(&generator.generate_pom(&model, "demo"), root_dir).unwrap();
+}
@@ -1,4 +1,8 @@
 use project_gen_java::{
+    AnnotationValue,
+    BeanOptions,
+    Javadoc,
+    CodeBlock,
     JavaSourceCodeWriter,
     JavaSourceStructure, 
     JavaSourceCode, 
@@ -10,6 +14,12 @@ use project_gen_java::{
     JavaMethodStatement, 
     JavaAnnotationDeclaration, 
     JavaAnnotationAttribute, 
+    JavaEnumConstant,
+    JavaInitializerBlock,
+    JavaVersion,
+    Modifier,
+    TypeName,
+    TypeParameter,
     ValueType
 };
 
@@ -18,11 +28,11 @@ fn main() {
     let java_structure = JavaSourceStructure::new(root_dir);
     let mut java_source_code = JavaSourceCode::new();
     let mut compilation_unit = JavaCompilationUnit::new("com.example.test","Test");
-    let mut type_declaration = JavaTypeDeclaration::new(2, "Test", None);
+    let mut type_declaration = JavaTypeDeclaration::new(Modifier::PUBLIC, "Test", None);
     
-    let mut field1 = JavaFieldDeclaration::new("name","java.lang.String",4,Some("zhangsan"));
-    let field2 = JavaFieldDeclaration::new("age","int",4,Some("12"));
-    let field3 = JavaFieldDeclaration::new("card","cn.ljyun.entity.Card",4,Some("new Card()"));
+    let mut field1 = JavaFieldDeclaration::new("name","java.lang.String",Modifier::PRIVATE,Some("zhangsan"));
+    let field2 = JavaFieldDeclaration::new("age","int",Modifier::PRIVATE,Some("12"));
+    let field3 = JavaFieldDeclaration::new("card","cn.ljyun.entity.Card",Modifier::PRIVATE,Some("new Card()"));
     
     let mut annotation = JavaAnnotationDeclaration::new("TestAnnotation");
     annotation.add_attribute(JavaAnnotationAttribute::new("value",ValueType::default(),vec!["test"]));
@@ -34,19 +44,290 @@ fn main() {
 
     
 
-    let mut method = JavaMethodDeclaration::new("test", "void", 2);
+    let mut method = JavaMethodDeclaration::new("test", "void", Modifier::PUBLIC);
     let param = JavaMethodParameter::new("card","cn.ljyun.Card");
     method.add_parameter(param);
     let st1 = JavaMethodStatement::new("$T $V = new $T();",vec!["cn.ljyun.Card","card","cn.ljyun.entity.Card"]);
     let st2 = JavaMethodStatement::new("System.out.println($V.getName());",vec!["card"]);
+    let st3 = JavaMethodStatement::new("requireNonNull($V);",vec!["card"]);
     method.add_statement(st1);
     method.add_statement(st2);
+    method.add_statement(st3);
     
 
     type_declaration.add_method(method);
+    compilation_unit.add_static_import("java.util.Objects", "requireNonNull");
     compilation_unit.add_type_declaration(type_declaration);
     java_source_code.add_compilation_unit(compilation_unit);
 
+    // 接口，包括 abstract 方法和 default 方法
+    let mut compilation_unit = JavaCompilationUnit::new("com.example.test","Named");
+    let mut named = JavaTypeDeclaration::new_interface(Modifier::PUBLIC, "Named", Some("java.io.Serializable"));
+    named.add_extend("java.lang.Comparable<Named>".to_string());
+    named.add_method(JavaMethodDeclaration::new("getName", "java.lang.String", Modifier::new()));
+    let mut export = JavaMethodDeclaration::new("export", "void", Modifier::new());
+    let mut writer = JavaMethodParameter::new("writer", "java.io.Writer");
+    writer.set_modifiers(Modifier::FINAL);
+    export.add_parameter(writer);
+    let mut fields = JavaMethodParameter::new("fields", "java.lang.String[]");
+    fields.set_varargs(true);
+    export.add_parameter(fields);
+    export.add_exception("java.io.IOException");
+    named.add_method(export);
+    let mut compare_to = JavaMethodDeclaration::new("compareTo", "int", Modifier::new());
+    compare_to.add_parameter(JavaMethodParameter::new("other", "Named"));
+    compare_to.add_statement(JavaMethodStatement::new("return getName().compareTo(other.getName());", vec![]));
+    named.add_method(compare_to);
+    compilation_unit.add_type_declaration(named);
+    java_source_code.add_compilation_unit(compilation_unit);
+
+    // 带构造参数的枚举
+    let mut compilation_unit = JavaCompilationUnit::new("com.example.test","Gender");
+    let mut gender = JavaTypeDeclaration::new_enum(Modifier::PUBLIC, "Gender");
+    gender.add_implement("com.example.test.Named".to_string());
+    gender.add_enum_constant(JavaEnumConstant::new("MALE", vec!["\"男\""]));
+    gender.add_enum_constant(JavaEnumConstant::new("FEMALE", vec!["\"女\""]));
+    gender.add_field(JavaFieldDeclaration::new("name", "java.lang.String", Modifier::PRIVATE | Modifier::FINAL, None));
+    let mut constructor = JavaMethodDeclaration::new_constructor(Modifier::new());
+    constructor.add_parameter(JavaMethodParameter::new("name", "java.lang.String"));
+    constructor.add_statement(JavaMethodStatement::new("this.name = name;", vec![]));
+    gender.add_constructor(constructor);
+    let mut get_name = JavaMethodDeclaration::new("getName", "java.lang.String", Modifier::PUBLIC);
+    get_name.add_statement(JavaMethodStatement::new("return this.name;", vec![]));
+    gender.add_method(get_name);
+    compilation_unit.add_type_declaration(gender);
+    java_source_code.add_compilation_unit(compilation_unit);
+
+    // record
+    let mut compilation_unit = JavaCompilationUnit::new("com.example.test","Point");
+    let mut point = JavaTypeDeclaration::new_record(Modifier::PUBLIC, "Point");
+    point.add_component(JavaMethodParameter::new("x", "int"));
+    point.add_component(JavaMethodParameter::new("y", "int"));
+    point.add_implement("java.io.Serializable".to_string());
+    compilation_unit.add_type_declaration(point);
+    java_source_code.add_compilation_unit(compilation_unit);
+
+    // sealed 接口、switch 表达式以及文本块，需要 Java 17
+    let mut compilation_unit = JavaCompilationUnit::new("com.example.test.shape","Shape");
+    let mut shape = JavaTypeDeclaration::new_interface(Modifier::PUBLIC | Modifier::SEALED, "Shape", None);
+    shape.add_permit("com.example.test.shape.Circle");
+    shape.add_permit("com.example.test.shape.Square");
+    let mut label = JavaAnnotationDeclaration::new("com.example.test.Label");
+    label.add_member("value", AnnotationValue::TextBlock("形状\n可以是圆形或者正方形".to_string()));
+    shape.add_annotation(label);
+    shape.add_method(JavaMethodDeclaration::new("sides", "int", Modifier::new()));
+    let mut describe = JavaMethodDeclaration::new("describe", "java.lang.String", Modifier::new());
+    let mut code = CodeBlock::new();
+    code.begin_switch_expression("return switch (sides())", vec![])
+        .add_switch_rule("case 0 -> $S", vec!["circle".into()])
+        .add_switch_rule("case 4 -> $S", vec!["square".into()])
+        .add_switch_rule("default -> $T.valueOf(sides())", vec!["java.lang.String".into()])
+        .end_switch_expression();
+    describe.add_code(code);
+    shape.add_method(describe);
+    compilation_unit.add_type_declaration(shape);
+    java_source_code.add_compilation_unit(compilation_unit);
+
+    let mut compilation_unit = JavaCompilationUnit::new("com.example.test.shape","Circle");
+    let mut circle = JavaTypeDeclaration::new_record(Modifier::PUBLIC, "Circle");
+    circle.add_component(JavaMethodParameter::new("radius", "double"));
+    circle.add_implement("com.example.test.shape.Shape");
+    let mut sides = JavaMethodDeclaration::new("sides", "int", Modifier::PUBLIC);
+    sides.add_statement(JavaMethodStatement::new("return 0;", vec![]));
+    circle.add_method(sides);
+    compilation_unit.add_type_declaration(circle);
+    java_source_code.add_compilation_unit(compilation_unit);
+
+    let mut compilation_unit = JavaCompilationUnit::new("com.example.test.shape","Square");
+    let mut square = JavaTypeDeclaration::new(Modifier::PUBLIC | Modifier::NON_SEALED, "Square", None);
+    square.add_implement("com.example.test.shape.Shape");
+    let mut sides = JavaMethodDeclaration::new("sides", "int", Modifier::PUBLIC);
+    sides.add_statement(JavaMethodStatement::new("return 4;", vec![]));
+    square.add_method(sides);
+    compilation_unit.add_type_declaration(square);
+    java_source_code.add_compilation_unit(compilation_unit);
+
+    // 泛型类型以及泛型方法
+    let mut compilation_unit = JavaCompilationUnit::new("com.example.test","Pair");
+    let mut pair = JavaTypeDeclaration::new(Modifier::PUBLIC, "Pair", None);
+    pair.add_type_parameter(TypeParameter::new("K"));
+    let mut value = TypeParameter::new("V");
+    value.add_bound("java.lang.Comparable<V>");
+    value.add_bound("java.io.Serializable");
+    pair.add_type_parameter(value);
+    pair.add_field(JavaFieldDeclaration::new("key", TypeName::variable("K"), Modifier::PRIVATE, None));
+    pair.add_field(JavaFieldDeclaration::new("values", "java.util.Map<K, java.util.List<? extends V>>", Modifier::PRIVATE, None));
+    let mut first = JavaMethodDeclaration::new("first", "T", Modifier::PUBLIC | Modifier::STATIC);
+    first.add_type_parameter(TypeParameter::new("T"));
+    first.add_parameter(JavaMethodParameter::new("items", "java.util.Collection<T>[]"));
+    first.add_statement(JavaMethodStatement::new("return items[0].iterator().next();", vec![]));
+    pair.add_method(first);
+    let mut options = BeanOptions::new();
+    options.set_immutable(true);
+    pair.apply_bean_options(&options);
+    pair.add_accessors(&options);
+    pair.add_equals_and_hash_code(&options);
+    pair.add_builder(&options);
+    compilation_unit.add_type_declaration(pair);
+    java_source_code.add_compilation_unit(compilation_unit);
+
+    // 成员类型以及匿名类
+    let mut compilation_unit = JavaCompilationUnit::new("com.example.test","Order");
+    compilation_unit.set_file_header("Copyright (c) example.com\nLicensed under the Apache License, Version 2.0");
+    let mut order = JavaTypeDeclaration::new(Modifier::PUBLIC, "Order", None);
+    let mut table = JavaAnnotationDeclaration::new("javax.persistence.Table");
+    table.add_member("name", "t_order");
+    for (name, columns) in [("idx_status", "status"), ("idx_code", "code, created_at")] {
+        let mut index = JavaAnnotationDeclaration::new("javax.persistence.Index");
+        index.add_member("name", name);
+        index.add_member("columnList", columns);
+        table.add_member("indexes", index);
+    }
+    order.add_annotation(table);
+    let mut label = JavaAnnotationDeclaration::new("Label");
+    label.add_member("value", "订单 \"Order\"");
+    label.add_member("priority", 1);
+    label.add_member("limit", 10_000_000_000i64);
+    label.add_member("separator", '\'');
+    label.add_member("visible", false);
+    label.add_member("types", vec![AnnotationValue::Class(TypeName::from("java.util.List")), AnnotationValue::Class(TypeName::from("int[]"))]);
+    order.add_annotation(label);
+    let mut suppress = JavaAnnotationDeclaration::new("java.lang.SuppressWarnings");
+    suppress.add_member("value", "unchecked");
+    order.add_annotation(suppress);
+    order.add_field(JavaFieldDeclaration::new("status", "Order.Status", Modifier::PRIVATE, None));
+    order.add_field(JavaFieldDeclaration::new("items", "java.util.List<Item>", Modifier::PRIVATE, None));
+    order.add_field(JavaFieldDeclaration::new("DEFAULT_STATUS", "Order.Status", Modifier::PRIVATE | Modifier::STATIC | Modifier::FINAL, None));
+    let mut static_block = JavaInitializerBlock::new_static();
+    static_block.add_statement(JavaMethodStatement::new("DEFAULT_STATUS = Status.CREATED;", vec![]));
+    order.add_initializer(static_block);
+    let mut init_block = JavaInitializerBlock::new();
+    init_block.add_statement(JavaMethodStatement::new("items = new $T<>();", vec!["java.util.ArrayList"]));
+    order.add_initializer(init_block);
+    let mut default_constructor = JavaMethodDeclaration::new_constructor(Modifier::PUBLIC);
+    default_constructor.call_this(vec!["DEFAULT_STATUS"]);
+    order.add_constructor(default_constructor);
+    let mut status_constructor = JavaMethodDeclaration::new_constructor(Modifier::PUBLIC);
+    status_constructor.add_parameter(JavaMethodParameter::new("status", "Order.Status"));
+    status_constructor.call_super(vec![]);
+    status_constructor.add_statement(JavaMethodStatement::new("this.status = status;", vec![]));
+    order.add_constructor(status_constructor);
+    let mut sort_items = JavaMethodDeclaration::new("sortItems", "void", Modifier::PUBLIC);
+    let mut sort = JavaMethodStatement::new("items.sort($A);", vec![]);
+    let mut comparator = JavaTypeDeclaration::new_anonymous("java.util.Comparator<Item>", vec![]);
+    let mut compare = JavaMethodDeclaration::new("compare", "int", Modifier::PUBLIC);
+    compare.add_annotation(JavaAnnotationDeclaration::new("java.lang.Override"));
+    compare.add_parameter(JavaMethodParameter::new("a", "Item"));
+    compare.add_parameter(JavaMethodParameter::new("b", "Item"));
+    compare.add_statement(JavaMethodStatement::new("return $T.compare(a.quantity, b.quantity);", vec!["java.lang.Integer"]));
+    comparator.add_method(compare);
+    sort.add_anonymous_class(comparator);
+    sort_items.add_statement(sort);
+    let first = JavaMethodStatement::new("$T<$T, $T> first = $T.entry(\"first\", items.get(0));", vec!["java.util.Map.Entry", "java.lang.String", "Item", "java.util.Map"]);
+    sort_items.add_statement(first);
+    order.add_method(sort_items);
+    let mut describe = JavaMethodDeclaration::new("describe", "java.lang.String", Modifier::PUBLIC);
+    let mut javadoc = Javadoc::new("生成订单的描述，依次追加状态、每一项的数量。描述只用于日志，格式可能发生变化，不要解析描述的内容，需要结构化数据时使用 {@link java.util.Map#entry(Object, Object)} 或者 {@link #sortItems()}。");
+    javadoc.add_param("code", "状态码");
+    javadoc.set_return("订单的描述");
+    javadoc.add_throws("java.lang.IllegalStateException", "线程被中断时抛出，这段说明足够长，需要换行才能写下，换行后缩进四个空格 continuation lines are indented by four spaces");
+    describe.set_javadoc(javadoc);
+    describe.add_parameter(JavaMethodParameter::new("code", "int"));
+    let mut code = CodeBlock::new();
+    code.add_statement("$T builder = new $T($S)", vec!["java.lang.StringBuilder".into(), "java.lang.StringBuilder".into(), "订单 \"描述\"\n".into()])
+        .add_comment("根据状态码追加描述")
+        .begin_control_flow("switch ($N)", vec!["code".into()])
+        .begin_case("case 1:", vec![])
+        .add_statement("builder.append($S)", vec!["created".into()])
+        .add_statement("break", vec![])
+        .end_case()
+        .begin_case("default:", vec![])
+        .add_statement("builder.append(code)", vec![])
+        .end_case()
+        .end_control_flow()
+        .begin_control_flow("try", vec![])
+        .add_statement("$T.sleep($L)", vec!["java.lang.Thread".into(), "10".into()])
+        .next_control_flow("catch ($T e)", vec!["java.lang.InterruptedException".into()])
+        .add_statement("throw new $T(e)", vec![TypeName::class("java.lang.IllegalStateException").into()])
+        .next_control_flow("finally", vec![])
+        .add_statement("builder.append('.')", vec![])
+        .end_control_flow()
+        .add_block_comment("跳过偶数项\n只是为了演示 while 循环")
+        .add_statement("int i = 0", vec![])
+        .begin_control_flow("while (i < items.size())", vec![])
+        .begin_control_flow("if (i % 2 == 0)", vec![])
+        .add_statement("i += 2", vec![])
+        .next_control_flow("else", vec![])
+        .add_statement("i++", vec![])
+        .end_control_flow()
+        .end_control_flow()
+        .begin_control_flow("items.forEach(item ->", vec![])
+        .add_statement("builder.append(item.quantity)", vec![])
+        .end_control_flow_with(")", vec![])
+        .add_statement("return builder.toString()", vec![]);
+    describe.add_code(code);
+    order.add_method(describe);
+    let mut status = JavaTypeDeclaration::new_enum(Modifier::PUBLIC, "Status");
+    status.add_enum_constant(JavaEnumConstant::new("CREATED", vec![]));
+    status.add_enum_constant(JavaEnumConstant::new("PAID", vec![]));
+    order.add_type(status);
+    let mut item = JavaTypeDeclaration::new(Modifier::PUBLIC | Modifier::STATIC, "Item", None);
+    item.set_javadoc("订单中的一项");
+    item.add_field(JavaFieldDeclaration::new("quantity", "int", Modifier::PRIVATE, None));
+    order.add_type(item);
+    let mut cursor = JavaTypeDeclaration::new(Modifier::PRIVATE, "Cursor", None);
+    let mut current = JavaMethodDeclaration::new("current", "Item", Modifier::new());
+    current.add_statement(JavaMethodStatement::new("return items.get(0);", vec![]));
+    cursor.add_method(current);
+    order.add_type(cursor);
+    compilation_unit.add_type_declaration(order);
+    java_source_code.add_compilation_unit(compilation_unit);
+
+    // 自动生成的 getter/setter、equals/hashCode/toString 以及 builder
+    let mut compilation_unit = JavaCompilationUnit::new("com.example.test","Person");
+    let mut person = JavaTypeDeclaration::new(Modifier::PUBLIC, "Person", None);
+    person.add_field(JavaFieldDeclaration::new("SEPARATOR", "java.lang.String", Modifier::PRIVATE | Modifier::STATIC | Modifier::FINAL, Some("\",\"")));
+    person.add_field(JavaFieldDeclaration::new("id", "long", Modifier::PRIVATE, None));
+    person.add_field(JavaFieldDeclaration::new("name", "java.lang.String", Modifier::PRIVATE, None));
+    person.add_field(JavaFieldDeclaration::new("score", "double", Modifier::PRIVATE, None));
+    person.add_field(JavaFieldDeclaration::new("active", "boolean", Modifier::PRIVATE, None));
+    person.add_field(JavaFieldDeclaration::new("tags", "java.lang.String[]", Modifier::PRIVATE, None));
+    person.add_field(JavaFieldDeclaration::new("password", "java.lang.String", Modifier::PRIVATE, None));
+    let mut options = BeanOptions::new();
+    options.exclude("password");
+    person.add_constructor(JavaMethodDeclaration::new_constructor(Modifier::PUBLIC));
+    person.add_all_args_constructor(&options);
+    person.add_accessors(&BeanOptions::new());
+    person.add_equals_and_hash_code(&options);
+    person.add_to_string(&options);
+    person.add_builder(&options);
+    compilation_unit.add_type_declaration(person);
+    java_source_code.add_compilation_unit(compilation_unit);
+
+    // 注解类型
+    let mut compilation_unit = JavaCompilationUnit::new("com.example.test","Label");
+    let mut label = JavaTypeDeclaration::new_annotation(Modifier::PUBLIC, "Label");
+    let mut retention = JavaAnnotationDeclaration::new("java.lang.annotation.Retention");
+    retention.add_member("value", AnnotationValue::enum_constant("java.lang.annotation.RetentionPolicy.RUNTIME"));
+    label.add_annotation(retention);
+    let mut value = JavaMethodDeclaration::new("value", "java.lang.String", Modifier::new());
+    value.set_default_value("\"\"");
+    label.add_method(value);
+    for (name, element_type, default_value) in [
+        ("priority", "int", "0"),
+        ("limit", "long", "0L"),
+        ("separator", "char", "','"),
+        ("visible", "boolean", "true"),
+        ("types", "java.lang.Class<?>[]", "{}"),
+    ] {
+        let mut element = JavaMethodDeclaration::new(name, element_type, Modifier::new());
+        element.set_default_value(default_value);
+        label.add_method(element);
+    }
+    compilation_unit.add_type_declaration(label);
+    java_source_code.add_compilation_unit(compilation_unit);
+
     let mut java_source_writer = JavaSourceCodeWriter::new();
+    java_source_writer.set_version(JavaVersion::Java17);
     java_source_writer.write(&java_structure, java_source_code).unwrap();
 }
\ No newline at end of file
@@ -0,0 +1,18 @@
+use project_gen_parser::{ApiDefinition, DataDefinition, Model};
+use project_gen_ts::{TsDataGenerator, TsSourceStructure};
+
+fn main() {
+    let data_yaml = concat!(env!("CARGO_MANIFEST_DIR"), "/../../../demo/data.yaml");
+    let api_yaml = concat!(env!("CARGO_MANIFEST_DIR"), "/../../../demo/api.yaml");
+    let model = match DataDefinition::parse(data_yaml)
+        .and_then(|data| ApiDefinition::parse(api_yaml, &data).map(|api| Model::new(&data, Some(&api)))) {
+        Ok(model) => model,
+        Err(e) => {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }
+    };
+    let root_dir = std::env::current_dir().unwrap();
+    let ts_structure = TsSourceStructure::new(root_dir);
+    TsDataGenerator::new().write(&ts_structure, &model).unwrap();
+}
@@ -0,0 +1,265 @@
+//! 带源码位置的诊断信息，用于一次性报告 yaml 中的所有错误
+//!
+//! ```text
+//! error[PG0012]: field `person.cards` refers to unknown entity `card2`
+//!   --> demo/data.yaml:22:14
+//!    |
+//! 22 |         ref: card2
+//!    |              ^^^^^ unknown entity
+//!    |
+//!    = help: declare entity `card2` or fix the reference
+//! ```
+
+use std::{collections::HashMap, fmt};
+
+/// 稳定的错误码，发布后不再修改含义
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ErrorCode(pub &'static str);
+
+impl ErrorCode {
+    /// yaml 语法错误或者结构与定义不符
+    pub const INVALID_YAML: ErrorCode = ErrorCode("PG0001");
+    /// 数据源名称重复
+    pub const DUPLICATE_DATASOURCE: ErrorCode = ErrorCode("PG0002");
+    /// 数据源子包名重复
+    pub const DUPLICATE_PACKAGE: ErrorCode = ErrorCode("PG0003");
+    /// 多个主数据源
+    pub const MULTIPLE_PRIMARY: ErrorCode = ErrorCode("PG0004");
+    /// 实体名称重复
+    pub const DUPLICATE_ENTITY: ErrorCode = ErrorCode("PG0010");
+    /// 实体缺少主键
+    pub const MISSING_ID: ErrorCode = ErrorCode("PG0011");
+    /// 引用了不存在的实体
+    pub const UNKNOWN_REF: ErrorCode = ErrorCode("PG0012");
+    /// 引用了其他数据源的实体
+    pub const CROSS_DATASOURCE_REF: ErrorCode = ErrorCode("PG0013");
+    /// ref 类型缺少 ref
+    pub const MISSING_REF: ErrorCode = ErrorCode("PG0014");
+    /// list 类型缺少 list
+    pub const MISSING_LIST: ErrorCode = ErrorCode("PG0015");
+    /// list 的元素类型是 list
+    pub const NESTED_LIST: ErrorCode = ErrorCode("PG0016");
+    /// 字段与行为标志自动添加的字段冲突
+    pub const RESERVED_FIELD: ErrorCode = ErrorCode("PG0017");
+    /// type、ref、list 没有且只有一个
+    pub const INVALID_TYPE: ErrorCode = ErrorCode("PG0020");
+    /// 路径参数不在请求路径中
+    pub const UNKNOWN_PATH_VARIABLE: ErrorCode = ErrorCode("PG0021");
+    /// 接口或者接口请求名称重复
+    pub const DUPLICATE_API: ErrorCode = ErrorCode("PG0022");
+    /// 分页、排序或者过滤用在了不返回实体列表的请求上，或者过滤条件不适用于字段
+    pub const INVALID_LISTING: ErrorCode = ErrorCode("PG0023");
+    /// 排序或者过滤的字段不属于返回的实体
+    pub const UNKNOWN_LISTING_FIELD: ErrorCode = ErrorCode("PG0024");
+    /// DTO 名称重复
+    pub const DUPLICATE_DTO: ErrorCode = ErrorCode("PG0025");
+    /// DTO 同时使用了 include 和 exclude
+    pub const INVALID_DTO: ErrorCode = ErrorCode("PG0026");
+    /// DTO 中列出的字段不属于源实体
+    pub const UNKNOWN_DTO_FIELD: ErrorCode = ErrorCode("PG0027");
+    /// DTO 中的字段名重复
+    pub const DUPLICATE_DTO_FIELD: ErrorCode = ErrorCode("PG0028");
+    /// seed 中设置了不存在的字段
+    pub const SEED_UNKNOWN_FIELD: ErrorCode = ErrorCode("PG0030");
+    /// seed 值与字段类型不符
+    pub const SEED_INVALID_VALUE: ErrorCode = ErrorCode("PG0031");
+    /// seed 行主键重复
+    pub const SEED_DUPLICATE_ID: ErrorCode = ErrorCode("PG0032");
+    /// seed 中的 ref 没有对应的 seed 行
+    pub const SEED_UNKNOWN_REF: ErrorCode = ErrorCode("PG0033");
+    /// 查询方法名无法解析或者参数不合法
+    pub const INVALID_QUERY: ErrorCode = ErrorCode("PG0034");
+    /// 查询中使用了不存在的字段
+    pub const UNKNOWN_QUERY_FIELD: ErrorCode = ErrorCode("PG0035");
+    /// 查询条件不适用于字段的类型
+    pub const INVALID_QUERY_OPERATOR: ErrorCode = ErrorCode("PG0036");
+    /// 同一个实体中的查询名称重复
+    pub const DUPLICATE_QUERY: ErrorCode = ErrorCode("PG0037");
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// 源文件中的字节区间 [start, end)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Span {
+        Span { start, end }
+    }
+}
+
+/// 指向源文件某个位置的标注
+#[derive(Debug, Clone)]
+pub struct Label {
+    pub file: String,
+    pub span: Span,
+    pub message: String,
+}
+
+impl Label {
+    pub fn new(file: &str, span: Span, message: &str) -> Label {
+        Label {
+            file: file.to_owned(),
+            span,
+            message: message.to_owned(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    pub code: ErrorCode,
+    pub message: String,
+    /// 错误发生的位置
+    pub label: Option<Label>,
+    pub help: Option<String>,
+    /// 与错误相关的其他位置，如重复定义时的第一次定义
+    pub related: Vec<Label>,
+}
+
+impl Diagnostic {
+    pub fn new(code: ErrorCode, message: String) -> Diagnostic {
+        Diagnostic {
+            code,
+            message,
+            label: None,
+            help: None,
+            related: vec![],
+        }
+    }
+
+    pub fn with_label(mut self, label: Option<Label>) -> Diagnostic {
+        self.label = label;
+        self
+    }
+
+    pub fn with_help(mut self, help: String) -> Diagnostic {
+        self.help = Some(help);
+        self
+    }
+
+    pub fn with_related(mut self, label: Option<Label>) -> Diagnostic {
+        self.related.extend(label);
+        self
+    }
+}
+
+/// 诊断信息收集器，同时保存源文件内容用于渲染代码片段
+#[derive(Debug, Clone, Default)]
+pub struct Diagnostics {
+    pub items: Vec<Diagnostic>,
+    sources: HashMap<String, String>,
+}
+
+impl Diagnostics {
+    pub fn new() -> Diagnostics {
+        Diagnostics::default()
+    }
+
+    pub fn add_source(&mut self, file: &str, text: &str) {
+        self.sources.insert(file.to_owned(), text.to_owned());
+    }
+
+    pub fn push(&mut self, diagnostic: Diagnostic) {
+        self.items.push(diagnostic);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.items.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.items.len()
+    }
+
+    /// 合并另一个收集器的诊断信息和源文件
+    pub fn extend(&mut self, other: Diagnostics) {
+        self.items.extend(other.items);
+        self.sources.extend(other.sources);
+    }
+
+    /// 没有错误时返回 value，否则返回包含所有错误的 `PGError::Diagnostics`
+    pub fn into_result<T>(self, value: T) -> crate::Result<T> {
+        if self.is_empty() {
+            Ok(value)
+        } else {
+            Err(crate::PGError::Diagnostics(self))
+        }
+    }
+
+    fn render_label(&self, f: &mut fmt::Formatter<'_>, label: &Label, marker: char, gutter: usize) -> fmt::Result {
+        let source = match self.sources.get(&label.file) {
+            Some(source) => source,
+            None => {
+                writeln!(f, "{:gutter$}--> {}", "", label.file, gutter = gutter)?;
+                return writeln!(f, "{:gutter$} = {}", "", label.message, gutter = gutter);
+            }
+        };
+        let (line_number, line_start) = line_of(source, label.span.start);
+        let line = source[line_start..].lines().next().unwrap_or("");
+        let column = source[line_start..label.span.start].chars().count();
+        let end = label.span.end.min(line_start + line.len()).max(label.span.start);
+        let width = source[label.span.start..end].chars().count().max(1);
+        let number = (line_number + 1).to_string();
+
+        writeln!(f, "{:gutter$}--> {}:{}:{}", "", label.file, line_number + 1, column + 1, gutter = gutter)?;
+        writeln!(f, "{:gutter$} |", "", gutter = gutter)?;
+        writeln!(f, "{:>gutter$} | {}", number, line, gutter = gutter)?;
+        write!(f, "{:gutter$} | {}{}", "", " ".repeat(column), marker.to_string().repeat(width), gutter = gutter)?;
+        if label.message.is_empty() {
+            writeln!(f)
+        } else {
+            writeln!(f, " {}", label.message)
+        }
+    }
+}
+
+/// 偏移所在的行号以及行首偏移，行号从 0 开始
+fn line_of(source: &str, offset: usize) -> (usize, usize) {
+    let before = &source[..offset.min(source.len())];
+    let line_number = before.matches('\n').count();
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    (line_number, line_start)
+}
+
+impl fmt::Display for Diagnostics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, diagnostic) in self.items.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            // 行号所需的宽度
+            let gutter = diagnostic.label.iter()
+                .chain(diagnostic.related.iter())
+                .filter_map(|label| self.sources.get(&label.file).map(|s| line_of(s, label.span.start).0 + 1))
+                .max()
+                .unwrap_or(0)
+                .to_string()
+                .len();
+            writeln!(f, "error[{}]: {}", diagnostic.code, diagnostic.message)?;
+            if let Some(label) = diagnostic.label.as_ref() {
+                self.render_label(f, label, '^', gutter)?;
+            }
+            if let Some(help) = diagnostic.help.as_ref() {
+                writeln!(f, "{:gutter$} |", "", gutter = gutter)?;
+                writeln!(f, "{:gutter$} = help: {}", "", help, gutter = gutter)?;
+            }
+            for related in &diagnostic.related {
+                writeln!(f, "note: {}", related.message)?;
+                self.render_label(f, &Label::new(&related.file, related.span, ""), '-', gutter)?;
+            }
+        }
+        if self.items.len() > 1 {
+            write!(f, "\nerror: found {} problems", self.items.len())?;
+        }
+        Ok(())
+    }
+}
@@ -1,6 +1,11 @@
+// `failure_derive` expands `Fail` into impls nested inside a const block
+#![allow(non_local_definitions)]
+
 use std::{io, string::FromUtf8Error, str::Utf8Error};
 use failure::Fail;
 
+use crate::{diagnostic::Diagnostics, validation::ValidationErrors};
+
 #[derive(Debug, Fail)]
 pub enum PGError {
   /// IO Error
@@ -12,15 +17,33 @@ pub enum PGError {
   /// quick_xml error
   #[fail(display = "{}",_0)]
   Xml(#[cause] quick_xml::Error),
-  ///
+  /// plain message error
   #[fail(display = "{}",_0)]
   StringError(String),
-  ///
+  /// utf8 error
   #[fail(display = "UTF-8 error: {}",_0)]
   Utf8(#[cause] FromUtf8Error),
 
   #[fail(display = "UTF-8 error: {}",_0)]
   Utf82(#[cause] Utf8Error),
+  /// one or more diagnostics pointing into the source files
+  #[fail(display = "{}",_0)]
+  Diagnostics(Diagnostics),
+  /// model validation errors found before writing any file
+  #[fail(display = "{}",_0)]
+  Validation(ValidationErrors),
+}
+
+impl From<ValidationErrors> for PGError {
+    fn from(value: ValidationErrors) -> Self {
+        PGError::Validation(value)
+    }
+}
+
+impl From<Diagnostics> for PGError {
+    fn from(value: Diagnostics) -> Self {
+        PGError::Diagnostics(value)
+    }
 }
 
 impl From<io::Error> for PGError {
@@ -1,3 +1,6 @@
+pub mod diagnostic;
 pub mod error;
+pub mod naming;
+pub mod validation;
 
-pub use error::Result;
\ No newline at end of file
+pub use error::{PGError, Result};
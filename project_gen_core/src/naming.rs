@@ -0,0 +1,58 @@
+//! 命名风格转换工具，yaml中的名称可以是 camelCase、PascalCase 或 snake_case
+
+/// 将名称拆分为单词，支持 camelCase、PascalCase、snake_case 以及 kebab-case
+fn split_words(name: &str) -> Vec<String> {
+    let mut words: Vec<String> = vec![];
+    let mut current = String::new();
+    let mut prev_lower = false;
+    for c in name.chars() {
+        if c == '_' || c == '-' || c == ' ' || c == '.' {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            prev_lower = false;
+            continue;
+        }
+        if c.is_uppercase() && prev_lower && !current.is_empty() {
+            words.push(std::mem::take(&mut current));
+        }
+        prev_lower = c.is_lowercase() || c.is_ascii_digit();
+        current.push(c);
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+fn capitalize(word: &str) -> String {
+    let mut chars = word.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars.flat_map(|c| c.to_lowercase())).collect(),
+        None => String::new(),
+    }
+}
+
+/// person_card -> PersonCard
+pub fn pascal_case(name: &str) -> String {
+    split_words(name).iter().map(|w| capitalize(w)).collect()
+}
+
+/// person_card -> personCard
+pub fn camel_case(name: &str) -> String {
+    let pascal = pascal_case(name);
+    let mut chars = pascal.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// personCard -> person_card
+pub fn snake_case(name: &str) -> String {
+    split_words(name)
+        .iter()
+        .map(|w| w.to_lowercase())
+        .collect::<Vec<String>>()
+        .join("_")
+}
@@ -0,0 +1,52 @@
+//! 写入之前校验模型发现的错误，一次报告所有的问题。
+//! 具体的错误类型由各个语言定义，可以用 `downcast` 取回
+
+use std::fmt;
+
+use failure::Fail;
+
+/// 校验错误的集合，保留每个错误原来的类型
+#[derive(Debug, Default)]
+pub struct ValidationErrors {
+    errors: Vec<Box<dyn Fail>>,
+}
+
+impl ValidationErrors {
+    pub fn new() -> ValidationErrors {
+        ValidationErrors::default()
+    }
+
+    pub fn add_error(&mut self, error: impl Fail) {
+        self.errors.push(Box::new(error));
+    }
+
+    pub fn errors(&self) -> &[Box<dyn Fail>] {
+        &self.errors
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.errors.len()
+    }
+
+    /// 类型为 T 的错误，如 `errors.downcast::<JavaValidationError>()`
+    pub fn downcast<T: Fail>(&self) -> impl Iterator<Item = &T> {
+        self.errors.iter().filter_map(|error| error.downcast_ref::<T>())
+    }
+}
+
+/// 每个错误一行
+impl fmt::Display for ValidationErrors {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, error) in self.errors.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", error)?;
+        }
+        Ok(())
+    }
+}
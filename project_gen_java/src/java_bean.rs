@@ -0,0 +1,253 @@
+use project_gen_core::naming;
+
+use super::{
+    CodeBlock,
+    JavaAnnotationDeclaration,
+    JavaFieldDeclaration,
+    JavaMethodDeclaration,
+    JavaMethodParameter,
+    JavaTypeDeclaration,
+    Modifier,
+    TypeName,
+};
+
+/// 生成 getter/setter、equals/hashCode/toString、构造方法以及 builder 时的选项
+#[derive(Debug, Clone, Default)]
+pub struct BeanOptions {
+    /// 不参与生成的字段
+    pub exclude: Vec<String>,
+    /// 不可变的类：类和字段为 final，不生成 setter
+    pub immutable: bool,
+}
+
+impl BeanOptions {
+    pub fn new() -> BeanOptions {
+        BeanOptions::default()
+    }
+
+    pub fn exclude(&mut self, field: &str) {
+        self.exclude.push(field.to_owned());
+    }
+
+    pub fn set_immutable(&mut self, immutable: bool) {
+        self.immutable = immutable;
+    }
+}
+
+impl JavaTypeDeclaration {
+    /// 参与生成的字段：非静态、没有被排除，并且不是已经初始化的 final 字段
+    fn bean_fields(&self, options: &BeanOptions) -> Vec<&JavaFieldDeclaration> {
+        self.fields
+            .iter()
+            .filter(|f| !f.modifiers.is_static())
+            .filter(|f| !options.exclude.contains(&f.name))
+            .filter(|f| !(f.modifiers.is_final() && f.value.is_some()))
+            .collect()
+    }
+
+    /// 类型本身的写法，泛型类型使用通配符，如 `Pair<?, ?>`
+    fn wildcard_type(&self) -> String {
+        if self.type_parameters.is_empty() {
+            return self.name.clone();
+        }
+        let wildcards = vec!["?"; self.type_parameters.len()];
+        format!("{}<{}>", self.name, wildcards.join(", "))
+    }
+
+    /// 类型本身的写法，泛型类型带上类型变量，如 `Pair<K, V>`
+    fn self_type(&self) -> String {
+        if self.type_parameters.is_empty() {
+            return self.name.clone();
+        }
+        let names: Vec<&str> = self.type_parameters.iter().map(|t| t.name.as_str()).collect();
+        format!("{}<{}>", self.name, names.join(", "))
+    }
+
+    /// 按选项把类和字段设为 final，只在 immutable 时生效
+    pub fn apply_bean_options(&mut self, options: &BeanOptions) {
+        if !options.immutable {
+            return;
+        }
+        self.modifiers.insert(Modifier::FINAL);
+        for field in self.fields.iter_mut() {
+            if !field.modifiers.is_static() && !options.exclude.contains(&field.name) {
+                field.modifiers.insert(Modifier::FINAL);
+            }
+        }
+    }
+
+    /// 字段的 getter 和 setter，boolean 字段的 getter 以 is 开头，
+    /// 不可变的类以及 final 字段没有 setter
+    pub fn add_accessors(&mut self, options: &BeanOptions) {
+        let mut methods = vec![];
+        for field in self.bean_fields(options) {
+            let property = naming::pascal_case(&field.name);
+            let prefix = if field.return_type == TypeName::primitive("boolean") { "is" } else { "get" };
+            let mut getter = JavaMethodDeclaration::new(&format!("{}{}", prefix, property), field.return_type.clone(), Modifier::PUBLIC);
+            getter.add_code(CodeBlock::of("return this.$N;\n", vec![field.name.as_str().into()]));
+            methods.push(getter);
+
+            if options.immutable || field.modifiers.is_final() {
+                continue;
+            }
+            let mut setter = JavaMethodDeclaration::new(&format!("set{}", property), "void", Modifier::PUBLIC);
+            setter.add_parameter(JavaMethodParameter::new(&field.name, field.return_type.clone()));
+            setter.add_code(CodeBlock::of("this.$N = $N;\n", vec![field.name.as_str().into(), field.name.as_str().into()]));
+            methods.push(setter);
+        }
+        self.methods.extend(methods);
+    }
+
+    /// 基于字段的 equals 和 hashCode，数组使用 Arrays 比较
+    pub fn add_equals_and_hash_code(&mut self, options: &BeanOptions) {
+        let fields = self.bean_fields(options);
+        let wildcard_type = self.wildcard_type();
+
+        let mut equals = JavaMethodDeclaration::new("equals", "boolean", Modifier::PUBLIC);
+        equals.add_annotation(JavaAnnotationDeclaration::new("java.lang.Override"));
+        equals.add_parameter(JavaMethodParameter::new("o", "java.lang.Object"));
+        let mut code = CodeBlock::new();
+        code.begin_control_flow("if (this == o)", vec![])
+            .add_statement("return true", vec![])
+            .end_control_flow()
+            .begin_control_flow("if (o == null || getClass() != o.getClass())", vec![])
+            .add_statement("return false", vec![])
+            .end_control_flow();
+        if fields.is_empty() {
+            code.add_statement("return true", vec![]);
+        } else {
+            code.add_statement("$L other = ($L) o", vec![wildcard_type.as_str().into(), wildcard_type.as_str().into()]);
+            code.add("return ", vec![]);
+            for (i, field) in fields.iter().enumerate() {
+                if i > 0 {
+                    code.add("\n$>$>&& ", vec![]);
+                }
+                let name = field.name.as_str();
+                match &field.return_type {
+                    TypeName::Primitive(primitive) if primitive == "float" || primitive == "double" => {
+                        let wrapper = if primitive == "float" { "java.lang.Float" } else { "java.lang.Double" };
+                        code.add("$T.compare($N, other.$N) == 0", vec![wrapper.into(), name.into(), name.into()]);
+                    },
+                    TypeName::Primitive(_) => {
+                        code.add("$N == other.$N", vec![name.into(), name.into()]);
+                    },
+                    TypeName::Array(_) => {
+                        code.add("$T.equals($N, other.$N)", vec!["java.util.Arrays".into(), name.into(), name.into()]);
+                    },
+                    _ => {
+                        code.add("$T.equals($N, other.$N)", vec!["java.util.Objects".into(), name.into(), name.into()]);
+                    },
+                }
+                if i > 0 {
+                    code.add("$<$<", vec![]);
+                }
+            }
+            code.add(";\n", vec![]);
+        }
+        equals.add_code(code);
+
+        let mut hash_code = JavaMethodDeclaration::new("hashCode", "int", Modifier::PUBLIC);
+        hash_code.add_annotation(JavaAnnotationDeclaration::new("java.lang.Override"));
+        let (arrays, others): (Vec<&&JavaFieldDeclaration>, Vec<&&JavaFieldDeclaration>) =
+            fields.iter().partition(|f| matches!(f.return_type, TypeName::Array(_)));
+        let others: Vec<&str> = others.iter().map(|f| f.name.as_str()).collect();
+        let mut code = CodeBlock::new();
+        if arrays.is_empty() {
+            code.add_statement("return $T.hash($L)", vec!["java.util.Objects".into(), others.join(", ").into()]);
+        } else {
+            code.add_statement("int result = $T.hash($L)", vec!["java.util.Objects".into(), others.join(", ").into()]);
+            for field in arrays {
+                code.add_statement("result = 31 * result + $T.hashCode($N)", vec!["java.util.Arrays".into(), field.name.as_str().into()]);
+            }
+            code.add_statement("return result", vec![]);
+        }
+        hash_code.add_code(code);
+
+        self.methods.push(equals);
+        self.methods.push(hash_code);
+    }
+
+    /// 基于字段的 toString，如 `Person{id=1, name=a}`
+    pub fn add_to_string(&mut self, options: &BeanOptions) {
+        let fields = self.bean_fields(options);
+        let mut to_string = JavaMethodDeclaration::new("toString", "java.lang.String", Modifier::PUBLIC);
+        to_string.add_annotation(JavaAnnotationDeclaration::new("java.lang.Override"));
+        let mut code = CodeBlock::new();
+        code.add("return $S", vec![format!("{}{{", self.name).into()]);
+        code.add("$>$>", vec![]);
+        for (i, field) in fields.iter().enumerate() {
+            let label = format!("{}{}=", if i > 0 { ", " } else { "" }, field.name);
+            match &field.return_type {
+                TypeName::Array(_) => {
+                    code.add("\n+ $S + $T.toString($N)", vec![label.into(), "java.util.Arrays".into(), field.name.as_str().into()]);
+                },
+                _ => {
+                    code.add("\n+ $S + $N", vec![label.into(), field.name.as_str().into()]);
+                },
+            }
+        }
+        code.add("\n+ $S;\n$<$<", vec!["}".into()]);
+        to_string.add_code(code);
+        self.methods.push(to_string);
+    }
+
+    /// 以所有字段为参数的构造方法，已经存在相同参数的构造方法时不再添加
+    pub fn add_all_args_constructor(&mut self, options: &BeanOptions) {
+        let fields = self.bean_fields(options);
+        let names: Vec<&str> = fields.iter().map(|f| f.name.as_str()).collect();
+        let exists = self.constructors.iter().any(|c| {
+            c.parameters.iter().map(|p| p.name.as_str()).collect::<Vec<&str>>() == names
+        });
+        if exists {
+            return;
+        }
+        let mut constructor = JavaMethodDeclaration::new_constructor(Modifier::PUBLIC);
+        let mut code = CodeBlock::new();
+        for field in fields {
+            constructor.add_parameter(JavaMethodParameter::new(&field.name, field.return_type.clone()));
+            code.add_statement("this.$N = $N", vec![field.name.as_str().into(), field.name.as_str().into()]);
+        }
+        constructor.add_code(code);
+        self.constructors.push(constructor);
+    }
+
+    /// 静态嵌套的 Builder 类以及 `builder()` 方法，build 通过所有字段的构造方法创建对象
+    pub fn add_builder(&mut self, options: &BeanOptions) {
+        self.add_all_args_constructor(options);
+        let fields = self.bean_fields(options);
+        let self_type = self.self_type();
+        let builder_type = match self_type.find('<') {
+            Some(index) => format!("Builder{}", &self_type[index..]),
+            None => "Builder".to_string(),
+        };
+        let diamond = if self.type_parameters.is_empty() { "" } else { "<>" };
+
+        let mut builder = JavaTypeDeclaration::new(Modifier::PUBLIC | Modifier::STATIC, "Builder", None);
+        builder.set_javadoc(format!("{} 的构造器", self.name));
+        builder.type_parameters = self.type_parameters.clone();
+        let mut arguments = vec![];
+        for field in &fields {
+            builder.add_field(JavaFieldDeclaration::new(&field.name, field.return_type.clone(), Modifier::PRIVATE, None));
+            let mut setter = JavaMethodDeclaration::new(&field.name, builder_type.as_str(), Modifier::PUBLIC);
+            setter.add_parameter(JavaMethodParameter::new(&field.name, field.return_type.clone()));
+            let mut code = CodeBlock::new();
+            code.add_statement("this.$N = $N", vec![field.name.as_str().into(), field.name.as_str().into()])
+                .add_statement("return this", vec![]);
+            setter.add_code(code);
+            builder.add_method(setter);
+            arguments.push(field.name.clone());
+        }
+        let mut build = JavaMethodDeclaration::new("build", self_type.as_str(), Modifier::PUBLIC);
+        build.add_code(CodeBlock::of(
+            "return new $L$L($L);\n",
+            vec![self.name.as_str().into(), diamond.into(), arguments.join(", ").into()],
+        ));
+        builder.add_method(build);
+
+        let mut builder_method = JavaMethodDeclaration::new("builder", builder_type.as_str(), Modifier::PUBLIC | Modifier::STATIC);
+        builder_method.type_parameters = self.type_parameters.clone();
+        builder_method.add_code(CodeBlock::of("return new Builder$L();\n", vec![diamond.into()]));
+        self.methods.push(builder_method);
+        self.types.push(builder);
+    }
+}
@@ -0,0 +1,469 @@
+use super::{ClassName, JavaFeature, JavaMethodStatement, JavaTypeDeclaration, TypeName, REGION_BEGIN, REGION_END};
+
+/// CodeBlock 中占位符对应的参数
+pub enum CodeArg {
+    /// `$N`、`$S`、`$L` 原样使用，`$T` 解析为类型
+    Text(String),
+    Type(TypeName),
+    /// 只用于 `$L`，嵌入另一个代码块
+    Block(CodeBlock),
+    /// 只用于 `$L`，匿名类
+    Class(Box<JavaTypeDeclaration>),
+}
+
+impl From<&str> for CodeArg {
+    fn from(text: &str) -> CodeArg {
+        CodeArg::Text(text.to_owned())
+    }
+}
+
+impl From<&String> for CodeArg {
+    fn from(text: &String) -> CodeArg {
+        CodeArg::Text(text.clone())
+    }
+}
+
+impl From<String> for CodeArg {
+    fn from(text: String) -> CodeArg {
+        CodeArg::Text(text)
+    }
+}
+
+impl From<TypeName> for CodeArg {
+    fn from(type_name: TypeName) -> CodeArg {
+        CodeArg::Type(type_name)
+    }
+}
+
+impl From<ClassName> for CodeArg {
+    fn from(class_name: ClassName) -> CodeArg {
+        CodeArg::Type(TypeName::Class(class_name))
+    }
+}
+
+impl From<CodeBlock> for CodeArg {
+    fn from(block: CodeBlock) -> CodeArg {
+        CodeArg::Block(block)
+    }
+}
+
+impl From<JavaTypeDeclaration> for CodeArg {
+    fn from(anonymous_class: JavaTypeDeclaration) -> CodeArg {
+        CodeArg::Class(Box::new(anonymous_class))
+    }
+}
+
+pub(crate) enum CodePart {
+    Text(String),
+    Type(TypeName),
+    Class(Box<JavaTypeDeclaration>),
+    Indent,
+    Unindent,
+    /// 代码中使用了需要较新版本的语法，不输出任何内容
+    Requires(JavaFeature),
+    /// 控制结构的 `{`，之后增加缩进，位置由格式决定
+    OpenBrace,
+    /// `} else` 中 `}` 与后续部分之间的分隔，位置由格式决定
+    BraceSeparator,
+    /// 格式与参数不匹配，写入时返回错误
+    Invalid(String),
+}
+
+/// 方法体等多行代码，格式与 JavaPoet 相同：
+/// `$T` 类型（会被导入），`$N` 名称，`$S` 字符串字面量（会被转义），`$L` 原样输出，
+/// `$$` 为 `$`，`$>`、`$<` 增加和减少缩进。
+/// 格式与参数不匹配时写入会返回错误
+#[derive(Default)]
+pub struct CodeBlock {
+    pub(crate) parts: Vec<CodePart>,
+}
+
+impl CodeBlock {
+    pub fn new() -> CodeBlock {
+        CodeBlock { parts: vec![] }
+    }
+
+    /// 由一段格式创建代码块，常用于 `$L` 参数
+    pub fn of(format: &str, args: Vec<CodeArg>) -> CodeBlock {
+        let mut block = CodeBlock::new();
+        block.add(format, args);
+        block
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parts.is_empty()
+    }
+
+    /// 添加一段代码，不会自动换行
+    pub fn add(&mut self, format: &str, args: Vec<CodeArg>) -> &mut CodeBlock {
+        if let Err(message) = self.add_format(format, args) {
+            self.parts.push(CodePart::Invalid(message));
+        }
+        self
+    }
+
+    fn add_format(&mut self, format: &str, args: Vec<CodeArg>) -> Result<(), String> {
+        let mut args = args.into_iter();
+        let mut text = String::new();
+        let mut chars = format.chars();
+        while let Some(c) = chars.next() {
+            if c != '$' {
+                text.push(c);
+                continue;
+            }
+            let placeholder = chars.next();
+            match placeholder {
+                Some('$') => text.push('$'),
+                Some('>') => {
+                    self.push_text(&mut text);
+                    self.parts.push(CodePart::Indent);
+                },
+                Some('<') => {
+                    self.push_text(&mut text);
+                    self.parts.push(CodePart::Unindent);
+                },
+                Some(p @ ('T' | 'N' | 'S' | 'L')) => {
+                    let arg = args
+                        .next()
+                        .ok_or_else(|| format!("missing argument for ${} in `{}`", p, format))?;
+                    match (p, arg) {
+                        ('T', CodeArg::Text(name)) => {
+                            self.push_text(&mut text);
+                            self.parts.push(CodePart::Type(TypeName::parse(&name)));
+                        },
+                        ('T' | 'L', CodeArg::Type(type_name)) => {
+                            self.push_text(&mut text);
+                            self.parts.push(CodePart::Type(type_name));
+                        },
+                        ('N' | 'L', CodeArg::Text(value)) => text.push_str(&value),
+                        ('S', CodeArg::Text(value)) => {
+                            text.push('"');
+                            text.push_str(&escape_java_string(&value));
+                            text.push('"');
+                        },
+                        ('L', CodeArg::Block(block)) => {
+                            self.push_text(&mut text);
+                            self.parts.extend(block.parts);
+                        },
+                        ('L', CodeArg::Class(anonymous_class)) => {
+                            self.push_text(&mut text);
+                            self.parts.push(CodePart::Class(anonymous_class));
+                        },
+                        (p, _) => return Err(format!("invalid argument for ${} in `{}`", p, format)),
+                    }
+                },
+                Some(p) => return Err(format!("unknown placeholder ${} in `{}`", p, format)),
+                None => return Err(format!("dangling $ in `{}`", format)),
+            }
+        }
+        self.push_text(&mut text);
+        if args.next().is_some() {
+            return Err(format!("unused arguments for `{}`", format));
+        }
+        Ok(())
+    }
+
+    fn push_text(&mut self, text: &mut String) {
+        if !text.is_empty() {
+            self.parts.push(CodePart::Text(std::mem::take(text)));
+        }
+    }
+
+    /// 添加一条语句，自动添加 `;` 以及换行
+    pub fn add_statement(&mut self, format: &str, args: Vec<CodeArg>) -> &mut CodeBlock {
+        self.add(format, args);
+        self.add(";\n", vec![])
+    }
+
+    /// 开始一个控制结构，如 `if (a)`、`for (A a : list)`、`try`、`switch (a)`，
+    /// lambda 写作 `$T f = (a) ->` 并以 `end_control_flow_with("")` 结束
+    pub fn begin_control_flow(&mut self, format: &str, args: Vec<CodeArg>) -> &mut CodeBlock {
+        self.add(format, args);
+        self.parts.push(CodePart::OpenBrace);
+        self
+    }
+
+    /// 控制结构的下一部分，如 `else if (b)`、`else`、`catch (Exception e)`、`finally`
+    pub fn next_control_flow(&mut self, format: &str, args: Vec<CodeArg>) -> &mut CodeBlock {
+        self.add("$<}", vec![]);
+        self.parts.push(CodePart::BraceSeparator);
+        self.add(format, args);
+        self.parts.push(CodePart::OpenBrace);
+        self
+    }
+
+    pub fn end_control_flow(&mut self) -> &mut CodeBlock {
+        self.add("$<}\n", vec![])
+    }
+
+    /// 以 `}` 加上 format 和 `;` 结束，如 `} while (a);`、`};`、`});`
+    pub fn end_control_flow_with(&mut self, format: &str, args: Vec<CodeArg>) -> &mut CodeBlock {
+        self.add("$<}", vec![]);
+        self.add(format, args);
+        self.add(";\n", vec![])
+    }
+
+    /// switch 中的一个分支，如 `case 1:`、`default:`
+    pub fn begin_case(&mut self, format: &str, args: Vec<CodeArg>) -> &mut CodeBlock {
+        self.add(format, args);
+        self.add("\n$>", vec![])
+    }
+
+    pub fn end_case(&mut self) -> &mut CodeBlock {
+        self.add("$<", vec![])
+    }
+
+    /// 开始一个 switch 表达式，如 `return switch (a)`，分支使用 add_switch_rule，需要 Java 14
+    pub fn begin_switch_expression(&mut self, format: &str, args: Vec<CodeArg>) -> &mut CodeBlock {
+        self.require(JavaFeature::SwitchExpressions);
+        self.begin_control_flow(format, args)
+    }
+
+    /// `case A -> b` 形式的分支，自动添加 `;` 以及换行，需要 Java 14
+    pub fn add_switch_rule(&mut self, format: &str, args: Vec<CodeArg>) -> &mut CodeBlock {
+        self.require(JavaFeature::SwitchExpressions);
+        self.add_statement(format, args)
+    }
+
+    /// 以 `};` 结束 switch 表达式
+    pub fn end_switch_expression(&mut self) -> &mut CodeBlock {
+        self.end_control_flow_with("", vec![])
+    }
+
+    /// 标记代码使用了需要较新版本的语法，写入时会检查目标版本
+    pub fn require(&mut self, feature: JavaFeature) -> &mut CodeBlock {
+        self.parts.push(CodePart::Requires(feature));
+        self
+    }
+
+    /// 单行注释，每行以 `// ` 开头
+    pub fn add_comment(&mut self, comment: &str) -> &mut CodeBlock {
+        for line in comment.lines() {
+            let line = format!("// {}", line);
+            self.parts.push(CodePart::Text(line.trim_end().to_owned() + "\n"));
+        }
+        self
+    }
+
+    /// 保护区域，重新生成时保留区域中手写的代码
+    pub fn add_protected_region(&mut self, name: &str) -> &mut CodeBlock {
+        self.parts.push(CodePart::Text(format!("{} {}\n", REGION_BEGIN, name)));
+        self.parts.push(CodePart::Text(format!("{}\n", REGION_END)));
+        self
+    }
+
+    /// 块注释 `/* ... */`，中间每行以 ` * ` 开头
+    pub fn add_block_comment(&mut self, comment: &str) -> &mut CodeBlock {
+        let mut text = String::from("/*\n");
+        for line in comment.replace("*/", "*&#47;").lines() {
+            text.push_str(format!(" * {}", line).trim_end());
+            text.push('\n');
+        }
+        text.push_str(" */\n");
+        self.parts.push(CodePart::Text(text));
+        self
+    }
+
+    pub fn indent(&mut self) -> &mut CodeBlock {
+        self.parts.push(CodePart::Indent);
+        self
+    }
+
+    pub fn unindent(&mut self) -> &mut CodeBlock {
+        self.parts.push(CodePart::Unindent);
+        self
+    }
+
+    /// 追加另一个代码块
+    pub fn add_code(&mut self, block: CodeBlock) -> &mut CodeBlock {
+        self.parts.extend(block.parts);
+        self
+    }
+
+    /// 代码中引用的类，包括匿名类中引用的类
+    pub fn determine_imports(&self) -> Vec<ClassName> {
+        let mut imports = vec![];
+        for part in &self.parts {
+            match part {
+                CodePart::Type(type_name) => imports.extend(type_name.classes().into_iter().map(|c| c.top_level())),
+                CodePart::Class(anonymous_class) => imports.extend(anonymous_class.determine_imports()),
+                _ => {},
+            }
+        }
+        imports
+    }
+}
+
+/// 单行语句，`$T` 为类型，`$V` 原样输出，`$A` 依次为语句中的匿名类
+impl From<JavaMethodStatement> for CodeBlock {
+    fn from(statement: JavaMethodStatement) -> CodeBlock {
+        let mut block = CodeBlock::new();
+        let mut args = statement.args.into_iter();
+        let mut anonymous_classes = statement.anonymous_classes.into_iter();
+        let mut text = String::new();
+        let mut chars = statement.statement.chars();
+        while let Some(c) = chars.next() {
+            if c != '$' {
+                text.push(c);
+                continue;
+            }
+            match chars.next() {
+                Some('A') => {
+                    if let Some(anonymous_class) = anonymous_classes.next() {
+                        block.push_text(&mut text);
+                        block.parts.push(CodePart::Class(Box::new(anonymous_class)));
+                    }
+                },
+                Some('T') => {
+                    if let Some(arg) = args.next() {
+                        block.push_text(&mut text);
+                        block.parts.push(CodePart::Type(TypeName::parse(&arg)));
+                    }
+                },
+                Some('V') => text.push_str(&args.next().unwrap_or_default()),
+                Some(other) => {
+                    args.next();
+                    text.push('$');
+                    text.push(other);
+                },
+                None => text.push('$'),
+            }
+        }
+        text.push('\n');
+        block.push_text(&mut text);
+        block
+    }
+}
+
+/// 转义java字符串字面量中的特殊字符，非ASCII字符原样保留
+pub(crate) fn escape_java_string(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// 转义java字符字面量
+pub(crate) fn escape_java_char(value: char) -> String {
+    match value {
+        '\'' => "\\'".to_string(),
+        '"' => "\"".to_string(),
+        c => escape_java_string(&c.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JavaCompilationUnit, JavaMethodDeclaration, Modifier};
+
+    /// 把代码块作为 `Test.run()` 的方法体，返回整个编译单元的源码
+    fn render(code: CodeBlock) -> project_gen_core::Result<String> {
+        let mut method = JavaMethodDeclaration::new("run", "void", Modifier::PUBLIC);
+        method.add_code(code);
+        let mut type_declaration = JavaTypeDeclaration::new(Modifier::PUBLIC, "Test", None);
+        type_declaration.add_method(method);
+        let mut compilation_unit = JavaCompilationUnit::new("com.example", "Test");
+        compilation_unit.add_type_declaration(type_declaration);
+        compilation_unit.render()
+    }
+
+    fn text(code: &CodeBlock) -> String {
+        code.parts.iter().filter_map(|part| match part {
+            CodePart::Text(text) => Some(text.as_str()),
+            _ => None,
+        }).collect()
+    }
+
+    #[test]
+    fn string_literal_is_escaped() {
+        let code = CodeBlock::of("$S", vec!["say \"hi\" \\ 你好\n\t".into()]);
+        assert_eq!(text(&code), r#""say \"hi\" \\ 你好\n\t""#);
+    }
+
+    #[test]
+    fn dollar_escape_and_names() {
+        let code = CodeBlock::of("$N.$L = $$1", vec!["this".into(), "value".into()]);
+        assert_eq!(text(&code), "this.value = $1");
+    }
+
+    #[test]
+    fn type_records_import() {
+        let mut code = CodeBlock::new();
+        code.add_statement("$T<$T> names = new $T<>()", vec!["java.util.List".into(), "java.lang.String".into(), "java.util.ArrayList".into()]);
+        let imports: Vec<String> = code.determine_imports().iter().map(|c| c.canonical_name()).collect();
+        assert_eq!(imports, vec!["java.util.List", "java.lang.String", "java.util.ArrayList"]);
+        let source = render(code).unwrap();
+        assert!(source.contains("import java.util.ArrayList;\nimport java.util.List;\n"));
+        assert!(source.contains("    List<String> names = new ArrayList<>();\n"));
+    }
+
+    #[test]
+    fn nested_control_flow_indentation() {
+        let mut code = CodeBlock::new();
+        code.begin_control_flow("for (int i = 0; i < 3; i++)", vec![])
+            .begin_control_flow("if (i == 0)", vec![])
+            .add_statement("first()", vec![])
+            .next_control_flow("else if (i == 1)", vec![])
+            .add_statement("second()", vec![])
+            .next_control_flow("else", vec![])
+            .begin_control_flow("try", vec![])
+            .add_statement("other()", vec![])
+            .next_control_flow("catch ($T e)", vec!["java.lang.RuntimeException".into()])
+            .add_statement("throw e", vec![])
+            .end_control_flow()
+            .end_control_flow()
+            .end_control_flow();
+        let source = render(code).unwrap();
+        assert_eq!(source, "\
+package com.example;
+
+public class Test {
+  public void run() {
+    for (int i = 0; i < 3; i++) {
+      if (i == 0) {
+        first();
+      } else if (i == 1) {
+        second();
+      } else {
+        try {
+          other();
+        } catch (RuntimeException e) {
+          throw e;
+        }
+      }
+    }
+  }
+}
+");
+    }
+
+    #[test]
+    fn missing_argument_is_an_error() {
+        let code = CodeBlock::of("$S + $S", vec!["a".into()]);
+        assert!(matches!(code.parts.last(), Some(CodePart::Invalid(_))));
+        let error = render(code).unwrap_err().to_string();
+        assert!(error.contains("missing argument for $S in `$S + $S`"), "{}", error);
+    }
+
+    #[test]
+    fn unused_argument_is_an_error() {
+        let code = CodeBlock::of("$L", vec!["a".into(), "b".into()]);
+        let error = render(code).unwrap_err().to_string();
+        assert!(error.contains("unused arguments for `$L`"), "{}", error);
+    }
+
+    #[test]
+    fn unknown_placeholder_is_an_error() {
+        assert!(render(CodeBlock::of("$X", vec![])).is_err());
+        assert!(render(CodeBlock::of("a $", vec![])).is_err());
+    }
+}
@@ -0,0 +1,1259 @@
+use std::io::Write;
+
+use project_gen_core::{Result, naming};
+use project_gen_sql::{Dialect, SqlSchemaGenerator, SqlSeedGenerator};
+use project_gen_parser::{
+    ApiNode,
+    Cardinality,
+    ConditionNode,
+    DatasourceNode,
+    Direction,
+    Documentation,
+    DtoFieldSource,
+    DtoNode,
+    EntityId,
+    EntityNode,
+    FieldRole,
+    FieldType,
+    FilterNode,
+    ListingNode,
+    Model,
+    Operator,
+    QueryNode,
+    QuerySubject,
+    ScalarType,
+    SeedValue,
+};
+
+use super::{
+    AnnotationValue,
+    BeanOptions,
+    Build,
+    CodeBlock,
+    Configuration,
+    Javadoc,
+    Dependency,
+    FormatOptions,
+    JavaAnnotationAttribute,
+    JavaAnnotationDeclaration,
+    JavaCompilationUnit,
+    JavaFeature,
+    JavaFieldDeclaration,
+    JavaMethodDeclaration,
+    JavaMethodParameter,
+    JavaMethodStatement,
+    JavaSourceCode,
+    JavaSourceCodeWriter,
+    JavaSourceStructure,
+    JavaTypeDeclaration,
+    JavaVersion,
+    Modifier,
+    POM,
+    Property,
+    TypeParameter,
+    ValueType,
+};
+
+const ENTITY_PACKAGE: &str = "entity";
+const REPOSITORY_PACKAGE: &str = "repository";
+const CONFIG_PACKAGE: &str = "config";
+const DTO_PACKAGE: &str = "dto";
+const MAPPER_PACKAGE: &str = "mapper";
+const SERVICE_PACKAGE: &str = "service";
+
+const JPA_REPOSITORY: &str = "org.springframework.data.jpa.repository.JpaRepository";
+const JPA_SPECIFICATION_EXECUTOR: &str = "org.springframework.data.jpa.repository.JpaSpecificationExecutor";
+const PAGE_RESPONSE: &str = "PageResponse";
+/// 列表请求未指定 size 时每页的数量
+const DEFAULT_PAGE_SIZE: u32 = 20;
+const MYSQL_DRIVER: &str = "com.mysql.cj.jdbc.Driver";
+const SPRINGDOC_VERSION: &str = "1.6.15";
+/// 每个数据源的 Flyway 脚本目录，位于资源目录下
+const MIGRATION_LOCATION: &str = "db/migration";
+
+/// 超过该长度的查询使用文本块
+const LONG_QUERY_LENGTH: usize = 80;
+const LOMBOK: &str = "org.projectlombok";
+const MAVEN_PLUGINS: &str = "org.apache.maven.plugins";
+/// 类型末尾的保护区域
+const CUSTOM_REGION: &str = "custom";
+/// 导入之后的保护区域
+const IMPORTS_REGION: &str = "imports";
+
+/// 实体和 DTO 的生成方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JavaCodeStyle {
+    /// 显式生成 getter 和 setter
+    #[default]
+    Plain,
+    /// 使用 Lombok 的注解代替显式的方法，Service 使用 `@Slf4j`
+    Lombok,
+}
+
+/// 重新生成时保留实体和 Service 中手写代码的方式
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RegenerationMode {
+    /// 在导入之后以及类型末尾生成 `// @pg:begin` 和 `// @pg:end` 标记的保护区域，
+    /// 重新生成时保留区域中的内容
+    #[default]
+    ProtectedRegions,
+    /// 生成的代码放在抽象的 `AbstractPersonBase` 中，子类 `Person` 只在文件不存在时生成
+    GenerationGap,
+}
+
+/// 根据语义模型生成实体、Repository、Service 以及数据源配置
+/// 每个数据源的实体和 Repository 生成在各自的子包中，并拥有独立的
+/// DataSource、EntityManagerFactory 和事务管理器
+pub struct JavaDataGenerator {
+    pub base_package: String,
+    pub style: JavaCodeStyle,
+    /// 16 及以上的版本 DTO 生成为 record，较长的查询使用文本块
+    pub java_version: JavaVersion,
+    pub format: FormatOptions,
+    pub regeneration: RegenerationMode,
+    /// seed 脚本使用的数据库方言
+    pub dialect: Dialect,
+}
+
+impl JavaDataGenerator {
+    pub fn new(base_package: &str) -> JavaDataGenerator {
+        JavaDataGenerator {
+            base_package: base_package.to_owned(),
+            style: JavaCodeStyle::default(),
+            java_version: JavaVersion::default(),
+            format: FormatOptions::default(),
+            regeneration: RegenerationMode::default(),
+            dialect: Dialect::default(),
+        }
+    }
+
+    pub fn set_java_version(&mut self, java_version: JavaVersion) {
+        self.java_version = java_version;
+    }
+
+    pub fn set_style(&mut self, style: JavaCodeStyle) {
+        self.style = style;
+    }
+
+    pub fn set_format(&mut self, format: FormatOptions) {
+        self.format = format;
+    }
+
+    pub fn set_regeneration_mode(&mut self, regeneration: RegenerationMode) {
+        self.regeneration = regeneration;
+    }
+
+    pub fn set_dialect(&mut self, dialect: Dialect) {
+        self.dialect = dialect;
+    }
+
+    /// 生成java源码并写入 application.yml 以及每个数据源的建表语句
+    /// 声明了 seed 的数据源由 Flyway 执行脚本，建表语句写为 versioned migration `V1__schema.sql`，
+    /// seed 写为 repeatable migration，在建表之后执行，同时写入测试使用的 json 数据
+    pub fn write(&self, structure: &JavaSourceStructure, model: &Model) -> Result<()> {
+        let mut writer = JavaSourceCodeWriter::new();
+        writer.set_version(self.java_version);
+        writer.set_format(self.format.clone());
+        writer.write(structure, self.generate(model))?;
+        let mut file = structure.create_resource_file("application.yml")?;
+        file.write_all(self.generate_application_yml(model).as_bytes())?;
+        let schema_generator = SqlSchemaGenerator::new();
+        let seed_generator = SqlSeedGenerator::new(self.dialect);
+        for datasource in &model.datasources {
+            if !model.has_seed(datasource) {
+                let mut file = structure.create_resource_file(&format!("db/{}/schema.sql", datasource.package))?;
+                file.write_all(schema_generator.generate(model, datasource).as_bytes())?;
+                continue;
+            }
+            let mut file = structure.create_resource_file(&format!("{}/V1__schema.sql", self.migration_location(datasource)))?;
+            file.write_all(schema_generator.generate(model, datasource).as_bytes())?;
+            let mut file = structure.create_resource_file(&format!("{}/R__seed_data.sql", self.migration_location(datasource)))?;
+            file.write_all(seed_generator.generate(model, datasource).as_bytes())?;
+            for entity in datasource.entities.iter().map(|id| model.entity(*id)).filter(|e| !e.seed.is_empty()) {
+                let name = format!("fixtures/{}/{}.json", datasource.package, property_name(&entity.name));
+                let mut file = structure.create_test_resource_file(&name)?;
+                file.write_all(self.generate_fixture(model, entity).as_bytes())?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn generate(&self, model: &Model) -> JavaSourceCode {
+        let mut source_code = JavaSourceCode::new();
+        for datasource in &model.datasources {
+            for entity in datasource.entities.iter().map(|id| model.entity(*id)) {
+                for compilation_unit in self.generate_entity(model, entity) {
+                    source_code.add_compilation_unit(compilation_unit);
+                }
+                source_code.add_compilation_unit(self.generate_repository(model, entity));
+                if !entity.queries.is_empty() || listings(model, entity.id).next().is_some() {
+                    for compilation_unit in self.generate_service(model, entity) {
+                        source_code.add_compilation_unit(compilation_unit);
+                    }
+                }
+            }
+            source_code.add_compilation_unit(self.generate_datasource_config(model, datasource));
+        }
+        for dto in &model.dtos {
+            source_code.add_compilation_unit(self.generate_dto(model, dto));
+            source_code.add_compilation_unit(self.generate_mapper(model, dto));
+        }
+        let all_listings = model.apis.iter().flat_map(|api| api.requests.iter().filter_map(move |r| Some((api, r.name.as_str(), r.listing.as_ref()?))));
+        let mut paged = false;
+        for (api, request, listing) in all_listings {
+            paged |= listing.paged;
+            source_code.add_compilation_unit(self.generate_listing_request(model, api, request, listing));
+        }
+        if paged {
+            source_code.add_compilation_unit(self.generate_page_response());
+        }
+        source_code
+    }
+
+    /// 生成 spring.datasource 配置，每个数据源一个配置块
+    pub fn generate_application_yml(&self, model: &Model) -> String {
+        let mut yml = String::from("spring:\n  datasource:\n");
+        for datasource in &model.datasources {
+            yml.push_str(&format!("    {}:\n", property_name(&datasource.name)));
+            yml.push_str(&format!("      jdbc-url: jdbc:mysql://{}:{}/{}\n", datasource.host, datasource.port, datasource.database));
+            yml.push_str(&format!("      username: {}\n", yaml_string(&datasource.username)));
+            yml.push_str(&format!("      password: {}\n", yaml_string(&datasource.password)));
+            yml.push_str(&format!("      driver-class-name: {}\n", MYSQL_DRIVER));
+        }
+        if model.datasources.iter().any(|ds| model.has_seed(ds)) {
+            // 每个数据源在配置类中有独立的 Flyway，关闭只作用于主数据源的自动配置
+            yml.push_str("  flyway:\n    enabled: false\n");
+        }
+        yml
+    }
+
+    /// 生成的代码所需的 maven 依赖
+    pub fn dependencies(&self, model: &Model) -> Vec<Dependency> {
+        let mut mysql = Dependency::new("mysql", "mysql-connector-java", None);
+        mysql.set_scope("runtime".to_string());
+        let mut dependencies = vec![
+            Dependency::new("org.springframework.boot", "spring-boot-starter-data-jpa", None),
+            Dependency::new("org.springdoc", "springdoc-openapi-ui", Some(SPRINGDOC_VERSION)),
+            mysql,
+        ];
+        if model.datasources.iter().any(|ds| model.has_seed(ds)) {
+            dependencies.push(Dependency::new("org.flywaydb", "flyway-core", None));
+            if self.dialect == Dialect::MySql {
+                dependencies.push(Dependency::new("org.flywaydb", "flyway-mysql", None));
+            }
+        }
+        if self.style == JavaCodeStyle::Lombok {
+            let mut lombok = Dependency::new(LOMBOK, "lombok", None);
+            lombok.set_optional(true);
+            dependencies.push(lombok);
+        }
+        dependencies
+    }
+
+    /// 生成的代码对应的 POM，groupId 为基础包名
+    /// Lombok 需要在编译插件中配置注解处理器，并且不打包到 Spring Boot 的 jar 中
+    pub fn generate_pom(&self, model: &Model, artifact_id: &str) -> POM {
+        let mut pom = POM::new(&self.base_package, artifact_id);
+        pom.add_property(Property::new("maven.compiler.release", &self.java_version.release()));
+        for dependency in self.dependencies(model) {
+            pom.add_dependency(dependency);
+        }
+        if self.style != JavaCodeStyle::Lombok {
+            return pom;
+        }
+        let build = pom.build.get_or_insert_with(Build::default);
+        let mut path = Configuration::new("path");
+        path.add_child(Configuration::text("groupId", LOMBOK))
+            .add_child(Configuration::text("artifactId", "lombok"))
+            .add_child(Configuration::text("version", "${lombok.version}"));
+        let mut processor_paths = Configuration::new("annotationProcessorPaths");
+        processor_paths.add_child(path);
+        build.plugin(MAVEN_PLUGINS, "maven-compiler-plugin").add_configuration(processor_paths);
+
+        let mut exclude = Configuration::new("exclude");
+        exclude.add_child(Configuration::text("groupId", LOMBOK))
+            .add_child(Configuration::text("artifactId", "lombok"));
+        let mut excludes = Configuration::new("excludes");
+        excludes.add_child(exclude);
+        build.plugin("org.springframework.boot", "spring-boot-maven-plugin").add_configuration(excludes);
+        pom
+    }
+
+    /// 数据源的 Flyway 脚本目录
+    pub fn migration_location(&self, datasource: &DatasourceNode) -> String {
+        format!("{}/{}", MIGRATION_LOCATION, datasource.package)
+    }
+
+    /// 实体 seed 对应的 json 数据，属性名与实体类的字段一致，ref 写作 `{"id": 1}`
+    pub fn generate_fixture(&self, model: &Model, entity: &EntityNode) -> String {
+        let id_field = &entity.id_field().name;
+        let rows: Vec<String> = entity.seed.iter().map(|row| {
+            let mut properties = vec![format!("    {}: {}", json_string(id_field), row.id)];
+            for (index, value) in &row.values {
+                let field = &entity.fields[*index];
+                let is_ref = field.field_type.entity().is_some();
+                let id_name = field.field_type.entity().map(|id| model.entity(id).id_field().name.as_str()).unwrap_or_default();
+                properties.push(format!("    {}: {}", json_string(&field.name), json_value(value, is_ref.then_some(id_name))));
+            }
+            format!("  {{\n{}\n  }}", properties.join(",\n"))
+        }).collect();
+        format!("[\n{}\n]\n", rows.join(",\n"))
+    }
+
+    pub fn entity_package(&self, datasource: &DatasourceNode) -> String {
+        format!("{}.{}.{}", self.base_package, ENTITY_PACKAGE, datasource.package)
+    }
+
+    pub fn repository_package(&self, datasource: &DatasourceNode) -> String {
+        format!("{}.{}.{}", self.base_package, REPOSITORY_PACKAGE, datasource.package)
+    }
+
+    pub fn service_package(&self, datasource: &DatasourceNode) -> String {
+        format!("{}.{}.{}", self.base_package, SERVICE_PACKAGE, datasource.package)
+    }
+
+    pub fn config_package(&self) -> String {
+        format!("{}.{}", self.base_package, CONFIG_PACKAGE)
+    }
+
+    pub fn dto_package(&self) -> String {
+        format!("{}.{}", self.base_package, DTO_PACKAGE)
+    }
+
+    pub fn mapper_package(&self) -> String {
+        format!("{}.{}", self.base_package, MAPPER_PACKAGE)
+    }
+
+    /// 分页返回值的全限定名
+    pub fn page_response_class(&self) -> String {
+        format!("{}.{}", self.dto_package(), PAGE_RESPONSE)
+    }
+
+    /// 列表请求参数类的全限定名，如 `PersonListRequest`
+    pub fn listing_request_class(&self, api: &ApiNode, request: &str) -> String {
+        format!("{}.{}Request", self.dto_package(), naming::pascal_case(&format!("{}_{}", api.name, request)))
+    }
+
+    /// DTO 类的全限定名
+    pub fn dto_class(&self, dto: &DtoNode) -> String {
+        format!("{}.{}", self.dto_package(), naming::pascal_case(&dto.name))
+    }
+
+    /// 实体类的全限定名
+    pub fn entity_class(&self, model: &Model, id: EntityId) -> String {
+        let entity = model.entity(id);
+        format!("{}.{}", self.entity_package(model.datasource(entity.datasource)), naming::pascal_case(&entity.name))
+    }
+
+    /// 类型对应的java类型
+    pub fn java_type(&self, model: &Model, field_type: &FieldType) -> String {
+        match field_type {
+            FieldType::Scalar(scalar) => scalar_type(*scalar).to_string(),
+            FieldType::Entity(id) => self.entity_class(model, *id),
+            FieldType::List(element) => format!("java.util.List<{}>", self.java_type(model, element)),
+        }
+    }
+
+    fn generate_entity(&self, model: &Model, entity: &EntityNode) -> Vec<JavaCompilationUnit> {
+        let class_name = naming::pascal_case(&entity.name);
+        let mut type_declaration = self.generated_type(&class_name);
+        if let Some(doc) = entity.documentation.doc() {
+            type_declaration.set_javadoc(&doc);
+        }
+        // 逻辑删除的 @SQLDelete、@Where 只对实体生效，生成间隔模式下写在子类上
+        let mut annotations = vec![
+            JavaAnnotationDeclaration::new("javax.persistence.Entity"),
+            annotation("javax.persistence.Table", "name", &entity.table),
+        ];
+        annotations.extend(schema_annotation(&entity.documentation));
+        annotations.extend(self.behavior_annotations(entity));
+
+        for (index, field) in entity.fields.iter().enumerate() {
+            let field_type = self.java_type(model, &field.field_type);
+            // 新建的实体默认未删除
+            let value = (field.role == FieldRole::Deleted).then_some("false");
+            let mut field_declaration = JavaFieldDeclaration::new(&field.name, &field_type, Modifier::PRIVATE, value);
+            if let Some(doc) = field.documentation.doc() {
+                field_declaration.set_javadoc(&doc);
+            }
+            for annotation in self.field_annotations(model, entity, index) {
+                field_declaration.add_annotation(annotation);
+            }
+            if let Some(schema) = schema_annotation(&field.documentation) {
+                field_declaration.add_annotation(schema);
+            }
+            // 关联的实体可能相互引用，不参与 toString
+            if self.style == JavaCodeStyle::Lombok && field.field_type.entity().is_some() {
+                field_declaration.add_annotation(JavaAnnotationDeclaration::new("lombok.ToString.Exclude"));
+            }
+            type_declaration.add_field(field_declaration);
+        }
+
+        self.add_bean_methods(&mut type_declaration, true);
+
+        let base_annotations = vec![JavaAnnotationDeclaration::new("javax.persistence.MappedSuperclass")];
+        self.regenerable(&self.entity_package(model.datasource(entity.datasource)), &class_name, type_declaration, annotations, base_annotations)
+    }
+
+    fn generate_dto(&self, model: &Model, dto: &DtoNode) -> JavaCompilationUnit {
+        if self.java_version.supports(JavaFeature::Records) {
+            return self.generate_dto_record(model, dto);
+        }
+        let class_name = naming::pascal_case(&dto.name);
+        let mut compilation_unit = JavaCompilationUnit::new(&self.dto_package(), &class_name);
+        let mut type_declaration = JavaTypeDeclaration::new(Modifier::PUBLIC, &class_name, None);
+        if let Some(doc) = dto.documentation.doc() {
+            type_declaration.set_javadoc(&doc);
+        }
+        if let Some(schema) = schema_annotation(&dto.documentation) {
+            type_declaration.add_annotation(schema);
+        }
+        for field in &dto.fields {
+            let field_type = self.java_type(model, &field.field_type);
+            let mut field_declaration = JavaFieldDeclaration::new(&field.name, &field_type, Modifier::PRIVATE, None);
+            if let Some(doc) = field.documentation.doc() {
+                field_declaration.set_javadoc(&doc);
+            }
+            if let Some(schema) = schema_annotation(&field.documentation) {
+                field_declaration.add_annotation(schema);
+            }
+            type_declaration.add_field(field_declaration);
+        }
+        self.add_bean_methods(&mut type_declaration, false);
+        compilation_unit.add_type_declaration(type_declaration);
+        compilation_unit
+    }
+
+    /// record 形式的 DTO，字段的说明写在 `@param` 中
+    fn generate_dto_record(&self, model: &Model, dto: &DtoNode) -> JavaCompilationUnit {
+        let class_name = naming::pascal_case(&dto.name);
+        let mut compilation_unit = JavaCompilationUnit::new(&self.dto_package(), &class_name);
+        let mut type_declaration = JavaTypeDeclaration::new_record(Modifier::PUBLIC, &class_name);
+        let mut javadoc = Javadoc::new(&dto.documentation.doc().unwrap_or_default());
+        if let Some(schema) = schema_annotation(&dto.documentation) {
+            type_declaration.add_annotation(schema);
+        }
+        for field in &dto.fields {
+            let mut component = JavaMethodParameter::new(&field.name, self.java_type(model, &field.field_type));
+            if let Some(comment) = field.documentation.db_comment() {
+                javadoc.add_param(&field.name, comment);
+            }
+            if let Some(schema) = schema_annotation(&field.documentation) {
+                component.add_annotation(schema);
+            }
+            type_declaration.add_component(component);
+        }
+        if !javadoc.is_empty() {
+            type_declaration.set_javadoc(javadoc);
+        }
+        compilation_unit.add_type_declaration(type_declaration);
+        compilation_unit
+    }
+
+    /// 实体和 DTO 之间的转换，计算字段只在转换为 DTO 时设置
+    fn generate_mapper(&self, model: &Model, dto: &DtoNode) -> JavaCompilationUnit {
+        let entity = model.entity(dto.entity);
+        let entity_class = self.entity_class(model, entity.id);
+        let dto_class = self.dto_class(dto);
+        let name = format!("{}Mapper", naming::pascal_case(&dto.name));
+        let mut compilation_unit = JavaCompilationUnit::new(&self.mapper_package(), &name);
+        let mut type_declaration = JavaTypeDeclaration::new(Modifier::PUBLIC | Modifier::FINAL, &name, None);
+        type_declaration.set_javadoc(format!("{} 与 {} 之间的转换", naming::pascal_case(&entity.name), naming::pascal_case(&dto.name)));
+
+        let mut to_dto = JavaMethodDeclaration::new("toDto", &dto_class, Modifier::PUBLIC | Modifier::STATIC);
+        to_dto.add_parameter(JavaMethodParameter::new("entity", &entity_class));
+        to_dto.add_code(null_check("entity"));
+        let record = self.java_version.supports(JavaFeature::Records);
+        if record {
+            // record 通过构造方法按顺序传入所有字段
+            let mut code = CodeBlock::of("return new $T($>$>", vec![dto_class.as_str().into()]);
+            for (i, field) in dto.fields.iter().enumerate() {
+                let argument = match &field.source {
+                    DtoFieldSource::Field(index) => format!("entity.get{}()", naming::pascal_case(&entity.fields[*index].name)),
+                    DtoFieldSource::Computed(expression) => expression.clone(),
+                };
+                let separator = if i + 1 < dto.fields.len() { "," } else { "" };
+                code.add("\n$L$L", vec![argument.into(), separator.into()]);
+            }
+            code.add(");\n$<$<", vec![]);
+            to_dto.add_code(code);
+        } else {
+            to_dto.add_statement(JavaMethodStatement::new("$T dto = new $T();", vec![&dto_class, &dto_class]));
+            for field in &dto.fields {
+                let setter = naming::pascal_case(&field.name);
+                match &field.source {
+                    DtoFieldSource::Field(index) => {
+                        let getter = naming::pascal_case(&entity.fields[*index].name);
+                        to_dto.add_statement(JavaMethodStatement::new("dto.set$V(entity.get$V());", vec![&setter, &getter]));
+                    },
+                    DtoFieldSource::Computed(expression) => {
+                        to_dto.add_statement(JavaMethodStatement::new("dto.set$V($V);", vec![&setter, expression]));
+                    },
+                }
+            }
+            to_dto.add_statement(JavaMethodStatement::new("return dto;", vec![]));
+        }
+        type_declaration.add_method(to_dto);
+
+        let mut to_dto_list = JavaMethodDeclaration::new("toDtoList", format!("java.util.List<{}>", dto_class), Modifier::PUBLIC | Modifier::STATIC);
+        to_dto_list.add_parameter(JavaMethodParameter::new("entities", format!("java.util.List<{}>", entity_class)));
+        to_dto_list.add_statement(JavaMethodStatement::new(
+            "return entities.stream().map($V::toDto).collect($T.toList());",
+            vec![&name, "java.util.stream.Collectors"]));
+        type_declaration.add_method(to_dto_list);
+
+        // 审计以及逻辑删除字段由框架维护，不从 DTO 复制
+        let mut to_entity = JavaMethodDeclaration::new("toEntity", &entity_class, Modifier::PUBLIC | Modifier::STATIC);
+        to_entity.add_parameter(JavaMethodParameter::new("dto", &dto_class));
+        to_entity.add_code(null_check("dto"));
+        to_entity.add_statement(JavaMethodStatement::new("$T entity = new $T();", vec![&entity_class, &entity_class]));
+        for field in &dto.fields {
+            let index = match field.source {
+                DtoFieldSource::Field(index) => index,
+                DtoFieldSource::Computed(_) => continue,
+            };
+            if !matches!(entity.fields[index].role, FieldRole::Data | FieldRole::Version) {
+                continue;
+            }
+            let setter = naming::pascal_case(&entity.fields[index].name);
+            let getter = if record { field.name.clone() } else { format!("get{}", naming::pascal_case(&field.name)) };
+            to_entity.add_statement(JavaMethodStatement::new("entity.set$V(dto.$V());", vec![&setter, &getter]));
+        }
+        to_entity.add_statement(JavaMethodStatement::new("return entity;", vec![]));
+        type_declaration.add_method(to_entity);
+
+        compilation_unit.add_type_declaration(type_declaration);
+        compilation_unit
+    }
+
+    /// 逻辑删除以及审计需要的实体注解
+    fn behavior_annotations(&self, entity: &EntityNode) -> Vec<JavaAnnotationDeclaration> {
+        let mut annotations = vec![];
+        if let Some(deleted) = entity.role_field(FieldRole::Deleted) {
+            // 实体有 @Version 时 hibernate 会在主键之后绑定版本号
+            let mut sql = format!("UPDATE {} SET {} = true WHERE {} = ?", entity.table, deleted.column, entity.id_field().column);
+            if let Some(version) = entity.role_field(FieldRole::Version) {
+                sql.push_str(&format!(" AND {} = ?", version.column));
+            }
+            annotations.push(annotation("org.hibernate.annotations.SQLDelete", "sql", &sql));
+            annotations.push(annotation("org.hibernate.annotations.Where", "clause", &format!("{} = false", deleted.column)));
+        }
+        if entity.role_field(FieldRole::CreatedDate).is_some() {
+            let mut listeners = JavaAnnotationDeclaration::new("javax.persistence.EntityListeners");
+            listeners.add_attribute(JavaAnnotationAttribute::new(
+                "value",
+                ValueType::Class,
+                vec!["org.springframework.data.jpa.domain.support.AuditingEntityListener"]));
+            annotations.push(listeners);
+        }
+        annotations
+    }
+
+    fn field_annotations(&self, model: &Model, entity: &EntityNode, index: usize) -> Vec<JavaAnnotationDeclaration> {
+        let field = &entity.fields[index];
+        let role = match field.role {
+            FieldRole::Data | FieldRole::Deleted => None,
+            FieldRole::CreatedDate => Some("org.springframework.data.annotation.CreatedDate"),
+            FieldRole::CreatedBy => Some("org.springframework.data.annotation.CreatedBy"),
+            FieldRole::LastModifiedDate => Some("org.springframework.data.annotation.LastModifiedDate"),
+            FieldRole::LastModifiedBy => Some("org.springframework.data.annotation.LastModifiedBy"),
+            FieldRole::Version => Some("javax.persistence.Version"),
+        };
+        if let Some(role) = role {
+            return vec![
+                JavaAnnotationDeclaration::new(role),
+                annotation("javax.persistence.Column", "name", &field.column),
+            ];
+        }
+        if index == entity.id_field {
+            // 主键由数据库的 AUTO_INCREMENT 生成
+            let mut generated_value = JavaAnnotationDeclaration::new("javax.persistence.GeneratedValue");
+            generated_value.add_member("strategy", AnnotationValue::enum_constant("javax.persistence.GenerationType.IDENTITY"));
+            return vec![
+                JavaAnnotationDeclaration::new("javax.persistence.Id"),
+                generated_value,
+                annotation("javax.persistence.Column", "name", &field.column),
+            ];
+        }
+        let relation = model.outgoing(entity.id).find(|r| r.field == index);
+        match (relation, &field.field_type) {
+            (Some(relation), _) if relation.cardinality == Cardinality::ManyToOne => vec![
+                JavaAnnotationDeclaration::new("javax.persistence.ManyToOne"),
+                annotation("javax.persistence.JoinColumn", "name", &relation.join_column),
+            ],
+            // 一对多关联，外键位于被引用实体的表中
+            (Some(relation), _) => vec![
+                JavaAnnotationDeclaration::new("javax.persistence.OneToMany"),
+                annotation("javax.persistence.JoinColumn", "name", &relation.join_column),
+            ],
+            (None, FieldType::List(_)) => vec![
+                JavaAnnotationDeclaration::new("javax.persistence.ElementCollection"),
+                annotation("javax.persistence.CollectionTable", "name", &entity.collection_table(field)),
+                annotation("javax.persistence.Column", "name", &field.column),
+            ],
+            _ => vec![annotation("javax.persistence.Column", "name", &field.column)],
+        }
+    }
+
+    fn generate_repository(&self, model: &Model, entity: &EntityNode) -> JavaCompilationUnit {
+        let name = format!("{}Repository", naming::pascal_case(&entity.name));
+        let mut compilation_unit = JavaCompilationUnit::new(&self.repository_package(model.datasource(entity.datasource)), &name);
+        let id_type = self.java_type(model, &entity.id_field().field_type);
+        let entity_class = self.entity_class(model, entity.id);
+        let extends = format!("{}<{}, {}>", JPA_REPOSITORY, entity_class, id_type);
+        let mut type_declaration = JavaTypeDeclaration::new_interface(Modifier::PUBLIC, &name, Some(&extends));
+        if listings(model, entity.id).any(|(_, _, listing)| !listing.filters.is_empty()) {
+            type_declaration.add_extend(format!("{}<{}>", JPA_SPECIFICATION_EXECUTOR, entity_class));
+        }
+        if entity.role_field(FieldRole::Deleted).is_some() {
+            type_declaration.set_javadoc("实体上的 @Where 会过滤已逻辑删除的数据，delete 方法只更新删除标志\n原生 sql 查询需要自行添加过滤条件");
+        }
+        for query in &entity.queries {
+            type_declaration.add_method(self.repository_method(model, entity, query));
+        }
+        compilation_unit.add_type_declaration(type_declaration);
+        compilation_unit
+    }
+
+    /// 方法名解析得到的查询直接由 Spring Data 实现，其余的查询生成 @Query
+    /// 结构化查询的 limit 通过 Pageable 参数传入
+    fn repository_method(&self, model: &Model, entity: &EntityNode, query: &QueryNode) -> JavaMethodDeclaration {
+        let mut method = JavaMethodDeclaration::new(&query.name, self.query_return_type(model, entity, query), Modifier::new());
+        if let Some(doc) = query.documentation.doc() {
+            method.set_javadoc(&doc);
+        }
+        if !query.derived {
+            let clauses = jpql(entity, query);
+            let single_line = clauses.join(" ");
+            // 较长的查询在支持文本块时每个子句一行
+            let value = if single_line.len() > LONG_QUERY_LENGTH && self.java_version.supports(JavaFeature::TextBlocks) {
+                AnnotationValue::TextBlock(clauses.join("\n"))
+            } else {
+                AnnotationValue::String(single_line)
+            };
+            let mut query_annotation = JavaAnnotationDeclaration::new("org.springframework.data.jpa.repository.Query");
+            query_annotation.add_member("value", value);
+            method.add_annotation(query_annotation);
+        }
+        for (name, field, op) in query.params() {
+            let mut parameter = JavaMethodParameter::new(name, self.query_param_type(model, &entity.fields[field].field_type, op));
+            if !query.derived {
+                parameter.add_annotation(annotation("org.springframework.data.repository.query.Param", "value", name));
+            }
+            method.add_parameter(parameter);
+        }
+        if !query.derived && query.limit.is_some() && !query.unique {
+            method.add_parameter(JavaMethodParameter::new("pageable", "org.springframework.data.domain.Pageable"));
+        }
+        method
+    }
+
+    /// 对外暴露实体查询以及列表请求的 Service，查询在只读事务中执行
+    fn generate_service(&self, model: &Model, entity: &EntityNode) -> Vec<JavaCompilationUnit> {
+        let datasource = model.datasource(entity.datasource);
+        let entity_name = naming::pascal_case(&entity.name);
+        let name = format!("{}Service", entity_name);
+        let repository = format!("{}.{}Repository", self.repository_package(datasource), entity_name);
+        let mut type_declaration = self.generated_type(&name);
+        type_declaration.set_javadoc(format!("{} 查询", entity.documentation.label_or(&entity_name)));
+        let mut annotations = vec![JavaAnnotationDeclaration::new("org.springframework.stereotype.Service")];
+        let mut transactional = annotation(
+            "org.springframework.transaction.annotation.Transactional",
+            "transactionManager",
+            &format!("{}TransactionManager", naming::camel_case(&datasource.name)));
+        transactional.add_member("readOnly", true);
+        type_declaration.add_annotation(transactional);
+        // log 是 private 的字段，生成间隔模式下写在手写代码所在的子类上
+        if self.style == JavaCodeStyle::Lombok {
+            annotations.push(JavaAnnotationDeclaration::new("lombok.extern.slf4j.Slf4j"));
+        }
+
+        // 生成间隔模式下子类也需要使用 repository
+        let field_modifier = match self.regeneration {
+            RegenerationMode::ProtectedRegions => Modifier::PRIVATE,
+            RegenerationMode::GenerationGap => Modifier::PROTECTED,
+        };
+        let mut field = JavaFieldDeclaration::new("repository", &repository, field_modifier, None);
+        field.add_annotation(JavaAnnotationDeclaration::new("org.springframework.beans.factory.annotation.Autowired"));
+        type_declaration.add_field(field);
+
+        for query in &entity.queries {
+            let mut method = JavaMethodDeclaration::new(&query.name, self.query_return_type(model, entity, query), Modifier::PUBLIC);
+            if let Some(doc) = query.documentation.doc() {
+                method.set_javadoc(&doc);
+            }
+            let mut arguments = vec![];
+            for (name, field, op) in query.params() {
+                method.add_parameter(JavaMethodParameter::new(name, self.query_param_type(model, &entity.fields[field].field_type, op)));
+                arguments.push(name.to_string());
+            }
+            match query.limit {
+                Some(limit) if !query.derived && !query.unique => {
+                    arguments.push(format!("$T.of(0, {})", limit));
+                    method.add_statement(JavaMethodStatement::new(
+                        &format!("return this.repository.$V({});", arguments.join(", ")),
+                        vec![&query.name, "org.springframework.data.domain.PageRequest"]));
+                },
+                _ => method.add_statement(JavaMethodStatement::new(
+                    "return this.repository.$V($V);",
+                    vec![&query.name, &arguments.join(", ")])),
+            }
+            type_declaration.add_method(method);
+        }
+        for (api, request, listing) in listings(model, entity.id) {
+            type_declaration.add_method(self.listing_method(model, api, request, listing));
+        }
+        self.regenerable(&self.service_package(datasource), &name, type_declaration, annotations, vec![])
+    }
+
+    /// 生成间隔模式下为抽象的 `AbstractXxxBase`，否则为 name 本身
+    fn generated_type(&self, name: &str) -> JavaTypeDeclaration {
+        match self.regeneration {
+            RegenerationMode::ProtectedRegions => JavaTypeDeclaration::new(Modifier::PUBLIC, name, None),
+            RegenerationMode::GenerationGap => {
+                JavaTypeDeclaration::new(Modifier::PUBLIC | Modifier::ABSTRACT, &format!("Abstract{}Base", name), None)
+            },
+        }
+    }
+
+    /// 按再生成的方式输出 generated_type 创建的类型。保护区域模式下添加保护区域；
+    /// 生成间隔模式下添加只写入一次的子类 name，文档注释以及 annotations 写在子类上，
+    /// base_annotations 为基类额外的注解
+    fn regenerable(
+        &self,
+        package: &str,
+        name: &str,
+        mut type_declaration: JavaTypeDeclaration,
+        annotations: Vec<JavaAnnotationDeclaration>,
+        base_annotations: Vec<JavaAnnotationDeclaration>,
+    ) -> Vec<JavaCompilationUnit> {
+        match self.regeneration {
+            RegenerationMode::ProtectedRegions => {
+                type_declaration.annotations.splice(0..0, annotations);
+                type_declaration.add_protected_region(CUSTOM_REGION);
+                let mut compilation_unit = JavaCompilationUnit::new(package, name);
+                compilation_unit.add_protected_region(IMPORTS_REGION);
+                compilation_unit.add_type_declaration(type_declaration);
+                vec![compilation_unit]
+            },
+            RegenerationMode::GenerationGap => {
+                let base_name = type_declaration.name.clone();
+                let mut subclass = JavaTypeDeclaration::new(Modifier::PUBLIC, name, Some(&format!("{}.{}", package, base_name)));
+                subclass.javadoc = type_declaration.javadoc.take();
+                subclass.annotations = annotations;
+                type_declaration.set_javadoc(format!("{} 的生成代码，每次生成都会覆盖，手写的代码放在 {} 中", name, name));
+                type_declaration.annotations.splice(0..0, base_annotations);
+
+                let mut base = JavaCompilationUnit::new(package, &base_name);
+                base.add_type_declaration(type_declaration);
+                let mut compilation_unit = JavaCompilationUnit::new(package, name);
+                compilation_unit.set_write_once(true);
+                compilation_unit.add_type_declaration(subclass);
+                vec![base, compilation_unit]
+            },
+        }
+    }
+
+    /// 列表请求，过滤条件组合为 Specification，排序字段只能是声明的字段
+    fn listing_method(&self, model: &Model, api: &ApiNode, request: &str, listing: &ListingNode) -> JavaMethodDeclaration {
+        let entity = model.entity(listing.entity);
+        let entity_class = self.entity_class(model, entity.id);
+        let name = naming::camel_case(&format!("{}_{}", api.name, request));
+        let return_type = match listing.paged {
+            true => format!("{}<{}>", self.page_response_class(), entity_class),
+            false => format!("java.util.List<{}>", entity_class),
+        };
+        let mut method = JavaMethodDeclaration::new(&name, &return_type, Modifier::PUBLIC);
+        let mut javadoc = Javadoc::new(&format!("{}.{} 请求的{}查询", api.name, request, if listing.paged { "分页" } else { "列表" }));
+        javadoc.add_param("request", "过滤条件以及分页、排序参数");
+        javadoc.add_throws("java.lang.IllegalArgumentException", "排序字段不在允许的范围内");
+        method.set_javadoc(javadoc);
+        method.add_parameter(JavaMethodParameter::new("request", self.listing_request_class(api, request)));
+
+        let mut code = CodeBlock::new();
+        let mut arguments = vec![];
+        if !listing.filters.is_empty() {
+            let specification = format!("org.springframework.data.jpa.domain.Specification<{}>", entity_class);
+            let predicate = "javax.persistence.criteria.Predicate";
+            code.begin_control_flow("$T specification = (root, query, builder) ->", vec![specification.into()])
+                .add_statement("$T<$T> predicates = new $T<>()", vec!["java.util.List".into(), predicate.into(), "java.util.ArrayList".into()]);
+            for filter in &listing.filters {
+                code.add_code(self.filter_code(model, entity, filter));
+            }
+            code.add_statement("return builder.and(predicates.toArray(new $T[0]))", vec![predicate.into()])
+                .end_control_flow_with("", vec![]);
+            arguments.push("specification".to_string());
+        }
+        if !listing.sortable.is_empty() {
+            let fields: Vec<String> = listing.sortable.iter().map(|f| format!("\"{}\"", entity.fields[*f].name)).collect();
+            let sort = "org.springframework.data.domain.Sort";
+            code.add_statement("$T sort = $T.unsorted()", vec![sort.into(), sort.into()])
+                .begin_control_flow("if (request.getSort() != null)", vec![])
+                .begin_control_flow("for (String item : request.getSort())", vec![])
+                .add_statement("String[] parts = item.split($S)", vec![",".into()])
+                .begin_control_flow("if (!$T.asList($L).contains(parts[0]))", vec!["java.util.Arrays".into(), fields.join(", ").into()])
+                .add_statement("throw new IllegalArgumentException($S + parts[0])", vec!["cannot sort by ".into()])
+                .end_control_flow()
+                .add_statement(
+                    "$T.Direction direction = parts.length > 1 && $S.equalsIgnoreCase(parts[1]) ? $T.Direction.DESC : $T.Direction.ASC",
+                    vec![sort.into(), "desc".into(), sort.into(), sort.into()])
+                .add_statement("sort = sort.and($T.by(direction, parts[0]))", vec![sort.into()])
+                .end_control_flow()
+                .end_control_flow();
+        }
+        if listing.paged {
+            code.add_statement("int page = request.getPage() == null ? 0 : request.getPage()", vec![])
+                .add_statement("int size = request.getSize() == null ? $L : request.getSize()", vec![DEFAULT_PAGE_SIZE.to_string().into()]);
+            let sort = if listing.sortable.is_empty() { "" } else { ", sort" };
+            arguments.push(format!("$T.of(page, size{})", sort));
+            code.add_statement(
+                &format!("return $T.of(this.repository.findAll({}))", arguments.join(", ")),
+                vec![self.page_response_class().into(), "org.springframework.data.domain.PageRequest".into()]);
+        } else {
+            if !listing.sortable.is_empty() {
+                arguments.push("sort".to_string());
+            }
+            code.add_statement("return this.repository.findAll($L)", vec![arguments.join(", ").into()]);
+        }
+        method.add_code(code);
+        method
+    }
+
+    /// 参数不为空时添加过滤条件，无参数的条件使用 Boolean 开关
+    fn filter_code(&self, model: &Model, entity: &EntityNode, filter: &FilterNode) -> CodeBlock {
+        let field = &entity.fields[filter.field];
+        let value_type = self.filter_value_type(model, &field.field_type);
+        // ref 字段按被引用实体的主键过滤
+        let path = match field.field_type.entity() {
+            Some(target) => format!("root.get(\"{}\").<$T>get(\"{}\")", field.name, model.entity(target).id_field().name),
+            None => format!("root.<$T>get(\"{}\")", field.name),
+        };
+        let getter = |index: usize| format!("request.get{}()", naming::pascal_case(&filter.params[index]));
+        // (条件, 谓词)，谓词中的 $T 为字段类型
+        let conditions: Vec<(String, String)> = match filter.op {
+            Operator::Eq => vec![(getter(0), format!("builder.equal({}, {})", path, getter(0)))],
+            Operator::Ne => vec![(getter(0), format!("builder.notEqual({}, {})", path, getter(0)))],
+            Operator::Gt => vec![(getter(0), format!("builder.greaterThan({}, {})", path, getter(0)))],
+            Operator::Gte => vec![(getter(0), format!("builder.greaterThanOrEqualTo({}, {})", path, getter(0)))],
+            Operator::Lt => vec![(getter(0), format!("builder.lessThan({}, {})", path, getter(0)))],
+            Operator::Lte => vec![(getter(0), format!("builder.lessThanOrEqualTo({}, {})", path, getter(0)))],
+            Operator::Between => vec![
+                (getter(0), format!("builder.greaterThanOrEqualTo({}, {})", path, getter(0))),
+                (getter(1), format!("builder.lessThanOrEqualTo({}, {})", path, getter(1))),
+            ],
+            Operator::Like => vec![(getter(0), format!("builder.like({}, {})", path, getter(0)))],
+            Operator::StartsWith => vec![(getter(0), format!("builder.like({}, {} + \"%\")", path, getter(0)))],
+            Operator::EndsWith => vec![(getter(0), format!("builder.like({}, \"%\" + {})", path, getter(0)))],
+            Operator::Contains => vec![(getter(0), format!("builder.like({}, \"%\" + {} + \"%\")", path, getter(0)))],
+            Operator::In => vec![(getter(0), format!("{}.in({})", path, getter(0)))],
+            Operator::NotIn => vec![(getter(0), format!("builder.not({}.in({}))", path, getter(0)))],
+            Operator::IsNull => vec![(getter(0), format!("builder.isNull({})", path))],
+            Operator::IsNotNull => vec![(getter(0), format!("builder.isNotNull({})", path))],
+            Operator::IsTrue => vec![(getter(0), format!("builder.isTrue({})", path))],
+            Operator::IsFalse => vec![(getter(0), format!("builder.isFalse({})", path))],
+        };
+        let mut code = CodeBlock::new();
+        for (value, predicate) in conditions {
+            let condition = match filter.op.arity() {
+                0 => format!("Boolean.TRUE.equals({})", value),
+                _ if matches!(filter.op, Operator::In | Operator::NotIn) => format!("{} != null && !{}.isEmpty()", value, value),
+                _ => format!("{} != null", value),
+            };
+            code.begin_control_flow("if ($L)", vec![condition.into()])
+                .add_statement(&format!("predicates.add({})", predicate), vec![value_type.clone().into()])
+                .end_control_flow();
+        }
+        code
+    }
+
+    /// 过滤参数的类型，ref 字段使用被引用实体的主键类型
+    fn filter_value_type(&self, model: &Model, field_type: &FieldType) -> String {
+        match field_type.entity() {
+            Some(target) => self.java_type(model, &model.entity(target).id_field().field_type),
+            None => self.java_type(model, field_type),
+        }
+    }
+
+    /// 列表请求的参数，包括过滤条件以及分页和排序
+    fn generate_listing_request(&self, model: &Model, api: &ApiNode, request: &str, listing: &ListingNode) -> JavaCompilationUnit {
+        let class_name = self.listing_request_class(api, request).rsplit('.').next().unwrap_or_default().to_string();
+        let entity = model.entity(listing.entity);
+        let mut compilation_unit = JavaCompilationUnit::new(&self.dto_package(), &class_name);
+        let mut type_declaration = JavaTypeDeclaration::new(Modifier::PUBLIC, &class_name, None);
+        type_declaration.set_javadoc(format!("{}.{} 请求的参数", api.name, request));
+        let mut properties: Vec<(String, String, Option<String>)> = vec![];
+        for filter in &listing.filters {
+            let field = &entity.fields[filter.field];
+            let value_type = self.filter_value_type(model, &field.field_type);
+            let label = field.documentation.label_or(&field.name).to_string();
+            for (index, param) in filter.params.iter().enumerate() {
+                let param_type = match filter.op {
+                    _ if filter.op.arity() == 0 => "java.lang.Boolean".to_string(),
+                    Operator::In | Operator::NotIn => format!("java.util.List<{}>", value_type),
+                    _ => value_type.clone(),
+                };
+                properties.push((param.clone(), param_type, Some(filter.op.filter_doc(&label, index))));
+            }
+        }
+        if listing.paged {
+            properties.push(("page".to_string(), "java.lang.Integer".to_string(), Some("页码，从 0 开始".to_string())));
+            properties.push(("size".to_string(), "java.lang.Integer".to_string(), Some(format!("每页数量，默认 {}", DEFAULT_PAGE_SIZE))));
+        }
+        if !listing.sortable.is_empty() {
+            let fields: Vec<&str> = listing.sortable.iter().map(|f| entity.fields[*f].name.as_str()).collect();
+            properties.push((
+                "sort".to_string(),
+                "java.util.List<java.lang.String>".to_string(),
+                Some(format!("排序，格式为 `field[,asc|desc]`，可排序的字段：{}", fields.join(", ")))));
+        }
+        for (name, property_type, doc) in &properties {
+            let mut field_declaration = JavaFieldDeclaration::new(name, property_type, Modifier::PRIVATE, None);
+            if let Some(doc) = doc {
+                field_declaration.set_javadoc(doc);
+            }
+            type_declaration.add_field(field_declaration);
+        }
+        self.add_bean_methods(&mut type_declaration, false);
+        compilation_unit.add_type_declaration(type_declaration);
+        compilation_unit
+    }
+
+    /// 分页查询的返回值，由 Spring Data 的 Page 转换而来
+    fn generate_page_response(&self) -> JavaCompilationUnit {
+        let class_name = format!("{}<T>", PAGE_RESPONSE);
+        let mut compilation_unit = JavaCompilationUnit::new(&self.dto_package(), PAGE_RESPONSE);
+        let mut type_declaration = JavaTypeDeclaration::new(Modifier::PUBLIC, PAGE_RESPONSE, None);
+        type_declaration.add_type_parameter(TypeParameter::new("T"));
+        type_declaration.set_javadoc("分页查询的结果");
+        let properties = [
+            ("content", "java.util.List<T>", "当前页的数据"),
+            ("page", "int", "页码，从 0 开始"),
+            ("size", "int", "每页数量"),
+            ("totalElements", "long", "总数量"),
+            ("totalPages", "int", "总页数"),
+        ];
+        for (name, property_type, doc) in properties {
+            let mut field_declaration = JavaFieldDeclaration::new(name, property_type, Modifier::PRIVATE, None);
+            field_declaration.set_javadoc(doc);
+            type_declaration.add_field(field_declaration);
+        }
+        let mut of = JavaMethodDeclaration::new("of", &class_name, Modifier::PUBLIC | Modifier::STATIC);
+        let mut javadoc = Javadoc::new("由 {@link org.springframework.data.domain.Page} 创建分页结果");
+        javadoc.add_param("<T>", "数据的类型");
+        javadoc.add_param("page", "分页查询返回的数据");
+        javadoc.set_return("分页查询的结果");
+        of.set_javadoc(javadoc);
+        of.add_type_parameter(TypeParameter::new("T"));
+        of.add_parameter(JavaMethodParameter::new("page", "org.springframework.data.domain.Page<T>"));
+        of.add_statement(JavaMethodStatement::new("$V response = new $V<>();", vec![&class_name, PAGE_RESPONSE]));
+        of.add_statement(JavaMethodStatement::new("response.setContent(page.getContent());", vec![]));
+        of.add_statement(JavaMethodStatement::new("response.setPage(page.getNumber());", vec![]));
+        of.add_statement(JavaMethodStatement::new("response.setSize(page.getSize());", vec![]));
+        of.add_statement(JavaMethodStatement::new("response.setTotalElements(page.getTotalElements());", vec![]));
+        of.add_statement(JavaMethodStatement::new("response.setTotalPages(page.getTotalPages());", vec![]));
+        of.add_statement(JavaMethodStatement::new("return response;", vec![]));
+        type_declaration.add_method(of);
+        self.add_bean_methods(&mut type_declaration, false);
+        compilation_unit.add_type_declaration(type_declaration);
+        compilation_unit
+    }
+
+    /// 实体和 DTO 的 getter、setter，Lombok 方式下为相应的注解。
+    /// 实体只使用 @Getter、@Setter 和 @ToString，equals 和 hashCode 保持对象标识，
+    /// 避免比较延迟加载的关联以及保存后才生成的主键
+    fn add_bean_methods(&self, type_declaration: &mut JavaTypeDeclaration, entity: bool) {
+        if self.style == JavaCodeStyle::Plain {
+            type_declaration.add_accessors(&BeanOptions::new());
+            return;
+        }
+        if entity {
+            for name in ["lombok.Getter", "lombok.Setter", "lombok.ToString"] {
+                type_declaration.add_annotation(JavaAnnotationDeclaration::new(name));
+            }
+        } else {
+            type_declaration.add_annotation(JavaAnnotationDeclaration::new("lombok.Data"));
+            let mut equals_and_hash_code = JavaAnnotationDeclaration::new("lombok.EqualsAndHashCode");
+            equals_and_hash_code.add_member("callSuper", !type_declaration.extends.is_empty());
+            type_declaration.add_annotation(equals_and_hash_code);
+        }
+        // 抽象类不能创建对象，不生成 builder 和构造方法
+        if type_declaration.modifiers.is_abstract() {
+            return;
+        }
+        for name in ["lombok.Builder", "lombok.NoArgsConstructor", "lombok.AllArgsConstructor"] {
+            type_declaration.add_annotation(JavaAnnotationDeclaration::new(name));
+        }
+        // 有初始值的字段在 builder 中也使用初始值
+        for field in type_declaration.fields.iter_mut().filter(|f| f.value.is_some()) {
+            field.add_annotation(JavaAnnotationDeclaration::new("lombok.Builder.Default"));
+        }
+    }
+
+    fn query_return_type(&self, model: &Model, entity: &EntityNode, query: &QueryNode) -> String {
+        let entity_class = self.entity_class(model, entity.id);
+        match query.subject {
+            QuerySubject::Count => "long".to_string(),
+            QuerySubject::Exists => "boolean".to_string(),
+            QuerySubject::Find if query.unique => format!("java.util.Optional<{}>", entity_class),
+            QuerySubject::Find => format!("java.util.List<{}>", entity_class),
+        }
+    }
+
+    fn query_param_type(&self, model: &Model, field_type: &FieldType, op: Operator) -> String {
+        let java_type = self.java_type(model, field_type);
+        match op {
+            Operator::In | Operator::NotIn => format!("java.util.Collection<{}>", java_type),
+            _ => java_type,
+        }
+    }
+
+    /// 数据源配置类，包括 DataSource、EntityManagerFactory 以及事务管理器
+    fn generate_datasource_config(&self, model: &Model, datasource: &DatasourceNode) -> JavaCompilationUnit {
+        let name = format!("{}DataSourceConfig", naming::pascal_case(&datasource.name));
+        let prefix = naming::camel_case(&datasource.name);
+        let data_source_bean = format!("{}DataSource", prefix);
+        let entity_manager_factory_bean = format!("{}EntityManagerFactory", prefix);
+        let transaction_manager_bean = format!("{}TransactionManager", prefix);
+        let primary = datasource.primary;
+
+        let mut compilation_unit = JavaCompilationUnit::new(&self.config_package(), &name);
+        let mut type_declaration = JavaTypeDeclaration::new(Modifier::PUBLIC, &name, None);
+        type_declaration.add_annotation(JavaAnnotationDeclaration::new("org.springframework.context.annotation.Configuration"));
+        type_declaration.add_annotation(JavaAnnotationDeclaration::new("org.springframework.transaction.annotation.EnableTransactionManagement"));
+        let mut enable_repositories = annotation("org.springframework.data.jpa.repository.config.EnableJpaRepositories", "basePackages", &self.repository_package(datasource));
+        enable_repositories.add_attribute(JavaAnnotationAttribute::new("entityManagerFactoryRef", ValueType::Value, vec![&entity_manager_factory_bean]));
+        enable_repositories.add_attribute(JavaAnnotationAttribute::new("transactionManagerRef", ValueType::Value, vec![&transaction_manager_bean]));
+        type_declaration.add_annotation(enable_repositories);
+        // 审计的处理器是全局的，只在主数据源上开启一次
+        if primary && model.entities.iter().any(|e| e.role_field(FieldRole::CreatedDate).is_some()) {
+            type_declaration.add_annotation(JavaAnnotationDeclaration::new("org.springframework.data.jpa.repository.config.EnableJpaAuditing"));
+        }
+
+        // DataSource
+        let mut data_source = bean_method(&data_source_bean, "javax.sql.DataSource", primary);
+        data_source.add_annotation(annotation(
+            "org.springframework.boot.context.properties.ConfigurationProperties",
+            "prefix",
+            &format!("spring.datasource.{}", property_name(&datasource.name))));
+        data_source.add_statement(JavaMethodStatement::new("return $T.create().build();", vec!["org.springframework.boot.jdbc.DataSourceBuilder"]));
+        type_declaration.add_method(data_source);
+
+        // EntityManagerFactory
+        let mut entity_manager_factory = bean_method(&entity_manager_factory_bean, "org.springframework.orm.jpa.LocalContainerEntityManagerFactoryBean", primary);
+        entity_manager_factory.add_parameter(JavaMethodParameter::new("builder", "org.springframework.boot.orm.jpa.EntityManagerFactoryBuilder"));
+        entity_manager_factory.add_parameter(qualified_parameter("dataSource", "javax.sql.DataSource", &data_source_bean));
+        let packages = format!("\"{}\"", self.entity_package(datasource));
+        let persistence_unit = format!("\"{}\"", datasource.name);
+        entity_manager_factory.add_statement(JavaMethodStatement::new(
+            "return builder.dataSource(dataSource).packages($V).persistenceUnit($V).build();",
+            vec![&packages, &persistence_unit]));
+        type_declaration.add_method(entity_manager_factory);
+
+        // TransactionManager
+        let mut transaction_manager = bean_method(&transaction_manager_bean, "org.springframework.transaction.PlatformTransactionManager", primary);
+        transaction_manager.add_parameter(qualified_parameter("entityManagerFactory", "javax.persistence.EntityManagerFactory", &entity_manager_factory_bean));
+        transaction_manager.add_statement(JavaMethodStatement::new(
+            "return new $T(entityManagerFactory);",
+            vec!["org.springframework.orm.jpa.JpaTransactionManager"]));
+        type_declaration.add_method(transaction_manager);
+
+        // Flyway，启动时先建表再执行数据源的 seed 脚本
+        if model.has_seed(datasource) {
+            let flyway_bean = format!("{}Flyway", prefix);
+            let mut flyway = JavaMethodDeclaration::new(&flyway_bean, "org.flywaydb.core.Flyway", Modifier::PUBLIC);
+            let mut bean = annotation("org.springframework.context.annotation.Bean", "name", &flyway_bean);
+            bean.add_attribute(JavaAnnotationAttribute::new("initMethod", ValueType::Value, vec!["migrate"]));
+            flyway.add_annotation(bean);
+            flyway.add_parameter(qualified_parameter("dataSource", "javax.sql.DataSource", &data_source_bean));
+            let location = format!("\"classpath:{}\"", self.migration_location(datasource));
+            flyway.add_statement(JavaMethodStatement::new(
+                "return $T.configure().dataSource(dataSource).locations($V).baselineOnMigrate(true).load();",
+                vec!["org.flywaydb.core.Flyway", &location]));
+            type_declaration.add_method(flyway);
+        }
+
+        compilation_unit.add_type_declaration(type_declaration);
+        compilation_unit
+    }
+}
+
+/// 基础类型对应的java类型
+fn scalar_type(scalar: ScalarType) -> &'static str {
+    match scalar {
+        ScalarType::Id => "java.lang.Long",
+        ScalarType::String => "java.lang.String",
+        ScalarType::Int8 => "java.lang.Byte",
+        ScalarType::Int16 => "java.lang.Short",
+        ScalarType::Int32 => "java.lang.Integer",
+        ScalarType::Int64 => "java.lang.Long",
+        ScalarType::Float32 => "java.lang.Float",
+        ScalarType::Float64 => "java.lang.Double",
+        ScalarType::Bool => "java.lang.Boolean",
+        ScalarType::DateTime => "java.time.LocalDateTime",
+        ScalarType::Object => "java.lang.Object",
+    }
+}
+
+/// 返回实体的列表请求，按接口和请求名排列
+fn listings(model: &Model, entity: EntityId) -> impl Iterator<Item = (&ApiNode, &str, &ListingNode)> {
+    model.apis
+        .iter()
+        .flat_map(|api| api.requests.iter().map(move |request| (api, request)))
+        .filter_map(move |(api, request)| {
+            let listing = request.listing.as_ref().filter(|l| l.entity == entity)?;
+            Some((api, request.name.as_str(), listing))
+        })
+}
+
+/// 结构化查询对应的 JPQL，实体的别名为 e
+/// 查询的各个子句，依次为 select、where 以及 order by
+fn jpql(entity: &EntityNode, query: &QueryNode) -> Vec<String> {
+    let entity_name = naming::pascal_case(&entity.name);
+    let select = match query.subject {
+        QuerySubject::Find => format!("select e from {} e", entity_name),
+        QuerySubject::Count => format!("select count(e) from {} e", entity_name),
+        QuerySubject::Exists => format!("select count(e) > 0 from {} e", entity_name),
+    };
+    let mut jpql = vec![select];
+    if let Some(condition) = query.condition.as_ref() {
+        jpql.push(format!("where {}", jpql_condition(entity, condition, false)));
+    }
+    if !query.sort.is_empty() {
+        let order: Vec<String> = query.sort
+            .iter()
+            .map(|(field, direction)| match direction {
+                Direction::Asc => format!("e.{} asc", entity.fields[*field].name),
+                Direction::Desc => format!("e.{} desc", entity.fields[*field].name),
+            })
+            .collect();
+        jpql.push(format!("order by {}", order.join(", ")));
+    }
+    jpql
+}
+
+/// nested 为 true 时，多个条件需要加括号
+fn jpql_condition(entity: &EntityNode, condition: &ConditionNode, nested: bool) -> String {
+    let (items, separator) = match condition {
+        ConditionNode::Predicate { field, op, params } => {
+            let path = format!("e.{}", entity.fields[*field].name);
+            let param = |index: usize| params.get(index).map(|p| format!(":{}", p)).unwrap_or_default();
+            return match op {
+                Operator::Eq => format!("{} = {}", path, param(0)),
+                Operator::Ne => format!("{} <> {}", path, param(0)),
+                Operator::Gt => format!("{} > {}", path, param(0)),
+                Operator::Gte => format!("{} >= {}", path, param(0)),
+                Operator::Lt => format!("{} < {}", path, param(0)),
+                Operator::Lte => format!("{} <= {}", path, param(0)),
+                Operator::Between => format!("{} between {} and {}", path, param(0), param(1)),
+                Operator::Like => format!("{} like {}", path, param(0)),
+                Operator::StartsWith => format!("{} like concat({}, '%')", path, param(0)),
+                Operator::EndsWith => format!("{} like concat('%', {})", path, param(0)),
+                Operator::Contains => format!("{} like concat('%', {}, '%')", path, param(0)),
+                Operator::In => format!("{} in {}", path, param(0)),
+                Operator::NotIn => format!("{} not in {}", path, param(0)),
+                Operator::IsNull => format!("{} is null", path),
+                Operator::IsNotNull => format!("{} is not null", path),
+                Operator::IsTrue => format!("{} = true", path),
+                Operator::IsFalse => format!("{} = false", path),
+            };
+        },
+        ConditionNode::And(items) => (items, " and "),
+        ConditionNode::Or(items) => (items, " or "),
+    };
+    let parts: Vec<String> = items.iter().map(|item| jpql_condition(entity, item, true)).collect();
+    if nested && parts.len() > 1 {
+        format!("({})", parts.join(separator))
+    } else {
+        parts.join(separator)
+    }
+}
+
+/// 只有一个字符串属性的注解
+fn annotation(name: &str, attribute: &str, value: &str) -> JavaAnnotationDeclaration {
+    let mut annotation = JavaAnnotationDeclaration::new(name);
+    annotation.add_attribute(JavaAnnotationAttribute::new(attribute, ValueType::Value, vec![value]));
+    annotation
+}
+
+/// OpenAPI 文档中的标题和描述
+fn schema_annotation(documentation: &Documentation) -> Option<JavaAnnotationDeclaration> {
+    if documentation.label.is_none() && documentation.description.is_none() {
+        return None;
+    }
+    let mut schema = JavaAnnotationDeclaration::new("io.swagger.v3.oas.annotations.media.Schema");
+    if let Some(label) = documentation.label.as_ref() {
+        schema.add_attribute(JavaAnnotationAttribute::new("title", ValueType::Value, vec![label]));
+    }
+    if let Some(description) = documentation.description.as_ref() {
+        schema.add_attribute(JavaAnnotationAttribute::new("description", ValueType::Value, vec![description]));
+    }
+    Some(schema)
+}
+
+/// 参数为 null 时直接返回 null
+fn null_check(name: &str) -> CodeBlock {
+    let mut code = CodeBlock::new();
+    code.begin_control_flow("if ($N == null)", vec![name.into()])
+        .add_statement("return null", vec![])
+        .end_control_flow();
+    code
+}
+
+fn bean_method(name: &str, return_type: &str, primary: bool) -> JavaMethodDeclaration {
+    let mut method = JavaMethodDeclaration::new(name, return_type, Modifier::PUBLIC);
+    if primary {
+        method.add_annotation(JavaAnnotationDeclaration::new("org.springframework.context.annotation.Primary"));
+    }
+    method.add_annotation(annotation("org.springframework.context.annotation.Bean", "name", name));
+    method
+}
+
+fn qualified_parameter(name: &str, param_type: &str, qualifier: &str) -> JavaMethodParameter {
+    let mut parameter = JavaMethodParameter::new(name, param_type);
+    parameter.add_annotation(annotation("org.springframework.beans.factory.annotation.Qualifier", "value", qualifier));
+    parameter
+}
+
+/// spring 配置前缀使用 kebab-case
+fn property_name(name: &str) -> String {
+    naming::snake_case(name).replace('_', "-")
+}
+
+/// json 值，ref 写作只包含主键的对象
+fn json_value(value: &SeedValue, ref_id: Option<&str>) -> String {
+    match (value, ref_id) {
+        (SeedValue::Null, _) => "null".to_string(),
+        (SeedValue::List(values), _) => {
+            let values: Vec<String> = values.iter().map(|v| json_value(v, ref_id)).collect();
+            format!("[{}]", values.join(", "))
+        },
+        (SeedValue::Int(id), Some(ref_id)) => format!("{{{}: {}}}", json_string(ref_id), id),
+        (SeedValue::Bool(value), _) => value.to_string(),
+        (SeedValue::Int(value), _) => value.to_string(),
+        (SeedValue::Float(value), _) => value.to_string(),
+        (SeedValue::String(value), _) => json_string(value),
+        // jackson 默认的 LocalDateTime 格式
+        (SeedValue::DateTime(value), _) => json_string(&value.replacen(' ', "T", 1)),
+    }
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::from("\"");
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            '\r' => json.push_str("\\r"),
+            '\t' => json.push_str("\\t"),
+            c if (c as u32) < 0x20 => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+fn yaml_string(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
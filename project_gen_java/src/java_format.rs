@@ -0,0 +1,82 @@
+/// 缩进使用的字符
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IndentStyle {
+    Spaces,
+    Tabs,
+}
+
+/// 类型、方法以及控制结构的 `{` 的位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BraceStyle {
+    /// `if (a) {`
+    EndOfLine,
+    /// `{` 单独一行，`else`、`catch` 也另起一行
+    NextLine,
+}
+
+/// 源码的格式，默认与 google-java-format 一致：
+/// 两个空格缩进，续行缩进四个空格，每行最多 100 个字符
+#[derive(Debug, Clone)]
+pub struct FormatOptions {
+    pub indent_style: IndentStyle,
+    /// 每级缩进的宽度，使用 tab 时为计算行宽时 tab 的宽度
+    pub indent_width: usize,
+    /// 换行后的续行比当前行多缩进的级数
+    pub continuation_indent: usize,
+    /// 超出宽度的参数列表、注解属性以及链式调用会换行，文档注释也按该宽度换行
+    pub max_width: usize,
+    pub brace_style: BraceStyle,
+    /// 类型声明的 `{` 之后是否空一行
+    pub blank_line_after_type_header: bool,
+    /// 方法、构造方法、初始化块、成员类型以及带有注释或注解的字段之前的空行数
+    pub blank_lines_between_members: usize,
+    /// 没有注释和注解的字段之间的空行数
+    pub blank_lines_between_fields: usize,
+}
+
+impl Default for FormatOptions {
+    fn default() -> FormatOptions {
+        FormatOptions {
+            indent_style: IndentStyle::Spaces,
+            indent_width: 2,
+            continuation_indent: 2,
+            max_width: 100,
+            brace_style: BraceStyle::EndOfLine,
+            blank_line_after_type_header: false,
+            blank_lines_between_members: 1,
+            blank_lines_between_fields: 0,
+        }
+    }
+}
+
+impl FormatOptions {
+    pub fn new() -> FormatOptions {
+        FormatOptions::default()
+    }
+
+    pub fn set_indent(&mut self, indent_style: IndentStyle, indent_width: usize) {
+        self.indent_style = indent_style;
+        self.indent_width = indent_width;
+    }
+
+    pub fn set_max_width(&mut self, max_width: usize) {
+        self.max_width = max_width;
+    }
+
+    pub fn set_brace_style(&mut self, brace_style: BraceStyle) {
+        self.brace_style = brace_style;
+    }
+
+    /// level 级缩进
+    pub(crate) fn indent(&self, level: usize) -> String {
+        match self.indent_style {
+            IndentStyle::Spaces => " ".repeat(self.indent_width * level),
+            IndentStyle::Tabs => "\t".repeat(level),
+        }
+    }
+
+    /// level 级缩进占用的宽度
+    pub(crate) fn indent_columns(&self, level: usize) -> usize {
+        self.indent_width * level
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use super::ClassName;
+
+/// 编译单元的导入
+/// 简单类名冲突时先出现的类被导入，其余的类使用全限定名；
+/// 同一个包以及 java.lang 中的类不需要导入，但会占用简单类名
+#[derive(Debug, Default)]
+pub(crate) struct JavaImports {
+    package_name: String,
+    /// 编译单元中声明的类、同一个包中的类以及没有包名的类占用的简单类名
+    local: HashSet<String>,
+    /// java.lang 中被引用的类占用的简单类名
+    java_lang: HashSet<String>,
+    /// 简单类名对应的已导入的类
+    imported: HashMap<String, String>,
+    imports: BTreeSet<String>,
+    static_imports: BTreeSet<String>,
+}
+
+impl JavaImports {
+    /// declared 为编译单元中声明的类型，classes 为按出现顺序引用的类
+    pub fn resolve(package_name: &str, declared: &[&str], classes: Vec<ClassName>, static_imports: &[String]) -> JavaImports {
+        let mut imports = JavaImports {
+            package_name: package_name.to_owned(),
+            static_imports: static_imports.iter().cloned().collect(),
+            ..Default::default()
+        };
+        imports.local.extend(declared.iter().map(|name| name.to_string()));
+        let classes: Vec<ClassName> = classes.iter().map(|c| c.top_level()).collect();
+        for class_name in &classes {
+            let simple_name = class_name.simple_name().to_owned();
+            if imports.is_local(class_name) {
+                imports.local.insert(simple_name);
+            } else if class_name.package_name == "java.lang" {
+                imports.java_lang.insert(simple_name);
+            }
+        }
+        for class_name in classes {
+            if imports.is_local(&class_name) || class_name.package_name == "java.lang" {
+                continue;
+            }
+            let simple_name = class_name.simple_name();
+            if imports.local.contains(simple_name) || imports.java_lang.contains(simple_name) {
+                continue;
+            }
+            let canonical_name = class_name.canonical_name();
+            if !imports.imported.contains_key(simple_name) {
+                imports.imported.insert(simple_name.to_owned(), canonical_name.clone());
+                imports.imports.insert(canonical_name);
+            }
+        }
+        imports
+    }
+
+    fn is_local(&self, class_name: &ClassName) -> bool {
+        class_name.package_name.is_empty() || class_name.package_name == self.package_name
+    }
+
+    /// 类在源码中的写法，能够使用简单类名时使用简单类名，嵌套类为 `Outer.Inner`
+    pub fn class_name(&self, class_name: &ClassName) -> String {
+        let top_level = class_name.top_level();
+        let simple_name = top_level.simple_name();
+        let unqualified = if self.is_local(class_name) {
+            true
+        } else if class_name.package_name == "java.lang" {
+            !self.local.contains(simple_name) && !self.imported.contains_key(simple_name)
+        } else {
+            self.imported.get(simple_name) == Some(&top_level.canonical_name())
+        };
+        if unqualified {
+            class_name.relative_name()
+        } else {
+            class_name.canonical_name()
+        }
+    }
+
+    /// 排好序的导入语句，static 导入为一组，其他导入为一组
+    pub fn groups(&self) -> Vec<Vec<String>> {
+        let statics = self.static_imports.iter().map(|i| format!("import static {};", i)).collect();
+        let imports = self.imports.iter().map(|i| format!("import {};", i)).collect();
+        let groups: Vec<Vec<String>> = vec![statics, imports];
+        groups.into_iter().filter(|g| !g.is_empty()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{JavaCompilationUnit, JavaFieldDeclaration, JavaTypeDeclaration, Modifier};
+
+    fn resolve(declared: &[&str], classes: &[&str]) -> JavaImports {
+        let classes = classes.iter().map(|c| ClassName::best_guess(c)).collect();
+        JavaImports::resolve("com.example.entity", declared, classes, &[])
+    }
+
+    #[test]
+    fn clash_falls_back_to_qualified_name() {
+        let imports = resolve(&["Person"], &["com.example.entity.a.Card", "com.example.entity.b.Card"]);
+        assert_eq!(imports.groups(), vec![vec!["import com.example.entity.a.Card;".to_string()]]);
+        assert_eq!(imports.class_name(&ClassName::best_guess("com.example.entity.a.Card")), "Card");
+        assert_eq!(imports.class_name(&ClassName::best_guess("com.example.entity.b.Card")), "com.example.entity.b.Card");
+    }
+
+    #[test]
+    fn clashing_fields_keep_both_types() {
+        let mut person = JavaTypeDeclaration::new(Modifier::PUBLIC, "Person", None);
+        person.add_field(JavaFieldDeclaration::new("card", "com.example.entity.a.Card", Modifier::PRIVATE, None));
+        person.add_field(JavaFieldDeclaration::new("backup", "com.example.entity.b.Card", Modifier::PRIVATE, None));
+        let mut compilation_unit = JavaCompilationUnit::new("com.example.entity", "Person");
+        compilation_unit.add_type_declaration(person);
+        let source = compilation_unit.render().unwrap();
+        assert!(source.contains("import com.example.entity.a.Card;\n"));
+        assert!(source.contains("  private Card card;\n"));
+        assert!(source.contains("  private com.example.entity.b.Card backup;\n"));
+    }
+
+    #[test]
+    fn declared_type_wins_over_import() {
+        let imports = resolve(&["Card"], &["com.example.other.Card"]);
+        assert!(imports.groups().is_empty());
+        assert_eq!(imports.class_name(&ClassName::best_guess("com.example.other.Card")), "com.example.other.Card");
+    }
+
+    #[test]
+    fn skips_same_package_and_java_lang() {
+        let imports = resolve(&["Person"], &["com.example.entity.Card", "java.lang.String", "java.util.List"]);
+        assert_eq!(imports.groups(), vec![vec!["import java.util.List;".to_string()]]);
+        assert_eq!(imports.class_name(&ClassName::best_guess("com.example.entity.Card")), "Card");
+        assert_eq!(imports.class_name(&ClassName::best_guess("java.lang.String")), "String");
+    }
+
+    #[test]
+    fn java_lang_shadowed_by_import() {
+        let imports = resolve(&["Person"], &["com.example.text.String", "java.lang.String"]);
+        assert!(imports.groups().is_empty());
+        assert_eq!(imports.class_name(&ClassName::best_guess("java.lang.String")), "String");
+        assert_eq!(imports.class_name(&ClassName::best_guess("com.example.text.String")), "com.example.text.String");
+    }
+
+    #[test]
+    fn nested_class_imports_top_level() {
+        let imports = resolve(&["Person"], &["java.util.Map.Entry"]);
+        assert_eq!(imports.groups(), vec![vec!["import java.util.Map;".to_string()]]);
+        assert_eq!(imports.class_name(&ClassName::best_guess("java.util.Map.Entry")), "Map.Entry");
+    }
+
+    #[test]
+    fn static_imports_in_own_group() {
+        let statics = vec!["org.junit.Assert.assertEquals".to_string(), "java.util.Objects.requireNonNull".to_string()];
+        let imports = JavaImports::resolve("com.example", &["Test"], vec![ClassName::best_guess("java.util.List")], &statics);
+        assert_eq!(imports.groups(), vec![
+            vec![
+                "import static java.util.Objects.requireNonNull;".to_string(),
+                "import static org.junit.Assert.assertEquals;".to_string(),
+            ],
+            vec!["import java.util.List;".to_string()],
+        ]);
+    }
+
+    #[test]
+    fn groups_are_sorted_regardless_of_order() {
+        let forward = resolve(&["Person"], &["java.util.Set", "java.time.LocalDate", "javax.persistence.Entity", "java.util.List"]);
+        let backward = resolve(&["Person"], &["java.util.List", "javax.persistence.Entity", "java.time.LocalDate", "java.util.Set"]);
+        let expected = vec![vec![
+            "import java.time.LocalDate;".to_string(),
+            "import java.util.List;".to_string(),
+            "import java.util.Set;".to_string(),
+            "import javax.persistence.Entity;".to_string(),
+        ]];
+        assert_eq!(forward.groups(), expected);
+        assert_eq!(backward.groups(), expected);
+    }
+}
@@ -0,0 +1,117 @@
+use super::{ClassName, TypeName};
+
+/// 文档注释，description 中可以使用 `{@link java.util.List}`、`{@link Foo#bar()}`，
+/// 链接的类和 `@throws` 的异常参与导入
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Javadoc {
+    pub description: String,
+    pub params: Vec<(String, String)>,
+    pub returns: Option<String>,
+    pub throws: Vec<(TypeName, String)>,
+}
+
+impl Javadoc {
+    pub fn new(description: &str) -> Javadoc {
+        Javadoc {
+            description: description.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// `@param`，类型参数的名称写作 `<T>`
+    pub fn add_param(&mut self, name: &str, description: &str) {
+        self.params.push((name.to_owned(), description.to_owned()));
+    }
+
+    /// `@return`
+    pub fn set_return(&mut self, description: &str) {
+        self.returns = Some(description.to_owned());
+    }
+
+    /// `@throws`
+    pub fn add_throws(&mut self, exception: impl Into<TypeName>, description: &str) {
+        self.throws.push((exception.into(), description.to_owned()));
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.description.trim().is_empty() && self.params.is_empty() && self.returns.is_none() && self.throws.is_empty()
+    }
+
+    /// 链接以及 `@throws` 中引用的类
+    pub fn determine_imports(&self) -> Vec<ClassName> {
+        let mut imports = vec![];
+        let texts = std::iter::once(&self.description)
+            .chain(self.params.iter().map(|(_, d)| d))
+            .chain(self.returns.iter())
+            .chain(self.throws.iter().map(|(_, d)| d));
+        for text in texts {
+            for_each_link(text, |target| {
+                if let Some(class_name) = link_class(target) {
+                    imports.push(class_name.top_level());
+                }
+                target.to_owned()
+            });
+        }
+        for (exception, _) in &self.throws {
+            imports.extend(exception.classes().into_iter().map(|c| c.top_level()));
+        }
+        imports
+    }
+}
+
+impl From<&str> for Javadoc {
+    fn from(description: &str) -> Javadoc {
+        Javadoc::new(description)
+    }
+}
+
+impl From<&String> for Javadoc {
+    fn from(description: &String) -> Javadoc {
+        Javadoc::new(description)
+    }
+}
+
+impl From<String> for Javadoc {
+    fn from(description: String) -> Javadoc {
+        Javadoc::new(&description)
+    }
+}
+
+/// 依次处理 text 中 `{@link target}` 和 `{@linkplain target label}` 的 target，返回替换后的文本
+pub(crate) fn for_each_link<F: FnMut(&str) -> String>(text: &str, mut op: F) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("{@link") {
+        let (before, tag) = rest.split_at(start);
+        result.push_str(before);
+        let keyword_end = tag[1..]
+            .find(|c: char| c.is_whitespace() || c == '}')
+            .map(|i| i + 1)
+            .unwrap_or(tag.len());
+        let body = &tag[keyword_end..];
+        let target_start = body.len() - body.trim_start().len();
+        let target_end = body[target_start..]
+            .find(|c: char| c.is_whitespace() || c == '}')
+            .map(|i| i + target_start)
+            .unwrap_or(body.len());
+        result.push_str(&tag[..keyword_end + target_start]);
+        result.push_str(&op(&body[target_start..target_end]));
+        rest = &body[target_end..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// 链接中 `#` 之前的类，没有包名的类不需要处理
+pub(crate) fn link_class(target: &str) -> Option<ClassName> {
+    let class_part = target.split('#').next().unwrap_or_default();
+    if class_part.is_empty() {
+        return None;
+    }
+    let class_name = ClassName::best_guess(class_part);
+    if class_name.package_name.is_empty() {
+        None
+    } else {
+        Some(class_name)
+    }
+}
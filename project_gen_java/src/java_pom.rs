@@ -84,11 +84,60 @@ impl Dependency {
 
 }
 
+/// 插件的 configuration 中的一个元素，可以是文本或者嵌套的元素
+#[derive(Debug,Clone)]
+pub struct Configuration {
+    pub name: String,
+    pub value: Option<String>,
+    pub children: Vec<Configuration>,
+}
+
+impl Configuration {
+    pub fn new(name: &str) -> Configuration {
+        Configuration {
+            name: name.to_owned(),
+            value: None,
+            children: vec![],
+        }
+    }
+
+    /// 只包含文本的元素，如 `<release>17</release>`
+    pub fn text(name: &str, value: &str) -> Configuration {
+        Configuration {
+            name: name.to_owned(),
+            value: Some(value.to_owned()),
+            children: vec![],
+        }
+    }
+
+    pub fn add_child(&mut self, child: Configuration) -> &mut Self {
+        self.children.push(child);
+        self
+    }
+}
+
 pub struct Plugin {
     pub group_id: String,
     pub artifact_id: String,
-    pub version: String,
-    //todo 
+    /// 由 parent 管理版本的插件不需要指定
+    pub version: Option<String>,
+    pub configuration: Vec<Configuration>,
+}
+
+impl Plugin {
+    pub fn new(group_id: &str, artifact_id: &str, version: Option<&str>) -> Plugin {
+        Plugin {
+            group_id: group_id.to_owned(),
+            artifact_id: artifact_id.to_owned(),
+            version: version.map(|s| s.to_owned()),
+            configuration: vec![],
+        }
+    }
+
+    pub fn add_configuration(&mut self, configuration: Configuration) -> &mut Self {
+        self.configuration.push(configuration);
+        self
+    }
 }
 
 pub struct Build {
@@ -98,14 +147,27 @@ pub struct Build {
 impl Default for Build {
     fn default() -> Self {
         Build {
-            plugins: vec![Plugin{
-                group_id: "org.springframework.boot".to_string(),
-                artifact_id: "spring-boot-maven-plugin".to_string(),
-                version: SPRING_BOOT_VERSION.to_string(),
-            }]
+            plugins: vec![Plugin::new(
+                "org.springframework.boot",
+                "spring-boot-maven-plugin",
+                Some(SPRING_BOOT_VERSION))]
         }
     }
 }
+
+impl Build {
+    /// groupId 和 artifactId 对应的插件，不存在时添加
+    pub fn plugin(&mut self, group_id: &str, artifact_id: &str) -> &mut Plugin {
+        let index = match self.plugins.iter().position(|p| p.group_id == group_id && p.artifact_id == artifact_id) {
+            Some(index) => index,
+            None => {
+                self.plugins.push(Plugin::new(group_id, artifact_id, None));
+                self.plugins.len() - 1
+            },
+        };
+        &mut self.plugins[index]
+    }
+}
  
 pub struct POM {
     pub model_version: String,
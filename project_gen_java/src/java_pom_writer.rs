@@ -4,7 +4,7 @@ use quick_xml::{writer::Writer, events::{BytesStart, Event, BytesEnd, BytesText}
 
 use project_gen_core::Result;
 
-use super::{POM, Dependency, Property, Build, Plugin};
+use super::{POM, Dependency, Property, Build, Plugin, Configuration};
 
 const PROJECT_XMLNS : &str = "http://maven.apache.org/POM/4.0.0";
 const PROJECT_XSI : &str = "http://www.w3.org/2001/XMLSchema-instance";
@@ -53,7 +53,7 @@ impl POMWriter {
     }
 
     fn write_properties(&mut self,properties: &Vec<Property>) -> Result<()> {
-        if properties.len() == 0 {
+        if properties.is_empty() {
             return Ok(())
         }
         self.writer.create_element("properties")
@@ -68,13 +68,13 @@ impl POMWriter {
     }
 
     fn write_dependenies(&mut self,dependencies: &Vec<Dependency>) -> Result<()> {
-        if dependencies.len() == 0 {
+        if dependencies.is_empty() {
             return Ok(())
         }
         self.writer.write_event(Event::Start(BytesStart::new("dependencies")))?;
         for d in dependencies {
             self.writer.write_event(Event::Start(BytesStart::new("dependency")))?;
-            self.write_dependency(&d)?;
+            self.write_dependency(d)?;
             self.writer.write_event(Event::End(BytesEnd::new("dependency")))?;
         }
         self.writer.write_event(Event::End(BytesEnd::new("dependencies")))?;
@@ -94,7 +94,7 @@ impl POMWriter {
     }
 
     fn write_build_plugins(&mut self,plugins: &Vec<Plugin>) -> Result<()> {
-        if plugins.len() == 0 {
+        if plugins.is_empty() {
             return Ok(())
         }
         self.writer.write_event(Event::Start(BytesStart::new("plugins")))?;
@@ -109,11 +109,34 @@ impl POMWriter {
         self.writer.write_event(Event::Start(BytesStart::new("plugin")))?;
         self.writer.create_element("groupId").write_text_content(BytesText::new(plugin.group_id.as_str()))?;
         self.writer.create_element("artifactId").write_text_content(BytesText::new(plugin.artifact_id.as_str()))?;
-        self.writer.create_element("version").write_text_content(BytesText::new(plugin.version.as_str()))?;
+        if let Some(v) = plugin.version.as_ref() {
+            self.writer.create_element("version").write_text_content(BytesText::new(v.as_str()))?;
+        }
+        if !plugin.configuration.is_empty() {
+            self.writer.write_event(Event::Start(BytesStart::new("configuration")))?;
+            for c in &plugin.configuration {
+                self.write_configuration(c)?;
+            }
+            self.writer.write_event(Event::End(BytesEnd::new("configuration")))?;
+        }
         self.writer.write_event(Event::End(BytesEnd::new("plugin")))?;
         Ok(()) 
     }
 
+    fn write_configuration(&mut self,configuration: &Configuration) -> Result<()> {
+        if configuration.children.is_empty() {
+            self.writer.create_element(configuration.name.as_str())
+                .write_text_content(BytesText::new(configuration.value.as_deref().unwrap_or_default()))?;
+            return Ok(())
+        }
+        self.writer.write_event(Event::Start(BytesStart::new(configuration.name.as_str())))?;
+        for c in &configuration.children {
+            self.write_configuration(c)?;
+        }
+        self.writer.write_event(Event::End(BytesEnd::new(configuration.name.as_str())))?;
+        Ok(())
+    }
+
 
     fn write_dependency(&mut self,dependency: &Dependency) -> Result<()> {
         self.writer.create_element("groupId").write_text_content(BytesText::new(dependency.group_id.as_str()))?;
@@ -121,6 +144,12 @@ impl POMWriter {
         if let Some(v) = dependency.version.as_ref() {
             self.writer.create_element("version").write_text_content(BytesText::new(v.as_str()))?;
         }
+        if let Some(s) = dependency.scope.as_ref() {
+            self.writer.create_element("scope").write_text_content(BytesText::new(s.as_str()))?;
+        }
+        if let Some(o) = dependency.optional {
+            self.writer.create_element("optional").write_text_content(BytesText::new(o.to_string().as_str()))?;
+        }
 
         Ok(())
     }
@@ -145,8 +174,8 @@ impl POMWriter {
         let mut file:File = OpenOptions::new()
         .write(true)
         .create(true)
-        .open(to)
-        .map(|e| e.into())?;
+        .truncate(true)
+        .open(to)?;
         self.write(pom)?;
         write!(&mut file,"{}",std::str::from_utf8(self.writer.clone().into_inner().as_slice())?)?;
         Ok(())
@@ -0,0 +1,186 @@
+use project_gen_core::{PGError, Result};
+
+/// 保护区域的开始标记，后面跟区域名，如 `// @pg:begin custom`
+pub const REGION_BEGIN: &str = "// @pg:begin";
+/// 保护区域的结束标记
+pub const REGION_END: &str = "// @pg:end";
+
+/// 已有文件中的保护区域，重新生成时按区域名放回新生成的源码中
+#[derive(Debug, Clone, Default)]
+pub struct ProtectedRegions {
+    /// 区域名以及开始、结束标记之间的行，按出现的顺序排列
+    pub regions: Vec<(String, String)>,
+}
+
+impl ProtectedRegions {
+    pub fn new() -> ProtectedRegions {
+        ProtectedRegions::default()
+    }
+
+    /// 读取源码中的保护区域，区域不能嵌套，区域名不能重复
+    pub fn parse(source: &str) -> Result<ProtectedRegions> {
+        let mut regions = ProtectedRegions::new();
+        let mut current: Option<(String, String)> = None;
+        for (number, line) in source.lines().enumerate() {
+            match (region_marker(line), current.as_mut()) {
+                (Some(Marker::Begin(name)), None) => {
+                    if regions.get(name).is_some() {
+                        return Err(region_error(number, &format!("duplicate protected region `{}`", name)));
+                    }
+                    current = Some((name.to_owned(), String::new()));
+                },
+                (Some(Marker::Begin(name)), Some((outer, _))) => {
+                    return Err(region_error(number, &format!("protected region `{}` is nested in `{}`", name, outer)));
+                },
+                (Some(Marker::End), None) => {
+                    return Err(region_error(number, &format!("`{}` without `{}`", REGION_END, REGION_BEGIN)));
+                },
+                (Some(Marker::End), Some(_)) => regions.regions.extend(current.take()),
+                (None, Some((_, content))) => {
+                    content.push_str(line);
+                    content.push('\n');
+                },
+                (None, None) => {},
+            }
+        }
+        match current {
+            Some((name, _)) => Err(PGError::StringError(format!("protected region `{}` is not closed by `{}`", name, REGION_END))),
+            None => Ok(regions),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.regions.iter().find(|(n, _)| n == name).map(|(_, content)| content.as_str())
+    }
+
+    /// 用已有的内容替换新生成的源码中同名的区域。已有的区域在新生成的源码中不存在并且不为空时返回错误，
+    /// 避免丢失手写的代码
+    pub fn apply(&self, generated: &str) -> Result<String> {
+        let generated_regions = ProtectedRegions::parse(generated)?;
+        if let Some((name, _)) = self.regions.iter()
+            .find(|(name, content)| !content.trim().is_empty() && generated_regions.get(name).is_none()) {
+            return Err(PGError::StringError(format!("protected region `{}` no longer exists in the generated code", name)));
+        }
+        let mut source = String::with_capacity(generated.len());
+        let mut skip = false;
+        for line in generated.split_inclusive('\n') {
+            match region_marker(line) {
+                Some(Marker::Begin(name)) => {
+                    source.push_str(line);
+                    if let Some(content) = self.get(name) {
+                        source.push_str(content);
+                        skip = true;
+                    }
+                },
+                Some(Marker::End) => {
+                    source.push_str(line);
+                    skip = false;
+                },
+                None if !skip => source.push_str(line),
+                None => {},
+            }
+        }
+        Ok(source)
+    }
+}
+
+enum Marker<'a> {
+    Begin(&'a str),
+    End,
+}
+
+fn region_marker(line: &str) -> Option<Marker<'_>> {
+    let line = line.trim();
+    if line == REGION_END {
+        return Some(Marker::End);
+    }
+    let name = line.strip_prefix(REGION_BEGIN)?;
+    // 标记之后必须是空白再跟区域名，`// @pg:beginx` 不是标记
+    if !name.starts_with(char::is_whitespace) {
+        return None;
+    }
+    Some(Marker::Begin(name.trim()))
+}
+
+fn region_error(number: usize, message: &str) -> PGError {
+    PGError::StringError(format!("line {}: {}", number + 1, message))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GENERATED: &str = "\
+class Person {
+  // @pg:begin custom
+  // @pg:end
+}
+";
+
+    #[test]
+    fn region_is_kept_across_regeneration() {
+        let existing = "\
+class Person {
+  private int old;
+  // @pg:begin custom
+  public int age() {
+    return 1;
+  }
+  // @pg:end
+}
+";
+        let regions = ProtectedRegions::parse(existing).unwrap();
+        assert_eq!(regions.get("custom"), Some("  public int age() {\n    return 1;\n  }\n"));
+        assert_eq!(regions.apply(GENERATED).unwrap(), "\
+class Person {
+  // @pg:begin custom
+  public int age() {
+    return 1;
+  }
+  // @pg:end
+}
+");
+    }
+
+    #[test]
+    fn new_region_stays_empty() {
+        let regions = ProtectedRegions::parse("class Person {\n}\n").unwrap();
+        assert_eq!(regions.apply(GENERATED).unwrap(), GENERATED);
+    }
+
+    #[test]
+    fn removed_region_with_code_is_an_error() {
+        let existing = "// @pg:begin imports\nimport java.util.List;\n// @pg:end\n";
+        let error = ProtectedRegions::parse(existing).unwrap().apply(GENERATED).unwrap_err();
+        assert_eq!(error.to_string(), "protected region `imports` no longer exists in the generated code");
+    }
+
+    #[test]
+    fn removed_empty_region_is_dropped() {
+        let existing = "// @pg:begin imports\n\n// @pg:end\n";
+        assert_eq!(ProtectedRegions::parse(existing).unwrap().apply(GENERATED).unwrap(), GENERATED);
+    }
+
+    #[test]
+    fn duplicate_region_is_an_error() {
+        let existing = "// @pg:begin custom\n// @pg:end\n// @pg:begin custom\n// @pg:end\n";
+        let error = ProtectedRegions::parse(existing).unwrap_err();
+        assert_eq!(error.to_string(), "line 3: duplicate protected region `custom`");
+    }
+
+    #[test]
+    fn unbalanced_markers_are_errors() {
+        let nested = "// @pg:begin a\n// @pg:begin b\n// @pg:end\n// @pg:end\n";
+        assert_eq!(ProtectedRegions::parse(nested).unwrap_err().to_string(), "line 2: protected region `b` is nested in `a`");
+        let unopened = "code();\n// @pg:end\n";
+        assert_eq!(ProtectedRegions::parse(unopened).unwrap_err().to_string(), "line 2: `// @pg:end` without `// @pg:begin`");
+        let unclosed = "// @pg:begin custom\nkept();\n";
+        assert_eq!(ProtectedRegions::parse(unclosed).unwrap_err().to_string(), "protected region `custom` is not closed by `// @pg:end`");
+    }
+
+    #[test]
+    fn marker_needs_whitespace_before_name() {
+        let regions = ProtectedRegions::parse("// @pg:beginx\n").unwrap();
+        assert!(regions.regions.is_empty());
+    }
+}
@@ -1,138 +1,188 @@
 use std::{
     path::PathBuf, 
     fs::{File, OpenOptions, self}, 
+    ops::{BitOr, BitOrAssign},
 };
 
 use project_gen_core::Result;
 
+use super::{ClassName, CodeBlock, JavaSourceCodeWriter, JavaVersion, Javadoc, TypeName, TypeParameter};
+
+/// 各种声明会输出的修饰符
+const TYPE_MODIFIERS: Modifier = Modifier::PUBLIC.union(Modifier::PROTECTED).union(Modifier::PRIVATE)
+    .union(Modifier::ABSTRACT).union(Modifier::STATIC).union(Modifier::FINAL)
+    .union(Modifier::SEALED).union(Modifier::NON_SEALED).union(Modifier::STRICTFP);
+const FIELD_MODIFIERS: Modifier = Modifier::PUBLIC.union(Modifier::PROTECTED).union(Modifier::PRIVATE)
+    .union(Modifier::STATIC).union(Modifier::FINAL).union(Modifier::TRANSIENT).union(Modifier::VOLATILE);
+const METHOD_MODIFIERS: Modifier = Modifier::PUBLIC.union(Modifier::PROTECTED).union(Modifier::PRIVATE)
+    .union(Modifier::ABSTRACT).union(Modifier::STATIC).union(Modifier::FINAL)
+    .union(Modifier::SYNCHRONIZED).union(Modifier::NATIVE).union(Modifier::STRICTFP);
+
+/// 修饰符的集合，如 `Modifier::PUBLIC | Modifier::STATIC`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
 pub struct Modifier {
-    pub modifiers: u16,
+    modifiers: u16,
 }
 
 impl Modifier {
-    pub fn new(modifiers: u16) -> Modifier {
-        Modifier { modifiers }
+    pub const PUBLIC: Modifier = Modifier { modifiers: 0b10 };
+    pub const PRIVATE: Modifier = Modifier { modifiers: 0b100 };
+    pub const PROTECTED: Modifier = Modifier { modifiers: 0b1000 };
+    pub const STATIC: Modifier = Modifier { modifiers: 0b10000 };
+    pub const FINAL: Modifier = Modifier { modifiers: 0b100000 };
+    pub const ABSTRACT: Modifier = Modifier { modifiers: 0b1000000 };
+    pub const NATIVE: Modifier = Modifier { modifiers: 0b10000000 };
+    pub const STRICTFP: Modifier = Modifier { modifiers: 0b100000000 };
+    pub const SYNCHRONIZED: Modifier = Modifier { modifiers: 0b1000000000 };
+    pub const TRANSIENT: Modifier = Modifier { modifiers: 0b10000000000 };
+    pub const VOLATILE: Modifier = Modifier { modifiers: 0b100000000000 };
+    /// 只用于类和接口，需要 Java 17
+    pub const SEALED: Modifier = Modifier { modifiers: 0b1000000000000 };
+    pub const NON_SEALED: Modifier = Modifier { modifiers: 0b10000000000000 };
+
+    /// 所有的修饰符以及源码中的写法，按 JLS 推荐的顺序排列
+    pub const ALL: [(Modifier, &'static str); 13] = [
+        (Modifier::PUBLIC, "public"),
+        (Modifier::PROTECTED, "protected"),
+        (Modifier::PRIVATE, "private"),
+        (Modifier::ABSTRACT, "abstract"),
+        (Modifier::STATIC, "static"),
+        (Modifier::FINAL, "final"),
+        (Modifier::SEALED, "sealed"),
+        (Modifier::NON_SEALED, "non-sealed"),
+        (Modifier::TRANSIENT, "transient"),
+        (Modifier::VOLATILE, "volatile"),
+        (Modifier::SYNCHRONIZED, "synchronized"),
+        (Modifier::NATIVE, "native"),
+        (Modifier::STRICTFP, "strictfp"),
+    ];
+
+    /// 没有修饰符
+    pub fn new() -> Modifier {
+        Modifier::default()
     }
 
-    fn is_public(&self) -> bool {
-        self.modifiers & 0b10 != 0
+    pub fn is_empty(&self) -> bool {
+        self.modifiers == 0
     }
 
-    fn is_private(&self) -> bool {
-        self.modifiers & 0b100!= 0
+    /// 修饰符的个数
+    pub fn len(&self) -> usize {
+        self.modifiers.count_ones() as usize
     }
 
-    fn is_protected(&self) -> bool {
-        self.modifiers & 0b1000!= 0
+    /// 是否包含 other 中的所有修饰符
+    pub fn contains(&self, other: Modifier) -> bool {
+        self.modifiers & other.modifiers == other.modifiers
     }
 
-    fn is_static(&self) -> bool {
-        self.modifiers & 0b10000!= 0
+    /// 是否包含 other 中的任意一个修饰符
+    pub fn intersects(&self, other: Modifier) -> bool {
+        self.modifiers & other.modifiers != 0
     }
 
-    fn is_final(&self) -> bool {
-        self.modifiers & 0b100000!= 0
+    /// 两个集合中所有的修饰符，与 `|` 相同，可以用于常量
+    pub const fn union(self, other: Modifier) -> Modifier {
+        Modifier { modifiers: self.modifiers | other.modifiers }
     }
 
-    fn is_abstract(&self) -> bool {
-        self.modifiers & 0b1000000!= 0
+    pub fn insert(&mut self, other: Modifier) {
+        self.modifiers |= other.modifiers;
     }
 
-    fn is_native(&self) -> bool {
-        self.modifiers & 0b10000000!= 0
+    pub fn remove(&mut self, other: Modifier) {
+        self.modifiers &= !other.modifiers;
     }
 
-    fn is_strictfp(&self) -> bool {
-        self.modifiers & 0b100000000!= 0   
+    /// 同时在两个集合中的修饰符
+    pub fn intersection(&self, other: Modifier) -> Modifier {
+        Modifier { modifiers: self.modifiers & other.modifiers }
     }
 
-    fn is_synchronized(&self) -> bool {
-        self.modifiers & 0b1000000000!= 0
+    /// 在这个集合中而不在 other 中的修饰符
+    pub fn difference(&self, other: Modifier) -> Modifier {
+        Modifier { modifiers: self.modifiers & !other.modifiers }
     }
 
-    fn is_transient(&self) -> bool {
-        self.modifiers & 0b10000000000!= 0
+    /// 包含的修饰符，如 `["public", "static"]`
+    pub fn names(&self) -> Vec<&'static str> {
+        Modifier::ALL
+            .iter()
+            .filter(|(modifier, _)| self.contains(*modifier))
+            .map(|(_, name)| *name)
+            .collect()
     }
 
-    fn is_volatile(&self) -> bool {
-        self.modifiers & 0b100000000000!= 0
+    pub(crate) fn is_static(&self) -> bool {
+        self.contains(Modifier::STATIC)
     }
 
-    pub fn gen_type_modifiers(&self) -> String {
-        let modifiers = [
-            (self.is_public(), "public"),
-            (self.is_private(), "private"),
-            (self.is_protected(), "protected"),
-            (self.is_static(), "static"),
-            (self.is_final(), "final"),
-            (self.is_abstract(), "abstract"),
-            (self.is_strictfp(), "strictfp"),
-        ];
-        let modifier_strs :Vec<String>= modifiers
-            .iter()
-            .filter(|&(is_present, _)| *is_present)
-            .map(|(_, modifier)| modifier.to_string())
-            .collect();
+    pub(crate) fn is_final(&self) -> bool {
+        self.contains(Modifier::FINAL)
+    }
+
+    pub(crate) fn is_abstract(&self) -> bool {
+        self.contains(Modifier::ABSTRACT)
+    }
+
+    pub(crate) fn is_sealed(&self) -> bool {
+        self.contains(Modifier::SEALED)
+    }
 
-        modifier_strs.join(" ")    
+    pub(crate) fn is_non_sealed(&self) -> bool {
+        self.contains(Modifier::NON_SEALED)
+    }
+
+    /// 类型可以使用的修饰符，按 JLS 的顺序输出
+    pub fn gen_type_modifiers(&self) -> String {
+        self.render(TYPE_MODIFIERS)
     }
 
     pub fn gen_field_modifiers(&self) -> String {
-        let modifiers = [
-            (self.is_public(), "public"),
-            (self.is_private(), "private"),
-            (self.is_protected(), "protected"),
-            (self.is_static(), "static"),
-            (self.is_final(), "final"),
-            (self.is_transient(), "transient"),
-            (self.is_volatile(), "volatile"),
-        ];
-        let modifier_strs :Vec<String>= modifiers
-            .iter()
-            .filter(|&(is_present, _)| *is_present)
-            .map(|(_, modifier)| modifier.to_string())
-            .collect();
+        self.render(FIELD_MODIFIERS)
+    }
 
-        modifier_strs.join(" ")    
+    /// 参数以及局部变量只能使用 final
+    pub fn gen_parameter_modifiers(&self) -> String {
+        self.render(Modifier::FINAL)
     }
 
     pub fn gen_method_modifiers(&self) -> String {
-        let modifiers = [
-            (self.is_public(), "public"),
-            (self.is_private(), "private"),
-            (self.is_protected(), "protected"),
-            (self.is_static(), "static"),
-            (self.is_final(), "final"),
-            (self.is_abstract(), "abstract"),
-            (self.is_strictfp(), "strictfp"),
-            (self.is_synchronized(), "synchronized"),
-            (self.is_native(), "native"),
-        ];
-        let modifier_strs :Vec<String>= modifiers
-            .iter()
-            .filter(|&(is_present, _)| *is_present)
-            .map(|(_, modifier)| modifier.to_string())
-            .collect();
+        self.render(METHOD_MODIFIERS)
+    }
 
-        modifier_strs.join(" ")    
+    /// allowed 中包含的修饰符，以空格分隔
+    fn render(&self, allowed: Modifier) -> String {
+        self.intersection(allowed).names().join(" ")
     }
+}
+
+impl BitOr for Modifier {
+    type Output = Modifier;
 
+    fn bitor(self, other: Modifier) -> Modifier {
+        self.union(other)
+    }
+}
+
+impl BitOrAssign for Modifier {
+    fn bitor_assign(&mut self, other: Modifier) {
+        self.insert(other);
+    }
 }
 
+#[derive(Default)]
 pub enum ValueType {
     Class,
+    #[default]
     Value,
-}
-
-impl Default for ValueType {
-    fn default() -> ValueType {
-        ValueType::Value
-    }
+    /// 原样输出，用于数字、布尔值等
+    Literal,
 }
 
 pub struct JavaLanguage {
     pub name: String,
-    pub version: String,
+    pub version: JavaVersion,
     pub file_extension: String
 }
 
@@ -140,12 +190,114 @@ impl Default for JavaLanguage {
     fn default() -> JavaLanguage {
         JavaLanguage {
             name: "java".to_string(),
-            version: "1.8".to_string(),
+            version: JavaVersion::default(),
             file_extension: "java".to_string()
         }
     }
 }
 
+/// 注解的属性值
+pub enum AnnotationValue {
+    String(String),
+    Int(i32),
+    Long(i64),
+    Bool(bool),
+    Char(char),
+    /// 类字面量 `Foo.class`
+    Class(TypeName),
+    /// 枚举常量，如 `(javax.persistence.GenerationType, IDENTITY)`，枚举类会被导入
+    Enum(TypeName, String),
+    /// 数组 `{a, b}`，只有一个元素时省略大括号
+    Array(Vec<AnnotationValue>),
+    /// 嵌套的注解
+    Annotation(JavaAnnotationDeclaration),
+    /// 文本块，用于多行的 sql 等，需要 Java 15
+    TextBlock(String),
+    /// 原样输出，如常量表达式
+    Literal(String),
+}
+
+impl AnnotationValue {
+    /// 枚举常量，constant 为枚举的全限定名加上常量名，如 `java.lang.annotation.RetentionPolicy.RUNTIME`
+    pub fn enum_constant(constant: &str) -> AnnotationValue {
+        let (enum_type, name) = constant.rsplit_once('.').unwrap_or(("", constant));
+        AnnotationValue::Enum(TypeName::from(enum_type), name.to_owned())
+    }
+
+    fn determine_imports(&self) -> Vec<ClassName> {
+        match self {
+            AnnotationValue::Class(ty) | AnnotationValue::Enum(ty, _) => type_imports(ty),
+            AnnotationValue::Array(values) => values.iter().flat_map(|v| v.determine_imports()).collect(),
+            AnnotationValue::Annotation(annotation) => annotation.determine_imports(),
+            _ => vec![],
+        }
+    }
+
+    /// 是否包含文本块
+    pub(crate) fn has_text_block(&self) -> bool {
+        match self {
+            AnnotationValue::TextBlock(_) => true,
+            AnnotationValue::Array(values) => values.iter().any(|v| v.has_text_block()),
+            AnnotationValue::Annotation(annotation) => annotation.attributes.iter().any(|a| a.value.has_text_block()),
+            _ => false,
+        }
+    }
+}
+
+impl From<&str> for AnnotationValue {
+    fn from(value: &str) -> AnnotationValue {
+        AnnotationValue::String(value.to_owned())
+    }
+}
+
+impl From<String> for AnnotationValue {
+    fn from(value: String) -> AnnotationValue {
+        AnnotationValue::String(value)
+    }
+}
+
+impl From<&String> for AnnotationValue {
+    fn from(value: &String) -> AnnotationValue {
+        AnnotationValue::String(value.clone())
+    }
+}
+
+impl From<i32> for AnnotationValue {
+    fn from(value: i32) -> AnnotationValue {
+        AnnotationValue::Int(value)
+    }
+}
+
+impl From<i64> for AnnotationValue {
+    fn from(value: i64) -> AnnotationValue {
+        AnnotationValue::Long(value)
+    }
+}
+
+impl From<bool> for AnnotationValue {
+    fn from(value: bool) -> AnnotationValue {
+        AnnotationValue::Bool(value)
+    }
+}
+
+impl From<char> for AnnotationValue {
+    fn from(value: char) -> AnnotationValue {
+        AnnotationValue::Char(value)
+    }
+}
+
+impl From<JavaAnnotationDeclaration> for AnnotationValue {
+    fn from(value: JavaAnnotationDeclaration) -> AnnotationValue {
+        AnnotationValue::Annotation(value)
+    }
+}
+
+impl From<Vec<AnnotationValue>> for AnnotationValue {
+    fn from(values: Vec<AnnotationValue>) -> AnnotationValue {
+        AnnotationValue::Array(values)
+    }
+}
+
 pub struct JavaAnnotationDeclaration{
     pub name: String,
     pub attributes: Vec<JavaAnnotationAttribute>
@@ -163,14 +315,29 @@ impl JavaAnnotationDeclaration {
         self.attributes.push(attribute);
     }
 
-    pub fn determine_imports(&self) -> Vec<String> {
-        let mut imports = vec![];
-        if is_import_type(&self.name) {
-            imports.push(self.name.clone());
+    /// 添加属性，同名的属性合并为数组
+    pub fn add_member(&mut self, name: &str, value: impl Into<AnnotationValue>) {
+        let value = value.into();
+        match self.attributes.iter_mut().find(|a| a.name == name) {
+            Some(attribute) => {
+                let previous = std::mem::replace(&mut attribute.value, AnnotationValue::Array(vec![]));
+                attribute.value = match previous {
+                    AnnotationValue::Array(mut values) => {
+                        values.push(value);
+                        AnnotationValue::Array(values)
+                    },
+                    previous => AnnotationValue::Array(vec![previous, value]),
+                };
+            },
+            None => self.attributes.push(JavaAnnotationAttribute::of(name, value)),
         }
-        
+    }
+
+    pub fn determine_imports(&self) -> Vec<ClassName> {
+        let mut imports = import_types(&self.name);
+
         for attribute in &self.attributes {
-            let imports_from_attribute = attribute.determine_imports();
+            let imports_from_attribute = attribute.value.determine_imports();
             imports.extend(imports_from_attribute);
         }
 
@@ -181,143 +348,242 @@ impl JavaAnnotationDeclaration {
 
 pub struct JavaAnnotationAttribute {
     pub name: String,
-    pub value_type: ValueType,
-    pub value: Vec<String>
+    pub value: AnnotationValue,
 }
 
 impl JavaAnnotationAttribute {
+    /// 由字符串创建属性，多个值时为数组
     pub fn new(name: &str,value_type: ValueType, value: Vec<&str>) -> JavaAnnotationAttribute {
-        JavaAnnotationAttribute {
-            name:name.to_owned(),
-            value_type,
-            value: value.iter().map(|&s| s.to_owned()).collect()
-        }
+        let mut values: Vec<AnnotationValue> = value
+            .iter()
+            .map(|&v| match value_type {
+                ValueType::Class => AnnotationValue::Class(TypeName::from(v)),
+                ValueType::Value => AnnotationValue::String(v.to_owned()),
+                ValueType::Literal => AnnotationValue::Literal(v.to_owned()),
+            })
+            .collect();
+        let value = match values.len() {
+            1 => values.remove(0),
+            _ => AnnotationValue::Array(values),
+        };
+        JavaAnnotationAttribute::of(name, value)
     }
 
-    fn determine_imports(&self) -> Vec<String> {
-        let mut imports = vec![];
-        match self.value_type {
-            ValueType::Class => {
-                for value in self.value.iter() {
-                    if is_import_type(&value) {
-                        imports.push(value.clone());
-                    }
-                }
-            },
-            _ => {}
+    pub fn of(name: &str, value: impl Into<AnnotationValue>) -> JavaAnnotationAttribute {
+        JavaAnnotationAttribute {
+            name: name.to_owned(),
+            value: value.into(),
         }
-        imports
     }
 }
 
 
 pub struct JavaFieldDeclaration{
     pub name: String,
-    pub return_type: String,
+    pub return_type: TypeName,
     pub modifiers: Modifier,
     pub value: Option<String>,
     pub annotations: Vec<JavaAnnotationDeclaration>,
+    pub javadoc: Option<Javadoc>,
 }
 
 impl JavaFieldDeclaration {
-    pub fn new(name: &str, return_type: &str, modifiers: u16, value: Option<&str>) -> JavaFieldDeclaration {
+    pub fn new(name: &str, return_type: impl Into<TypeName>, modifiers: Modifier, value: Option<&str>) -> JavaFieldDeclaration {
         JavaFieldDeclaration {
             name:name.to_owned(),
-            return_type:return_type.to_owned(),
-            modifiers:Modifier::new(modifiers),
+            return_type:return_type.into(),
+            modifiers,
             value:value.map(|s| s.to_owned()),
-            annotations: vec![]
+            annotations: vec![],
+            javadoc: None,
         }
     }
 
+    pub fn set_javadoc(&mut self, javadoc: impl Into<Javadoc>) {
+        self.javadoc = Some(javadoc.into());
+    }
+
     pub fn add_annotation(&mut self, annotation: JavaAnnotationDeclaration) {
         self.annotations.push(annotation);
     }
 
-    pub fn determine_imports(&self) -> Vec<String> {
+    pub fn determine_imports(&self) -> Vec<ClassName> {
         let mut imports = vec![];
-        if is_import_type(self.return_type.as_str()) {
-            imports.push(self.return_type.clone());
-        }
+        imports.extend(type_imports(&self.return_type));
+        imports.extend(javadoc_imports(&self.javadoc));
         for annotation in &self.annotations {
             for import in annotation.determine_imports() {
                 imports.push(import);
             }
         }
-        return imports;
+        imports
     }
 }
 
 pub struct JavaMethodParameter{
     pub name: String,
-    pub param_type: String,
+    pub param_type: TypeName,
+    /// 参数只能使用 final
+    pub modifiers: Modifier,
+    /// 可变参数，只能是最后一个参数
+    pub varargs: bool,
     pub annotations: Vec<JavaAnnotationDeclaration>
 }
 
 impl JavaMethodParameter {
-    pub fn new(name: &str, param_type: &str) -> JavaMethodParameter {
+    pub fn new(name: &str, param_type: impl Into<TypeName>) -> JavaMethodParameter {
         JavaMethodParameter {
             name:name.to_owned(),
-            param_type:param_type.to_owned(),
+            param_type:param_type.into(),
+            modifiers: Modifier::new(),
+            varargs: false,
             annotations: vec![]
         }
     }
 
+    pub fn set_modifiers(&mut self, modifiers: Modifier) {
+        self.modifiers = modifiers;
+    }
+
+    /// 可变参数，param_type 为数组时写作 `String...`，否则在类型后加上 `...`
+    pub fn set_varargs(&mut self, varargs: bool) {
+        self.varargs = varargs;
+    }
+
     pub fn add_annotation(&mut self, annotation: JavaAnnotationDeclaration) {
         self.annotations.push(annotation);
     }
 
-    pub fn determine_imports(&self) -> Vec<String> {
+    pub fn determine_imports(&self) -> Vec<ClassName> {
         let mut imports = vec![];
-        if is_import_type(self.param_type.as_str()) {
-            imports.push(self.param_type.clone());
-        }
+        imports.extend(type_imports(&self.param_type));
         for annotation in &self.annotations {
             for import in annotation.determine_imports() {
                 imports.push(import);
             }
         }
-        return imports;
+        imports
     }
 }
 
 pub struct JavaMethodDeclaration{
     pub name: String,
-    pub return_type: String,
+    pub return_type: TypeName,
+    pub type_parameters: Vec<TypeParameter>,
     pub modifiers: Modifier, 
     pub annotations: Vec<JavaAnnotationDeclaration>,
     pub parameters: Vec<JavaMethodParameter>,
-    pub statements: Vec<JavaMethodStatement>
+    /// throws 声明的异常
+    pub exceptions: Vec<TypeName>,
+    /// 方法体
+    pub code: CodeBlock,
+    pub javadoc: Option<Javadoc>,
+    pub default_value: Option<String>,
+    /// 构造方法第一行调用的 `this(...)` 或者 `super(...)`
+    pub constructor_call: Option<String>,
 }
 
 impl JavaMethodDeclaration{
-    pub fn new(name: &str, return_type: &str, modifiers: u16) -> JavaMethodDeclaration {
+    /// 构造方法的名称，写入时使用类型的名称
+    pub const CONSTRUCTOR: &'static str = "<init>";
+
+    pub fn new(name: &str, return_type: impl Into<TypeName>, modifiers: Modifier) -> JavaMethodDeclaration {
         JavaMethodDeclaration{
             name:name.to_string(),
-            return_type:return_type.to_owned(),
-            modifiers:Modifier::new(modifiers),
+            return_type:return_type.into(),
+            type_parameters: vec![],
+            modifiers,
             annotations: vec![],
             parameters: vec![],
-            statements: vec![]
+            exceptions: vec![],
+            code: CodeBlock::new(),
+            javadoc: None,
+            default_value: None,
+            constructor_call: None,
+        }
+    }
+
+    /// 构造方法，通过 JavaTypeDeclaration::add_constructor 添加
+    pub fn new_constructor(modifiers: Modifier) -> JavaMethodDeclaration {
+        JavaMethodDeclaration::new(JavaMethodDeclaration::CONSTRUCTOR, "void", modifiers)
+    }
+
+    pub fn is_constructor(&self) -> bool {
+        self.name == JavaMethodDeclaration::CONSTRUCTOR
+    }
+
+    /// 构造方法中先调用 `this(args)`
+    pub fn call_this(&mut self, args: Vec<&str>) {
+        self.constructor_call = Some(format!("this({})", args.join(", ")));
+    }
+
+    /// 构造方法中先调用 `super(args)`
+    pub fn call_super(&mut self, args: Vec<&str>) {
+        self.constructor_call = Some(format!("super({})", args.join(", ")));
+    }
+
+    pub fn set_javadoc(&mut self, javadoc: impl Into<Javadoc>) {
+        self.javadoc = Some(javadoc.into());
+    }
+
+    /// 注解类型中元素的默认值，原样输出，如 `\"\"`、`0`
+    pub fn set_default_value(&mut self, default_value: &str) {
+        self.default_value = Some(default_value.to_owned());
+    }
+
+    /// 没有方法体的方法，abstract 方法、注解类型的元素或者接口中没有语句的方法
+    pub fn is_abstract_in(&self, kind: JavaTypeKind) -> bool {
+        match kind {
+            JavaTypeKind::Annotation => true,
+            JavaTypeKind::Interface => self.modifiers.is_abstract() || self.code.is_empty(),
+            _ => self.modifiers.is_abstract(),
         }
     }
 
+    /// 接口中有方法体的实例方法需要 default 修饰
+    pub fn is_default_in(&self, kind: JavaTypeKind) -> bool {
+        kind == JavaTypeKind::Interface
+            && !self.is_abstract_in(kind)
+            && !self.modifiers.is_static()
+            && !self.modifiers.contains(Modifier::PRIVATE)
+    }
+
     pub fn add_parameter(&mut self, parameter: JavaMethodParameter) {
         self.parameters.push(parameter);
     }
 
+    /// 添加 throws 声明的异常
+    pub fn add_exception(&mut self, exception: impl Into<TypeName>) {
+        self.exceptions.push(exception.into());
+    }
+
+    /// 泛型方法的类型参数，如 `<T>`
+    pub fn add_type_parameter(&mut self, type_parameter: TypeParameter) {
+        self.type_parameters.push(type_parameter);
+    }
+
     pub fn add_annotation(&mut self, annotation: JavaAnnotationDeclaration) {
         self.annotations.push(annotation);
     }
 
     pub fn add_statement(&mut self, statement: JavaMethodStatement) {
-        self.statements.push(statement);
+        self.code.add_code(statement.into());
     }
 
-    pub fn determine_imports(&self) -> Vec<String> {
+    /// 追加方法体中的代码
+    pub fn add_code(&mut self, code: CodeBlock) {
+        self.code.add_code(code);
+    }
+
+    pub fn determine_imports(&self) -> Vec<ClassName> {
         let mut imports = vec![];
-        if is_import_type(self.return_type.as_str()) {
-            imports.push(self.return_type.clone());
+        if !self.is_constructor() {
+            imports.extend(type_imports(&self.return_type));
+        }
+        imports.extend(javadoc_imports(&self.javadoc));
+        imports.extend(self.exceptions.iter().flat_map(type_imports));
+        for type_parameter in &self.type_parameters {
+            imports.extend(type_parameter.bounds.iter().flat_map(type_imports));
         }
         for annotation in &self.annotations {
             for import in annotation.determine_imports() {
@@ -330,12 +596,8 @@ impl JavaMethodDeclaration{
             }
         }
 
-        for statement in &self.statements {
-            for import in statement.determine_imports() {
-                imports.push(import);
-            }
-        }
-        return imports;
+        imports.extend(self.code.determine_imports());
+        imports
         
     }
 
@@ -344,6 +606,8 @@ impl JavaMethodDeclaration{
 pub struct JavaMethodStatement {
     pub statement: String,
     pub args: Vec<String>,
+    /// 语句中 `$A` 依次对应的匿名类
+    pub anonymous_classes: Vec<JavaTypeDeclaration>,
 }
 
 impl JavaMethodStatement {
@@ -352,58 +616,332 @@ impl JavaMethodStatement {
         JavaMethodStatement{
             statement: statement.to_owned(),
             args: args.iter().map(|&s| s.to_owned()).collect(),
+            anonymous_classes: vec![],
         }
     }
 
-    fn determine_imports(&self) -> Vec<String> {
-        self.args
-            .iter()
-            .filter(|&s| is_import_type(s))
-            .map(|s| s.to_owned())
-            .collect()
-        
+    /// 添加语句中 `$A` 对应的匿名类，按添加的顺序依次替换
+    pub fn add_anonymous_class(&mut self, anonymous_class: JavaTypeDeclaration) {
+        self.anonymous_classes.push(anonymous_class);
     }
      
 }
 
-/// java class
+/// 静态或者实例初始化块
+pub struct JavaInitializerBlock {
+    pub is_static: bool,
+    pub code: CodeBlock,
+}
+
+impl JavaInitializerBlock {
+    /// 实例初始化块，在构造方法之前执行
+    pub fn new() -> JavaInitializerBlock {
+        JavaInitializerBlock {
+            is_static: false,
+            code: CodeBlock::new(),
+        }
+    }
+
+    /// 静态初始化块 `static { ... }`
+    pub fn new_static() -> JavaInitializerBlock {
+        JavaInitializerBlock {
+            is_static: true,
+            code: CodeBlock::new(),
+        }
+    }
+
+    pub fn add_statement(&mut self, statement: JavaMethodStatement) {
+        self.code.add_code(statement.into());
+    }
+
+    pub fn add_code(&mut self, code: CodeBlock) {
+        self.code.add_code(code);
+    }
+
+    fn determine_imports(&self) -> Vec<ClassName> {
+        self.code.determine_imports()
+    }
+}
+
+impl Default for JavaInitializerBlock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// 类型声明的种类
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JavaTypeKind {
+    #[default]
+    Class,
+    Interface,
+    Enum,
+    Record,
+    Annotation,
+    /// 匿名类，只能在语句中使用
+    Anonymous,
+}
+
+impl JavaTypeKind {
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            JavaTypeKind::Class => "class",
+            JavaTypeKind::Interface => "interface",
+            JavaTypeKind::Enum => "enum",
+            JavaTypeKind::Record => "record",
+            JavaTypeKind::Annotation => "@interface",
+            JavaTypeKind::Anonymous => "new",
+        }
+    }
+}
+
+/// 枚举常量，args 为构造参数，原样输出
+pub struct JavaEnumConstant {
+    pub name: String,
+    pub args: Vec<String>,
+    pub annotations: Vec<JavaAnnotationDeclaration>,
+    pub javadoc: Option<Javadoc>,
+}
+
+impl JavaEnumConstant {
+    pub fn new(name: &str, args: Vec<&str>) -> JavaEnumConstant {
+        JavaEnumConstant {
+            name: name.to_owned(),
+            args: args.iter().map(|&s| s.to_owned()).collect(),
+            annotations: vec![],
+            javadoc: None,
+        }
+    }
+
+    pub fn add_annotation(&mut self, annotation: JavaAnnotationDeclaration) {
+        self.annotations.push(annotation);
+    }
+
+    pub fn set_javadoc(&mut self, javadoc: impl Into<Javadoc>) {
+        self.javadoc = Some(javadoc.into());
+    }
+}
+
+/// java 类型声明，包括类、接口、枚举、record 以及注解类型
+/// 接口的 extends 可以有多个，枚举和 record 只有 implements
 pub struct JavaTypeDeclaration{
+    pub kind: JavaTypeKind,
     pub modifiers: Modifier,
     pub name: String,
-    pub extends: Option<String>,
-    pub implements: Vec<String>,
+    pub type_parameters: Vec<TypeParameter>,
+    pub extends: Vec<TypeName>,
+    pub implements: Vec<TypeName>,
+    /// sealed 类型允许的子类型
+    pub permits: Vec<TypeName>,
+    /// 枚举常量，只用于枚举
+    pub enum_constants: Vec<JavaEnumConstant>,
+    /// record 的组成部分，只用于 record
+    pub components: Vec<JavaMethodParameter>,
+    /// 匿名类的构造参数，原样输出
+    pub args: Vec<String>,
     pub fields: Vec<JavaFieldDeclaration>,
+    pub initializers: Vec<JavaInitializerBlock>,
+    pub constructors: Vec<JavaMethodDeclaration>,
     pub methods: Vec<JavaMethodDeclaration>,
+    /// 成员类型，包括静态嵌套类、内部类、接口以及枚举
+    pub types: Vec<JavaTypeDeclaration>,
     pub annotations: Vec<JavaAnnotationDeclaration>,
+    pub javadoc: Option<Javadoc>,
+    /// 写在类型末尾的保护区域，重新生成时保留区域中手写的代码
+    pub protected_regions: Vec<String>,
 }
 
 impl JavaTypeDeclaration {
-    pub fn new(modifiers: u16, name: &str, extends: Option<&str>) -> JavaTypeDeclaration {
+    pub fn new(modifiers: Modifier, name: &str, extends: Option<&str>) -> JavaTypeDeclaration {
         JavaTypeDeclaration {
-            modifiers: Modifier::new(modifiers),
+            kind: JavaTypeKind::Class,
+            modifiers,
             name: name.to_owned(),
-            extends: extends.map(|s| s.to_owned()),
+            type_parameters: vec![],
+            extends: extends.into_iter().map(TypeName::from).collect(),
             implements: vec![],
+            permits: vec![],
+            enum_constants: vec![],
+            components: vec![],
+            args: vec![],
             fields: vec![],
+            initializers: vec![],
+            constructors: vec![],
             methods: vec![],
+            types: vec![],
             annotations: vec![],
+            javadoc: None,
+            protected_regions: vec![],
         }
     }
 
+    /// 接口声明，extends 为父接口，更多的父接口通过 add_extend 添加
+    pub fn new_interface(modifiers: Modifier, name: &str, extends: Option<&str>) -> JavaTypeDeclaration {
+        let mut declaration = JavaTypeDeclaration::new(modifiers, name, extends);
+        declaration.kind = JavaTypeKind::Interface;
+        declaration
+    }
+
+    pub fn new_enum(modifiers: Modifier, name: &str) -> JavaTypeDeclaration {
+        let mut declaration = JavaTypeDeclaration::new(modifiers, name, None);
+        declaration.kind = JavaTypeKind::Enum;
+        declaration
+    }
+
+    pub fn new_record(modifiers: Modifier, name: &str) -> JavaTypeDeclaration {
+        let mut declaration = JavaTypeDeclaration::new(modifiers, name, None);
+        declaration.kind = JavaTypeKind::Record;
+        declaration
+    }
+
+    /// 注解类型 `@interface`，元素为没有参数的方法
+    pub fn new_annotation(modifiers: Modifier, name: &str) -> JavaTypeDeclaration {
+        let mut declaration = JavaTypeDeclaration::new(modifiers, name, None);
+        declaration.kind = JavaTypeKind::Annotation;
+        declaration
+    }
+
+    /// 匿名类 `new Supertype(args) { ... }`，supertype 为父类或者接口
+    pub fn new_anonymous(supertype: impl Into<TypeName>, args: Vec<&str>) -> JavaTypeDeclaration {
+        let mut declaration = JavaTypeDeclaration::new(Modifier::new(), "", None);
+        declaration.kind = JavaTypeKind::Anonymous;
+        declaration.extends.push(supertype.into());
+        declaration.args = args.iter().map(|&s| s.to_owned()).collect();
+        declaration
+    }
+
     pub fn add_field(&mut self, field: JavaFieldDeclaration) {
         self.fields.push(field);
     }
 
+    /// 添加成员类型，静态嵌套类需要 static 修饰，否则为内部类
+    pub fn add_type(&mut self, member: JavaTypeDeclaration) {
+        self.types.push(member);
+    }
+
     pub fn add_method(&mut self, method: JavaMethodDeclaration) {
         self.methods.push(method);
     }
 
+    /// 添加构造方法，可以添加多个参数不同的构造方法
+    pub fn add_constructor(&mut self, constructor: JavaMethodDeclaration) {
+        self.constructors.push(constructor);
+    }
+
+    pub fn add_initializer(&mut self, initializer: JavaInitializerBlock) {
+        self.initializers.push(initializer);
+    }
+
+    /// 在类型末尾添加保护区域
+    pub fn add_protected_region(&mut self, name: &str) {
+        self.protected_regions.push(name.to_owned());
+    }
+
     pub fn add_annotation(&mut self, annotation: JavaAnnotationDeclaration) {
         self.annotations.push(annotation);
     }
 
-    pub fn add_implement(&mut self, implement: String) {
-        self.implements.push(implement);
+    pub fn add_extend(&mut self, extend: impl Into<TypeName>) {
+        self.extends.push(extend.into());
+    }
+
+    pub fn add_implement(&mut self, implement: impl Into<TypeName>) {
+        self.implements.push(implement.into());
+    }
+
+    /// 泛型类型的类型参数，如 `<T extends Comparable<T>>`
+    pub fn add_type_parameter(&mut self, type_parameter: TypeParameter) {
+        self.type_parameters.push(type_parameter);
+    }
+
+    /// sealed 类型允许的子类型，同一个编译单元中的子类型可以省略
+    pub fn add_permit(&mut self, permit: impl Into<TypeName>) {
+        self.permits.push(permit.into());
+    }
+
+    pub fn add_enum_constant(&mut self, constant: JavaEnumConstant) {
+        self.enum_constants.push(constant);
+    }
+
+    pub fn add_component(&mut self, component: JavaMethodParameter) {
+        self.components.push(component);
+    }
+
+    pub fn set_javadoc(&mut self, javadoc: impl Into<Javadoc>) {
+        self.javadoc = Some(javadoc.into());
+    }
+
+    /// 写在 extends 和 implements 后面的类型，接口的 implements 作为 extends 处理
+    pub fn supertypes(&self) -> (Vec<&TypeName>, Vec<&TypeName>) {
+        let extends = self.extends.iter();
+        let implements = self.implements.iter();
+        match self.kind {
+            JavaTypeKind::Class => (extends.take(1).collect(), implements.collect()),
+            JavaTypeKind::Interface => (extends.chain(implements).collect(), vec![]),
+            JavaTypeKind::Enum | JavaTypeKind::Record => (vec![], implements.collect()),
+            JavaTypeKind::Annotation => (vec![], vec![]),
+            JavaTypeKind::Anonymous => (extends.chain(implements).take(1).collect(), vec![]),
+        }
+    }
+
+    /// 类型以及成员类型中声明的类型名称，会遮蔽导入的同名类
+    pub fn declared_names(&self) -> Vec<&str> {
+        let mut names = vec![];
+        if !self.name.is_empty() {
+            names.push(self.name.as_str());
+        }
+        for member in &self.types {
+            names.extend(member.declared_names());
+        }
+        names
+    }
+
+    /// 类型中引用的类，包括成员类型中引用的类
+    pub fn determine_imports(&self) -> Vec<ClassName> {
+        let mut imports: Vec<ClassName> = javadoc_imports(&self.javadoc);
+        self.annotations.iter().for_each(|annotation| {
+            imports.extend(annotation.determine_imports());
+        });
+
+        let (extends, implements) = self.supertypes();
+        extends.iter().chain(implements.iter()).for_each(|ty| {
+            imports.extend(type_imports(ty));
+        });
+        self.permits.iter().for_each(|ty| {
+            imports.extend(type_imports(ty));
+        });
+
+        self.type_parameters.iter().for_each(|type_parameter| {
+            imports.extend(type_parameter.bounds.iter().flat_map(type_imports));
+        });
+
+        self.enum_constants.iter().for_each(|constant| {
+            imports.extend(javadoc_imports(&constant.javadoc));
+            constant.annotations.iter().for_each(|annotation| {
+                imports.extend(annotation.determine_imports());
+            });
+        });
+
+        self.components.iter().for_each(|component| {
+            imports.extend(component.determine_imports());
+        });
+
+        self.fields.iter().for_each(|field| {
+            imports.extend(field.determine_imports());
+        });
+
+        self.initializers.iter().for_each(|initializer| {
+            imports.extend(initializer.determine_imports());
+        });
+
+        self.constructors.iter().chain(self.methods.iter()).for_each(|method| {
+            imports.extend(method.determine_imports());
+        });
+
+        self.types.iter().for_each(|member| {
+            imports.extend(member.determine_imports());
+        });
+        imports
     }
 
 }
@@ -413,7 +951,15 @@ impl JavaTypeDeclaration {
 pub struct JavaCompilationUnit {
     pub package_name: String,
     pub name: String,
+    /// 写在 package 之前的文件头注释，如版权声明
+    pub file_header: Option<String>,
+    /// static 导入，如 `org.junit.Assert.assertEquals`、`java.util.Objects.*`
+    pub static_imports: Vec<String>,
     pub type_declarations: Vec<JavaTypeDeclaration>,
+    /// 写在导入之后的保护区域，用于手写代码需要的导入
+    pub protected_regions: Vec<String>,
+    /// 只在文件不存在时写入，用于生成间隔模式中由用户维护的子类
+    pub write_once: bool,
 }
 
 impl JavaCompilationUnit {
@@ -421,36 +967,55 @@ impl JavaCompilationUnit {
         JavaCompilationUnit {
             package_name:package_name.to_owned(),
             name:name.to_owned(),
+            file_header: None,
+            static_imports: vec![],
             type_declarations: vec![],
-
+            protected_regions: vec![],
+            write_once: false,
         }
     }
 
-    pub fn add_type_declaration(&mut self, type_declaration: JavaTypeDeclaration) {
-        self.type_declarations.push(type_declaration);
+    /// 在导入之后添加保护区域
+    pub fn add_protected_region(&mut self, name: &str) {
+        self.protected_regions.push(name.to_owned());
     }
 
-    pub fn determine_imports(&self) -> Vec<String> {
-        let mut imports: Vec<String> = vec![];
-        let types = &self.type_declarations;
-        types.iter().for_each(|type_declaration| {
-            if let Some(extend) = type_declaration.extends.as_ref() {
-                imports.push(extend.clone());
-            }
+    pub fn set_write_once(&mut self, write_once: bool) {
+        self.write_once = write_once;
+    }
 
-            type_declaration.implements.iter().for_each(|i| {
-                imports.push(i.clone());
-            });
+    pub fn set_file_header(&mut self, file_header: &str) {
+        self.file_header = Some(file_header.to_owned());
+    }
 
-            type_declaration.fields.iter().for_each(|field| {
-                imports.extend(field.determine_imports());
-            });
+    /// 添加 static 导入，member 为 `*` 时导入类中所有的静态成员
+    pub fn add_static_import(&mut self, class_name: &str, member: &str) {
+        let import = format!("{}.{}", class_name, member);
+        if !self.static_imports.contains(&import) {
+            self.static_imports.push(import);
+        }
+    }
 
-            type_declaration.methods.iter().for_each(|method| {
-                imports.extend(method.determine_imports());
-            });
-        });
-        return imports;
+    /// 编译单元中声明的类型的名称，包括成员类型
+    pub fn declared_names(&self) -> Vec<&str> {
+        self.type_declarations.iter().flat_map(|t| t.declared_names()).collect()
+    }
+
+    /// 使用默认设置生成的源码，用于测试以及预览，需要指定java版本时使用 JavaSourceCodeWriter::render
+    pub fn render(&self) -> Result<String> {
+        JavaSourceCodeWriter::new().render(self)
+    }
+
+    pub fn add_type_declaration(&mut self, type_declaration: JavaTypeDeclaration) {
+        self.type_declarations.push(type_declaration);
+    }
+
+    /// 编译单元中引用的类，按出现的顺序排列
+    pub fn determine_imports(&self) -> Vec<ClassName> {
+        self.type_declarations
+            .iter()
+            .flat_map(|type_declaration| type_declaration.determine_imports())
+            .collect()
     }
     
 }
@@ -459,6 +1024,12 @@ pub struct JavaSourceCode {
     pub compilation_units: Vec<JavaCompilationUnit>,
 }
 
+impl Default for JavaSourceCode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 impl JavaSourceCode {
     pub fn new() -> JavaSourceCode {
         JavaSourceCode {
@@ -475,6 +1046,8 @@ impl JavaSourceCode {
 pub struct JavaSourceStructure{
     pub root_directory: PathBuf,
     pub resource_directory: PathBuf,
+    /// 测试使用的资源，如 seed 生成的 json 数据
+    pub test_resource_directory: PathBuf,
     pub source_directory: PathBuf,
     pub file_extension: String,
 }
@@ -483,10 +1056,12 @@ impl JavaSourceStructure{
     pub fn new(root_directory: PathBuf) -> JavaSourceStructure {
         let language = JavaLanguage::default();
         let source_directory = root_directory.join(language.name);
-        let resource_directory = root_directory.join("resource");
+        let resource_directory = root_directory.join("resources");
+        let test_resource_directory = root_directory.join("test-resources");
         JavaSourceStructure {
             root_directory,
             resource_directory,  
+            test_resource_directory,
             source_directory,
             file_extension: language.file_extension,
         }
@@ -502,14 +1077,101 @@ impl JavaSourceStructure{
         OpenOptions::new()
            .write(true)
            .create(true)
+           .truncate(true)
            .open(to.clone())
            .map_err(|e| e.into())
     }
+
+    /// 读取已有的源码文件，文件不存在时返回 None
+    pub fn read_source_file(&self,package_name:&str,file_name:&str) -> Result<Option<String>> {
+        let path = self.source_directory
+            .join(package_name.replace('.', "/"))
+            .join(format!("{}.{}", file_name, self.file_extension));
+        match fs::read_to_string(path) {
+            Ok(content) => Ok(Some(content)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// 写入源码文件，先写入同一目录下的临时文件再重命名，替换已有的文件
+    pub fn write_source_file(&self,package_name:&str,file_name:&str,content:&[u8]) -> Result<()> {
+        let directory = self.source_directory.join(package_name.replace('.', "/"));
+        fs::create_dir_all(&directory)?;
+        let file_name = format!("{}.{}", file_name, self.file_extension);
+        let temp = directory.join(format!(".{}.tmp", file_name));
+        fs::write(&temp, content)?;
+        if let Err(e) = fs::rename(&temp, directory.join(file_name)) {
+            let _ = fs::remove_file(&temp);
+            return Err(e.into());
+        }
+        Ok(())
+    }
+
+    /// 在资源目录下创建文件，如 application.yml、db/demo/schema.sql
+    pub fn create_resource_file(&self,file_name:&str) -> Result<File> {
+        create_file(self.resource_directory.join(file_name))
+    }
+
+    /// 在测试资源目录下创建文件，如 fixtures/demo/person.json
+    pub fn create_test_resource_file(&self,file_name:&str) -> Result<File> {
+        create_file(self.test_resource_directory.join(file_name))
+    }
+}
+
+/// 创建文件以及所在的目录，已存在的文件会被清空
+fn create_file(to: PathBuf) -> Result<File> {
+    if let Some(parent) = to.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    OpenOptions::new()
+       .write(true)
+       .create(true)
+       .truncate(true)
+       .open(to)
+       .map_err(|e| e.into())
 }
 
 
 
-fn is_import_type(ty: &str) -> bool{
-    ty.contains(".") && !ty.starts_with("java.lang.")
+/// 类型中引用的类，嵌套类取最外层的类，是否导入由写入时的导入解析决定
+pub(crate) fn type_imports(ty: &TypeName) -> Vec<ClassName> {
+    ty.classes()
+        .into_iter()
+        .map(|c| c.top_level())
+        .collect()
 }
 
+/// 文档注释中链接以及 `@throws` 引用的类
+fn javadoc_imports(javadoc: &Option<Javadoc>) -> Vec<ClassName> {
+    javadoc.as_ref().map(|j| j.determine_imports()).unwrap_or_default()
+}
+
+/// 字符串形式的类型中引用的类，如 `java.util.Map<java.lang.String, ? extends a.B>[]`
+fn import_types(ty: &str) -> Vec<ClassName> {
+    type_imports(&TypeName::parse(ty))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn modifiers_in_jls_order() {
+        let modifiers = Modifier::FINAL | Modifier::STATIC | Modifier::ABSTRACT | Modifier::PUBLIC;
+        assert_eq!(modifiers.gen_type_modifiers(), "public abstract static final");
+        let modifiers = Modifier::STRICTFP | Modifier::NATIVE | Modifier::SYNCHRONIZED | Modifier::FINAL | Modifier::STATIC | Modifier::PRIVATE;
+        assert_eq!(modifiers.gen_method_modifiers(), "private static final synchronized native strictfp");
+        let modifiers = Modifier::VOLATILE | Modifier::TRANSIENT | Modifier::STATIC | Modifier::PROTECTED;
+        assert_eq!(modifiers.gen_field_modifiers(), "protected static transient volatile");
+        assert_eq!((Modifier::NON_SEALED | Modifier::PUBLIC).gen_type_modifiers(), "public non-sealed");
+    }
+
+    #[test]
+    fn modifiers_not_allowed_for_element_are_skipped() {
+        let modifiers = Modifier::PUBLIC | Modifier::FINAL | Modifier::TRANSIENT;
+        assert_eq!(modifiers.gen_parameter_modifiers(), "final");
+        assert_eq!(modifiers.gen_method_modifiers(), "public final");
+        assert_eq!(Modifier::new().gen_type_modifiers(), "");
+    }
+}
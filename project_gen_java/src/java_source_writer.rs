@@ -1,66 +1,153 @@
 use std::{
-    fs::File, 
     io::Write,  
     fmt, 
-    collections::{HashSet, HashMap}, 
 };
 
-use project_gen_core::Result;
+use project_gen_core::{validation::ValidationErrors, PGError, Result};
 
 use super::{
     JavaSourceStructure, 
     JavaSourceCode, 
     JavaCompilationUnit, 
     JavaFieldDeclaration, 
-    JavaMethodDeclaration, JavaMethodParameter,JavaMethodStatement, JavaAnnotationDeclaration, ValueType};
+    JavaMethodDeclaration, JavaMethodParameter, JavaAnnotationDeclaration, JavaTypeDeclaration, JavaInitializerBlock, JavaEnumConstant, JavaTypeKind, AnnotationValue, TypeName, ClassName, TypeParameter, JavaLanguage, JavaVersion, JavaFeature, FormatOptions, BraceStyle, ProtectedRegions, JavaValidationError, JavaValidationErrorKind, REGION_BEGIN, REGION_END};
+use super::java_import::JavaImports;
+use super::java_code_block::{CodePart, escape_java_char, escape_java_string};
+use super::java_javadoc::{for_each_link, link_class};
+use super::Javadoc;
+use super::CodeBlock;
 
 
 
 #[derive(Debug,Clone)]
 pub struct JavaSourceCodeWriter{    
+    /// 目标版本不支持的语法会返回错误
+    version: JavaVersion,
+    format: FormatOptions,
+}
+
+impl Default for JavaSourceCodeWriter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl JavaSourceCodeWriter{
     pub fn new() -> JavaSourceCodeWriter{
-        JavaSourceCodeWriter{}
+        JavaSourceCodeWriter{
+            version: JavaLanguage::default().version,
+            format: FormatOptions::default(),
+        }
     }
 
+    pub fn set_version(&mut self,version: JavaVersion) {
+        self.version = version;
+    }
+
+    pub fn set_format(&mut self,format: FormatOptions) {
+        self.format = format;
+    }
+
+    /// 校验后写入所有的编译单元，每个文件先完整生成再替换已有的文件，
+    /// 生成失败时不会留下写了一半的文件。已有文件中保护区域的内容会保留，
+    /// write_once 的编译单元在文件已经存在时跳过
     pub fn write(&mut self,structure: &JavaSourceStructure,source_code:JavaSourceCode) -> Result<()> {
+        // 写入任何文件之前先校验，一次报告所有的问题
+        let errors = source_code.validate();
+        if !errors.is_empty() {
+            let mut validation = ValidationErrors::new();
+            for error in errors {
+                validation.add_error(error);
+            }
+            return Err(validation.into());
+        }
         let compilation_units = source_code.compilation_units;
         for compilation_unit in &compilation_units {
+            let existing = structure.read_source_file(&compilation_unit.package_name, &compilation_unit.name)?;
+            if compilation_unit.write_once && existing.is_some() {
+                continue;
+            }
             // todo 后续使用异步写文件
-            let mut compilation_unit_writer = CompilationUnitWriter::new();
-            compilation_unit_writer.write_compilation_unit(structure, compilation_unit)?;
-
+            let mut source = self.render(compilation_unit)?;
+            // 已有文件中保护区域的内容放回新生成的源码
+            if let Some(existing) = existing {
+                source = ProtectedRegions::parse(&existing)
+                    .and_then(|regions| regions.apply(&source))
+                    .map_err(|e| {
+                        let location = format!("{}.{}", compilation_unit.package_name, compilation_unit.name);
+                        validation_error(&location, JavaValidationErrorKind::ProtectedRegion { message: e.to_string() })
+                    })?;
+            }
+            structure.write_source_file(&compilation_unit.package_name, &compilation_unit.name, source.as_bytes())?;
         }
 
         Ok(())
     }
 
-    
+    /// 把编译单元写入任意的 io::Write
+    pub fn write_compilation_unit<W: Write>(&self,out: &mut W,compilation_unit: &JavaCompilationUnit) -> Result<()> {
+        let mut compilation_unit_writer = CompilationUnitWriter::new(self.version, self.format.clone());
+        compilation_unit_writer.write_compilation_unit(out, compilation_unit)
+    }
+
+    /// 编译单元的源码，用于测试以及预览
+    pub fn render(&self,compilation_unit: &JavaCompilationUnit) -> Result<String> {
+        let mut out = Vec::new();
+        self.write_compilation_unit(&mut out, compilation_unit)?;
+        Ok(String::from_utf8(out).map_err(|e| e.utf8_error())?)
+    }
+
+}
+
+/// 写入时发现的单个问题，与写入前的校验使用相同的错误类型
+fn validation_error(location: &str, kind: JavaValidationErrorKind) -> PGError {
+    let mut errors = ValidationErrors::new();
+    errors.add_error(JavaValidationError { location: location.to_owned(), kind });
+    errors.into()
+}
 
+/// 类型中的成员，用于决定成员之间的空行
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Member {
+    /// 没有注释和注解的字段
+    PlainField,
+    Field,
+    Other,
 }
 
 struct CompilationUnitWriter {
-    level: u8,
-    ident: String,
+    level: usize,
+    format: FormatOptions,
     need_ident: bool,
-    imports: HashSet<String>
+    imports: JavaImports,
+    version: JavaVersion,
+    /// 正在写入的编译单元，用于错误信息
+    unit_name: String,
 }
 
 impl CompilationUnitWriter {
-    fn new() -> CompilationUnitWriter{
+    fn new(version: JavaVersion,format: FormatOptions) -> CompilationUnitWriter{
         CompilationUnitWriter{
             level: 0,
-            ident: "    ".to_string(),
+            format,
             need_ident: false,
-            imports: HashSet::new()
+            imports: JavaImports::default(),
+            version,
+            unit_name: String::new(),
+        }
+    }
+
+    /// 目标版本不支持 feature 时返回错误，owner 为使用该语法的类型或者编译单元
+    fn require(&self,owner: &str,feature: JavaFeature) -> Result<()> {
+        if self.version.supports(feature) {
+            return Ok(());
         }
+        Err(validation_error(owner, JavaValidationErrorKind::UnsupportedFeature { feature, version: self.version }))
     }
 
-    fn write_to(&mut self,file:&mut File,fmt: fmt::Arguments<'_>) -> Result<()> {
+    fn write_to(&mut self,file:&mut dyn Write,fmt: fmt::Arguments<'_>) -> Result<()> {
         if self.need_ident {
-            write!(file,"{}",self.ident.repeat(self.level as usize))?;
+            write!(file,"{}",self.format.indent(self.level))?;
             self.need_ident = false;
         }
         file.write_fmt(fmt)?;
@@ -72,213 +159,1216 @@ impl CompilationUnitWriter {
         self.need_ident = true;
     }
 
-    fn write_with_indent<O:FnOnce(&mut File,&mut CompilationUnitWriter) -> Result<()>>(&mut self,file:&mut File,mut op:O) -> Result<()> {
+    fn write_with_indent<O:FnOnce(&mut dyn Write,&mut CompilationUnitWriter) -> Result<()>>(&mut self,file:&mut dyn Write,op:O) -> Result<()> {
         self.level += 1;
         op(file,self)?;
         self.level -= 1;
         Ok(())
     }
 
-    fn write_compilation_unit(&mut self,structure: &JavaSourceStructure,compilation_unit: &JavaCompilationUnit) -> Result<()> {
-        let mut file = structure.create_source_file(compilation_unit.package_name.clone(), compilation_unit.name.clone())?;
+    fn write_compilation_unit(&mut self,file:&mut dyn Write,compilation_unit: &JavaCompilationUnit) -> Result<()> {
+        self.unit_name = compilation_unit.name.clone();
+        if let Some(file_header) = &compilation_unit.file_header {
+            let width = self.format.max_width.saturating_sub(3);
+            let lines: Vec<String> = file_header.lines().flat_map(|line| wrap(line, width, "")).collect();
+            self.write_comment(file, "/*", &lines)?;
+        }
         // write package
-        self.write_to(&mut file, format_args!("package {};\n\n",compilation_unit.package_name))?;
+        self.write_to(file, format_args!("package {};\n\n",compilation_unit.package_name))?;
         // write imports
-        self.write_imports(&mut file,compilation_unit.determine_imports())?;
-        self.write_to(&mut file, format_args!("\n"))?;
+        self.write_imports(file,compilation_unit)?;
+        if !compilation_unit.protected_regions.is_empty() {
+            self.write_protected_regions(file, &compilation_unit.protected_regions)?;
+            self.write_to(file, format_args!("\n"))?;
+        }
         // write class
-        let type_declarations =  &compilation_unit.type_declarations;
-        for type_declaration in type_declarations {
-            self.write_annotation(&mut file, &type_declaration.annotations, false, true)?;
-            let modifers_str = type_declaration.modifiers.gen_type_modifiers();
-            self.write_to(&mut file, format_args!("{} class {} ",modifers_str,type_declaration.name))?;
-            // todo write extends
-            if let Some(extend) = type_declaration.extends.as_ref() {
-                self.write_to(&mut file, format_args!("extends {} ",extend))?;
+        for (i,type_declaration) in compilation_unit.type_declarations.iter().enumerate() {
+            if i > 0 {
+                self.write_to(file, format_args!("\n"))?;
             }
-            // todo write implements
-            self.write_to(&mut file, format_args!(" {{\n\n"))?;
-            self.need_ident();
-            if type_declaration.fields.len() > 0 {
-                self.write_type_fields(&mut file,&type_declaration.fields)?;
+            self.write_type_declaration(file, type_declaration)?;
+            self.write_to(file, format_args!("\n"))?;
+        }
+        Ok(())
+    }
+
+    /// 写入类型声明，成员类型递归写入，结束的 `}` 后面没有换行
+    fn write_type_declaration(&mut self,file:&mut dyn Write,type_declaration: &JavaTypeDeclaration) -> Result<()> {
+        if type_declaration.kind == JavaTypeKind::Record {
+            self.require(&type_declaration.name, JavaFeature::Records)?;
+        }
+        let modifiers = &type_declaration.modifiers;
+        if modifiers.is_sealed() || modifiers.is_non_sealed() || !type_declaration.permits.is_empty() {
+            self.require(&type_declaration.name, JavaFeature::SealedTypes)?;
+        }
+        self.write_javadoc(file, type_declaration.javadoc.as_ref())?;
+        self.write_annotation(file, &type_declaration.annotations, true)?;
+        self.need_ident();
+        let mut header = type_declaration.modifiers.gen_type_modifiers();
+        if !header.is_empty() {
+            header.push(' ');
+        }
+        header.push_str(&format!("{} {}{}",type_declaration.kind.keyword(),type_declaration.name,self.type_parameters(&type_declaration.type_parameters)));
+        let mut clauses = vec![];
+        let (extends, implements) = type_declaration.supertypes();
+        if !extends.is_empty() {
+            let extends: Vec<String> = extends.iter().map(|x| self.type_name(x)).collect();
+            clauses.push(format!("extends {}",extends.join(", ")));
+        }
+        if !implements.is_empty() {
+            let implements: Vec<String> = implements.iter().map(|x| self.type_name(x)).collect();
+            clauses.push(format!("implements {}",implements.join(", ")));
+        }
+        if !type_declaration.permits.is_empty() {
+            let permits: Vec<String> = type_declaration.permits.iter().map(|x| self.type_name(x)).collect();
+            clauses.push(format!("permits {}",permits.join(", ")));
+        }
+        // 超出宽度时 extends、implements 以及 permits 各自换行
+        let mut supertypes: String = clauses.iter().map(|c| format!(" {}",c)).collect();
+        let header_width = self.format.indent_columns(self.level) + width(&header) + width(&supertypes) + self.brace_width();
+        if type_declaration.kind != JavaTypeKind::Record && header_width > self.format.max_width {
+            let continuation = self.format.indent(self.level + self.format.continuation_indent);
+            supertypes = clauses.iter().map(|c| format!("\n{}{}",continuation,c)).collect();
+        }
+        if type_declaration.kind == JavaTypeKind::Record {
+            let components = self.method_parameters(&type_declaration.name, &type_declaration.components)?;
+            self.write_wrapped(file, &format!("{}(",header), &components, &format!("){}",supertypes), self.brace_width())?;
+        } else {
+            self.write_to(file, format_args!("{}{}",header,supertypes))?;
+        }
+        self.write_body(file, type_declaration, self.format.blank_line_after_type_header)
+    }
+
+    /// `{`、类型的成员以及 `}`，没有成员时为 `{}`
+    fn write_body(&mut self,file:&mut dyn Write,type_declaration: &JavaTypeDeclaration,blank_line_after_header:bool) -> Result<()> {
+        if !has_members(type_declaration) && self.format.brace_style == BraceStyle::EndOfLine {
+            return self.write_to(file, format_args!(" {{}}"));
+        }
+        self.open_brace(file)?;
+        self.write_type_body(file, type_declaration, blank_line_after_header)?;
+        self.need_ident();
+        self.write_to(file, format_args!("}}"))
+    }
+
+    /// 按格式写入 `{` 并换行
+    fn open_brace(&mut self,file:&mut dyn Write) -> Result<()> {
+        match self.format.brace_style {
+            BraceStyle::EndOfLine => self.write_to(file, format_args!(" {{\n")),
+            BraceStyle::NextLine => {
+                self.write_to(file, format_args!("\n"))?;
+                self.need_ident();
+                self.write_to(file, format_args!("{{\n"))
+            },
+        }
+    }
+
+    /// 与 `{` 写在同一行时 `{` 占用的宽度
+    fn brace_width(&self) -> usize {
+        match self.format.brace_style {
+            BraceStyle::EndOfLine => 2,
+            BraceStyle::NextLine => 0,
+        }
+    }
+
+    /// 类型的成员，依次为枚举常量、静态字段、静态初始化块、实例字段、实例初始化块、
+    /// 构造方法、方法以及成员类型
+    fn write_type_body(&mut self,file:&mut dyn Write,type_declaration: &JavaTypeDeclaration,blank_line_after_header:bool) -> Result<()> {
+        self.write_with_indent(file,|file,writer|{
+            let mut previous = None;
+            if type_declaration.kind == JavaTypeKind::Enum {
+                let has_members = !type_declaration.fields.is_empty()
+                    || !type_declaration.initializers.is_empty()
+                    || !type_declaration.constructors.is_empty()
+                    || !type_declaration.methods.is_empty()
+                    || !type_declaration.types.is_empty();
+                if !type_declaration.enum_constants.is_empty() || has_members {
+                    writer.separate(file, &mut previous, Member::Other, blank_line_after_header)?;
+                    writer.write_enum_constants(file,&type_declaration.enum_constants,has_members)?;
+                }
+            }
+            for is_static in [true, false] {
+                for field in type_declaration.fields.iter().filter(|f| f.modifiers.is_static() == is_static) {
+                    let member = if field.javadoc.is_none() && field.annotations.is_empty() { Member::PlainField } else { Member::Field };
+                    writer.separate(file, &mut previous, member, blank_line_after_header)?;
+                    writer.write_field(file, field)?;
+                }
+                for initializer in type_declaration.initializers.iter().filter(|i| i.is_static == is_static) {
+                    writer.separate(file, &mut previous, Member::Other, blank_line_after_header)?;
+                    writer.write_initializer(file, initializer)?;
+                }
             }
-            
-            if type_declaration.methods.len() > 0 {
-                self.write_type_methods(&mut file,&type_declaration.methods)?;
+            for method in type_declaration.constructors.iter().chain(type_declaration.methods.iter()) {
+                writer.separate(file, &mut previous, Member::Other, blank_line_after_header)?;
+                writer.write_method(file, type_declaration, method)?;
             }
+            for member in &type_declaration.types {
+                writer.separate(file, &mut previous, Member::Other, blank_line_after_header)?;
+                writer.write_type_declaration(file, member)?;
+                writer.write_to(file, format_args!("\n"))?;
+            }
+            if !type_declaration.protected_regions.is_empty() {
+                writer.separate(file, &mut previous, Member::Other, blank_line_after_header)?;
+                writer.write_protected_regions(file, &type_declaration.protected_regions)?;
+            }
+            Ok(())
+        })
+    }
 
-            self.write_to(&mut file, format_args!("\n}}"))?;
+    /// 保护区域的开始和结束标记，区域之间空一行
+    fn write_protected_regions(&mut self,file:&mut dyn Write,regions:&[String]) -> Result<()> {
+        for (i,region) in regions.iter().enumerate() {
+            if i > 0 {
+                self.write_to(file, format_args!("\n"))?;
+            }
+            self.need_ident();
+            self.write_to(file, format_args!("{} {}\n",REGION_BEGIN,region))?;
+            self.need_ident();
+            self.write_to(file, format_args!("{}\n",REGION_END))?;
         }
-        
-        
         Ok(())
     }
 
-    fn write_imports(&mut self,file:&mut File,imports:Vec<String>)->Result<()> {
-        let mut class_map = HashMap::<String,String>::new();
-        imports.iter().for_each(|x|{
-            class_map.insert(x.clone().rsplit(".").next().to_owned().unwrap().to_string(),x.clone());
-        });
-        let imports:HashSet<String> = class_map.values().cloned().collect();
-        self.imports = imports.clone();
-        for import in  imports {
-            self.write_to(file, format_args!("import {};\n",import))?;
+    /// 成员之前的空行，第一个成员之前按 blank_line_after_header 决定
+    fn separate(&mut self,file:&mut dyn Write,previous:&mut Option<Member>,member:Member,blank_line_after_header:bool) -> Result<()> {
+        let blank_lines = match *previous {
+            None => blank_line_after_header as usize,
+            Some(Member::PlainField | Member::Field) if member == Member::PlainField => self.format.blank_lines_between_fields,
+            Some(_) => self.format.blank_lines_between_members,
+        };
+        *previous = Some(member);
+        for _ in 0..blank_lines {
+            file.write_all(b"\n")?;
         }
-
         Ok(())
     }
 
-    fn write_type_fields(&mut self,file:&mut File,field_declarations:&Vec<JavaFieldDeclaration>)->Result<()> {
-        
-        self.write_with_indent(file,|file,writer|{
-            for field_declaration in field_declarations {
-                writer.write_annotation(file,&field_declaration.annotations,true,true)?;
-                writer.need_ident(); 
-                let modfier_str = field_declaration.modifiers.gen_field_modifiers();
-                writer.write_to(file, format_args!("{} ",modfier_str))?;
-                writer.write_to(file, format_args!("{} ",writer.get_unqualified_name(field_declaration.return_type.clone())))?;
-                writer.write_to(file, format_args!("{}",field_declaration.name))?;
-                if let Some(value) = &field_declaration.value {
-                    writer.write_to(file, format_args!(" = {}",value))?;
+    /// 匿名类表达式 `new Supertype(args) { ... }`，结束的 `}` 后面没有换行
+    fn write_anonymous_class(&mut self,file:&mut dyn Write,anonymous_class: &JavaTypeDeclaration) -> Result<()> {
+        let (supertype, _) = anonymous_class.supertypes();
+        let supertype = supertype.first().map(|x| self.type_name(x)).unwrap_or_else(|| "Object".to_string());
+        self.write_to(file, format_args!("new {}({})",supertype,anonymous_class.args.join(", ")))?;
+        self.write_body(file, anonymous_class, false)
+    }
+
+    /// 枚举常量，有其他成员时以分号结束
+    fn write_enum_constants(&mut self,file:&mut dyn Write,constants:&[JavaEnumConstant],has_members:bool)->Result<()> {
+        for (i,constant) in constants.iter().enumerate() {
+            if i > 0 && constant.javadoc.is_some() {
+                for _ in 0..self.format.blank_lines_between_members {
+                    file.write_all(b"\n")?;
                 }
-                writer.write_to(file, format_args!(";\n\n"))?;
-                
             }
-            
-            Ok(())
-        })?;
+            self.write_javadoc(file, constant.javadoc.as_ref())?;
+            self.write_annotation(file,&constant.annotations,true)?;
+            self.need_ident();
+            self.write_to(file, format_args!("{}",constant.name))?;
+            if !constant.args.is_empty() {
+                self.write_to(file, format_args!("({})",constant.args.join(", ")))?;
+            }
+            if i + 1 < constants.len() {
+                self.write_to(file, format_args!(",\n"))?;
+            } else if has_members {
+                self.write_to(file, format_args!(";\n"))?;
+            } else {
+                self.write_to(file, format_args!("\n"))?;
+            }
+        }
+        if constants.is_empty() {
+            self.need_ident();
+            self.write_to(file, format_args!(";\n"))?;
+        }
+        Ok(())
+    }
+
+    fn write_imports(&mut self,file:&mut dyn Write,compilation_unit: &JavaCompilationUnit)->Result<()> {
+        self.imports = JavaImports::resolve(
+            &compilation_unit.package_name,
+            &compilation_unit.declared_names(),
+            compilation_unit.determine_imports(),
+            &compilation_unit.static_imports,
+        );
+        for group in self.imports.groups() {
+            for import in group {
+                self.write_to(file, format_args!("{}\n",import))?;
+            }
+            self.write_to(file, format_args!("\n"))?;
+        }
 
         Ok(())
     }
 
-    fn write_annotation(&mut self,file:&mut File,annotations:&Vec<JavaAnnotationDeclaration>,need_indent:bool,need_wrap:bool)->Result<()> {
+    fn write_field(&mut self,file:&mut dyn Write,field_declaration:&JavaFieldDeclaration)->Result<()> {
+        self.write_javadoc(file, field_declaration.javadoc.as_ref())?;
+        self.write_annotation(file,&field_declaration.annotations,true)?;
+        self.need_ident(); 
+        let modfier_str = field_declaration.modifiers.gen_field_modifiers();
+        if !modfier_str.is_empty() {
+            self.write_to(file, format_args!("{} ",modfier_str))?;
+        }
+        self.write_to(file, format_args!("{} {}",self.type_name(&field_declaration.return_type),field_declaration.name))?;
+        if let Some(value) = &field_declaration.value {
+            self.write_to(file, format_args!(" = {}",value))?;
+        }
+        self.write_to(file, format_args!(";\n"))
+    }
+
+    /// 注解，own_line 为 true 时每个注解一行并且在超出宽度时换行，否则写在同一行并以空格分隔
+    fn write_annotation(&mut self,file:&mut dyn Write,annotations:&[JavaAnnotationDeclaration],own_line:bool)->Result<()> {
         for annotation in annotations {
-            if need_indent {
-                self.need_ident();
+            if annotation.attributes.iter().any(|a| a.value.has_text_block()) {
+                let owner = self.unit_name.clone();
+                self.require(&owner, JavaFeature::TextBlocks)?;
             }
-            self.write_to(file, format_args!("@{}",annotation.name))?;
-            if !annotation.attributes.is_empty() {
-                self.write_to(file, format_args!("("))?;
+            if !own_line {
+                let annotation = self.annotation(annotation);
+                self.write_to(file, format_args!("{} ",annotation))?;
+                continue;
             }
-            let mut need_comma = false;
-            for attribute in &annotation.attributes {
-                if need_comma {
-                    self.write_to(file, format_args!(","))?;
-                } else {
-                    need_comma = true;
-                }
-                if attribute.name == "value" {
-                    self.write_to(file, format_args!("\"{}\"", attribute.value.join(",")))?;
-                    continue;
-                }
-    
-                let mut attrs: Vec<String> = vec![];
-                match attribute.value_type{
-                    ValueType::Class => {
-                        attribute.value.iter().for_each(|x|{
-                            attrs.push(self.get_unqualified_name(x.clone()) + ".class");
-                        });
-                    },
-                    _ =>{}
-                }
-    
-                self.write_to(file, format_args!( "{}={{{}}}", attribute.name, attrs.join(",")))?;
+            self.need_ident();
+            let name = self.get_unqualified_name(annotation.name.clone());
+            let attributes = self.annotation_attributes(annotation);
+            if attributes.is_empty() {
+                self.write_to(file, format_args!("@{}\n",name))?;
+            } else {
+                self.write_wrapped(file, &format!("@{}(",name), &attributes, ")", 0)?;
+                self.write_to(file, format_args!("\n"))?;
             }
-            if !annotation.attributes.is_empty() {
-                self.write_to(file, format_args!(")"))?;
+        }
+        Ok(())
+    }
+
+    /// 注解在源码中的写法，只有 value 一个属性时省略属性名
+    fn annotation(&self,annotation:&JavaAnnotationDeclaration) -> String {
+        let name = self.get_unqualified_name(annotation.name.clone());
+        let attributes = self.annotation_attributes(annotation);
+        if attributes.is_empty() {
+            format!("@{}", name)
+        } else {
+            format!("@{}({})", name, attributes.join(", "))
+        }
+    }
+
+    fn annotation_attributes(&self,annotation:&JavaAnnotationDeclaration) -> Vec<String> {
+        match annotation.attributes.as_slice() {
+            [attribute] if attribute.name == "value" => vec![self.annotation_value(&attribute.value)],
+            attributes => attributes
+                .iter()
+                .map(|a| format!("{} = {}", a.name, self.annotation_value(&a.value)))
+                .collect(),
+        }
+    }
+
+    fn annotation_value(&self,value:&AnnotationValue) -> String {
+        match value {
+            AnnotationValue::String(value) => format!("\"{}\"", escape_java_string(value)),
+            AnnotationValue::Int(value) => value.to_string(),
+            AnnotationValue::Long(value) => format!("{}L", value),
+            AnnotationValue::Bool(value) => value.to_string(),
+            AnnotationValue::Char(value) => format!("'{}'", escape_java_char(*value)),
+            AnnotationValue::Class(ty) => format!("{}.class", self.type_name(ty)),
+            AnnotationValue::Enum(ty, constant) => format!("{}.{}", self.type_name(ty), constant),
+            AnnotationValue::Array(values) if values.len() == 1 => self.annotation_value(&values[0]),
+            AnnotationValue::Array(values) => {
+                let values: Vec<String> = values.iter().map(|v| self.annotation_value(v)).collect();
+                format!("{{{}}}", values.join(", "))
+            },
+            AnnotationValue::Annotation(annotation) => self.annotation(annotation),
+            AnnotationValue::Literal(value) => value.clone(),
+            AnnotationValue::TextBlock(value) => self.text_block(value),
+        }
+    }
+
+    /// 文本块，内容以及结束的 `"""` 使用续行缩进
+    fn text_block(&self,value:&str) -> String {
+        let indent = self.format.indent(self.level + self.format.continuation_indent);
+        let mut text = String::from("\"\"\"\n");
+        for line in value.lines() {
+            let line = line.replace('\\', "\\\\").replace("\"\"\"", "\\\"\"\"");
+            if !line.trim().is_empty() {
+                text.push_str(&indent);
+                text.push_str(line.trim_end());
             }
+            text.push('\n');
+        }
+        text.push_str(&indent);
+        text.push_str("\"\"\"");
+        text
+    }
 
-            
-            if need_wrap {
-                self.write_to(file, format_args!("\n"))?;
+    /// 依次写入 prefix、以 `, ` 分隔的 items 以及 suffix，reserved 为同一行后续内容的宽度。
+    /// 超出宽度时在 prefix 之后换行并使用续行缩进，仍然超出宽度时每项一行
+    fn write_wrapped(&mut self,file:&mut dyn Write,prefix:&str,items:&[String],suffix:&str,reserved:usize) -> Result<()> {
+        let max_width = self.format.max_width;
+        let single = format!("{}{}{}", prefix, items.join(", "), suffix);
+        let start = self.format.indent_columns(self.level);
+        if items.is_empty() || single.contains('\n') || start + width(&single) + reserved <= max_width {
+            return self.write_to(file, format_args!("{}",single));
+        }
+        let level = self.level + self.format.continuation_indent;
+        let continuation = self.format.indent(level);
+        let filled = format!("{}{}", items.join(", "), suffix);
+        if self.format.indent_columns(level) + width(&filled) + reserved <= max_width {
+            self.write_to(file, format_args!("{}\n{}{}",prefix,continuation,filled))
+        } else {
+            let separator = format!(",\n{}", continuation);
+            self.write_to(file, format_args!("{}\n{}{}{}",prefix,continuation,items.join(&separator),suffix))
+        }
+    }
+
+    /// 写入文档注释，每行以 ` * ` 开头，超出宽度的行在空白处换行，
+    /// 标签的后续行缩进四个空格
+    fn write_javadoc(&mut self,file:&mut dyn Write,javadoc:Option<&Javadoc>)->Result<()> {
+        let javadoc = match javadoc {
+            Some(javadoc) if !javadoc.is_empty() => javadoc,
+            _ => return Ok(()),
+        };
+        self.need_ident();
+        let width = self.format.max_width.saturating_sub(self.format.indent_columns(self.level) + 3);
+        let mut lines = vec![];
+        for line in javadoc.description.trim().lines() {
+            lines.extend(wrap(&self.resolve_links(line), width, ""));
+        }
+        let mut tags = vec![];
+        for (name, description) in &javadoc.params {
+            tags.push(format!("@param {} {}", name, description));
+        }
+        if let Some(returns) = &javadoc.returns {
+            tags.push(format!("@return {}", returns));
+        }
+        for (exception, description) in &javadoc.throws {
+            tags.push(format!("@throws {} {}", self.type_name(exception), description));
+        }
+        if !lines.is_empty() && !tags.is_empty() {
+            lines.push(String::new());
+        }
+        for tag in tags {
+            lines.extend(wrap(&self.resolve_links(&tag), width, "    "));
+        }
+        self.write_comment(file, "/**", &lines)
+    }
+
+    /// 写入 `/**` 或者 `/*` 开头的多行注释
+    fn write_comment(&mut self,file:&mut dyn Write,start:&str,lines:&[String])->Result<()> {
+        self.write_to(file, format_args!("{}\n",start))?;
+        for line in lines {
+            self.need_ident();
+            let line = line.replace("*/", "*&#47;");
+            if line.trim().is_empty() {
+                self.write_to(file, format_args!(" *\n"))?;
+            } else {
+                self.write_to(file, format_args!(" * {}\n", line.trim_end()))?;
             }
         }
+        self.need_ident();
+        self.write_to(file, format_args!(" */\n"))?;
         Ok(())
     }
 
-    fn write_type_methods(&mut self,file:&mut File,method_declarations:&Vec<JavaMethodDeclaration>)->Result<()> {
-        
+    /// 文档注释链接中的类使用导入后的名称
+    fn resolve_links(&self,text:&str) -> String {
+        for_each_link(text, |target| match (link_class(target), target.find('#')) {
+            (Some(class_name), member) => {
+                let member = member.map(|i| &target[i..]).unwrap_or_default();
+                format!("{}{}", self.class_name(&class_name), member)
+            },
+            (None, _) => target.to_owned(),
+        })
+    }
+
+    /// 初始化块 `static { ... }` 或者 `{ ... }`
+    fn write_initializer(&mut self,file:&mut dyn Write,initializer:&JavaInitializerBlock)->Result<()> {
+        self.need_ident();
+        if initializer.is_static {
+            self.write_to(file, format_args!("static"))?;
+            if initializer.code.is_empty() && self.format.brace_style == BraceStyle::EndOfLine {
+                return self.write_to(file, format_args!(" {{}}\n"));
+            }
+            self.open_brace(file)?;
+        } else {
+            self.write_to(file, format_args!("{{\n"))?;
+        }
         self.write_with_indent(file,|file,writer|{
-            for method_declaration in method_declarations {
-                writer.write_annotation(file,&method_declaration.annotations,true,true)?;
-                writer.need_ident();
-                let modfier_str = method_declaration.modifiers.gen_method_modifiers();
-                writer.write_to(file, format_args!("{} ",modfier_str))?;
-                writer.write_to(file, format_args!("{} {}(",writer.get_unqualified_name(method_declaration.return_type.clone()),method_declaration.name))?;
-                let params = &method_declaration.parameters;
-                if params.len() > 0 {
-                    writer.write_method_paramters(file, params)?;
-                }
-                writer.write_to(file, format_args!(") {{\n"))?;
-                writer.write_with_indent(file,|file,writer|{
-                    writer.write_method_statements(file,&method_declaration.statements)?;
-                    Ok(())  
-                })?;
-                // method over
-                writer.write_to(file, format_args!("\n"))?;
+            writer.write_code(file,&initializer.code)
+        })?;
+        self.need_ident();
+        self.write_to(file, format_args!("}}\n"))
+    }
+
+    /// 方法以及构造方法，方法体为空时写作 `{}`
+    fn write_method(&mut self,file:&mut dyn Write,type_declaration:&JavaTypeDeclaration,method_declaration:&JavaMethodDeclaration)->Result<()> {
+        let kind = type_declaration.kind;
+        self.write_javadoc(file, method_declaration.javadoc.as_ref())?;
+        self.write_annotation(file,&method_declaration.annotations,true)?;
+        self.need_ident();
+        let mut prefix = method_declaration.modifiers.gen_method_modifiers();
+        if !prefix.is_empty() {
+            prefix.push(' ');
+        }
+        if method_declaration.is_default_in(kind) {
+            prefix.push_str("default ");
+        }
+        if !method_declaration.type_parameters.is_empty() {
+            prefix.push_str(&format!("{} ",self.type_parameters(&method_declaration.type_parameters)));
+        }
+        if method_declaration.is_constructor() {
+            prefix.push_str(&format!("{}(",type_declaration.name));
+        } else {
+            prefix.push_str(&format!("{} {}(",self.type_name(&method_declaration.return_type),method_declaration.name));
+        }
+        let owner = format!("{}.{}", type_declaration.name, method_declaration.name);
+        let params = self.method_parameters(&owner, &method_declaration.parameters)?;
+        let mut suffix = format!("){}", self.exceptions(&method_declaration.exceptions));
+        if method_declaration.is_abstract_in(kind) {
+            if let Some(default_value) = method_declaration.default_value.as_ref() {
+                suffix.push_str(&format!(" default {}",default_value));
+            }
+            suffix.push(';');
+            self.write_wrapped(file, &prefix, &params, &suffix, 0)?;
+            return self.write_to(file, format_args!("\n"));
+        }
+        self.write_wrapped(file, &prefix, &params, &suffix, self.brace_width())?;
+        if method_declaration.code.is_empty() && method_declaration.constructor_call.is_none()
+            && self.format.brace_style == BraceStyle::EndOfLine {
+            return self.write_to(file, format_args!(" {{}}\n"));
+        }
+        self.open_brace(file)?;
+        self.write_with_indent(file,|file,writer|{
+            if let Some(constructor_call) = &method_declaration.constructor_call {
                 writer.need_ident();
-                writer.write_to(file, format_args!("}}\n"))?;
+                writer.write_to(file, format_args!("{};\n",constructor_call))?;
             }
-            Ok(())   
+            writer.write_code(file,&method_declaration.code)
         })?;
-        
-        
+        self.need_ident();
+        self.write_to(file, format_args!("}}\n"))
+    }
 
+    /// 写入代码块，每行开头缩进，空行不缩进，超出宽度的链式调用在 `.` 之前换行。
+    /// 代码块总是以换行结束
+    fn write_code(&mut self,file:&mut dyn Write,code:&CodeBlock) -> Result<()> {
+        self.need_ident = false;
+        let mut line = String::new();
+        // 当前行开始时的缩进级别
+        let mut line_level = self.level;
+        // 当前行已经写入了一部分，如匿名类结束的 `}`
+        let mut mid_line = false;
+        for part in &code.parts {
+            match part {
+                CodePart::Text(text) => {
+                    for (i, piece) in text.split('\n').enumerate() {
+                        if i > 0 {
+                            self.write_code_line(file, &line, line_level, mid_line)?;
+                            line.clear();
+                            mid_line = false;
+                        }
+                        if line.is_empty() {
+                            line_level = self.level;
+                        }
+                        line.push_str(piece);
+                    }
+                },
+                CodePart::Type(type_name) => {
+                    if line.is_empty() {
+                        line_level = self.level;
+                    }
+                    line.push_str(&self.type_name(type_name));
+                },
+                CodePart::Class(anonymous_class) => {
+                    if !mid_line {
+                        write!(file,"{}",self.format.indent(line_level))?;
+                    }
+                    file.write_all(line.as_bytes())?;
+                    line.clear();
+                    self.write_anonymous_class(file, anonymous_class)?;
+                    mid_line = true;
+                },
+                CodePart::Indent => self.level += 1,
+                CodePart::Unindent => self.level = self.level.saturating_sub(1),
+                CodePart::Requires(feature) => {
+                    let owner = self.unit_name.clone();
+                    self.require(&owner, *feature)?;
+                },
+                CodePart::Invalid(message) => {
+                    return Err(validation_error(&self.unit_name, JavaValidationErrorKind::InvalidCode { message: message.clone() }));
+                },
+                CodePart::OpenBrace => {
+                    match self.format.brace_style {
+                        BraceStyle::EndOfLine => {
+                            if line.is_empty() && !mid_line {
+                                line_level = self.level;
+                                line.push('{');
+                            } else {
+                                line.push_str(" {");
+                            }
+                            self.write_code_line(file, &line, line_level, mid_line)?;
+                        },
+                        BraceStyle::NextLine => {
+                            if !line.is_empty() || mid_line {
+                                self.write_code_line(file, &line, line_level, mid_line)?;
+                            }
+                            self.write_code_line(file, "{", self.level, false)?;
+                        },
+                    }
+                    line.clear();
+                    mid_line = false;
+                    self.level += 1;
+                },
+                CodePart::BraceSeparator => {
+                    match self.format.brace_style {
+                        BraceStyle::EndOfLine => line.push(' '),
+                        BraceStyle::NextLine => {
+                            self.write_code_line(file, &line, line_level, mid_line)?;
+                            line.clear();
+                            mid_line = false;
+                        },
+                    }
+                },
+            }
+        }
+        if !line.is_empty() || mid_line {
+            self.write_code_line(file, &line, line_level, mid_line)?;
+        }
         Ok(())
     }
 
-    fn write_method_statements(&mut self,file:&mut File,statements:&Vec<JavaMethodStatement>) -> Result<()> {
-        for statement in statements {
-            self.need_ident();
-            let args = &statement.args;
-            let statement = &statement.statement;
-            let mut result = String::from(statement);
-            let mut arg_index = 0;
-            for p in 0..statement.len() {
-                if statement.chars().nth(p).unwrap() == '$' {
-                    let param_name = statement.chars().nth(p+1).unwrap();
-                    if param_name == 'T' {
-                        let replace_value = self.get_unqualified_name(args[arg_index].clone());
-                        result = result.replacen("$T", &replace_value,1);
-                    
-                    } else if param_name == 'V' {
-                        result = result.replacen("$V", args[arg_index].as_str(),1);
-                    }  
-                    arg_index += 1;
-                }
-            }
-            self.write_to(file, format_args!("{} ",result))?;
-            self.write_to(file, format_args!("\n"))?;
+    /// 写入一行代码以及换行，mid_line 为 true 时该行已经写入了缩进
+    fn write_code_line(&mut self,file:&mut dyn Write,line:&str,level:usize,mid_line:bool) -> Result<()> {
+        if mid_line {
+            writeln!(file,"{}",line)?;
+            return Ok(());
+        }
+        if line.trim().is_empty() {
+            file.write_all(b"\n")?;
+            return Ok(());
+        }
+        if line.trim_start().starts_with("//") || line.trim_start().starts_with('*') || line.trim_start().starts_with("/*") {
+            writeln!(file,"{}{}",self.format.indent(level),line)?;
+            return Ok(());
+        }
+        let columns = self.format.indent_columns(level);
+        let step = self.format.indent_columns(self.format.continuation_indent);
+        let max_width = self.format.max_width;
+        let fits = |text: &str, depth: usize| columns + step * depth + width(text) <= max_width;
+        for (depth, text) in break_code(line, 0, &fits) {
+            writeln!(file,"{}{}",self.format.indent(level + depth * self.format.continuation_indent),text)?;
         }
         Ok(())
     }
 
-    fn write_method_paramters(&mut self,file:&mut File,parameters:&Vec<JavaMethodParameter>) -> Result<()> {
-        for (i,param) in parameters.iter().enumerate() {
-            if i>0 {
-                self.write_to(file, format_args!(","))?;
-               
+    /// 参数在源码中的写法，owner 为参数所在的方法或者 record，用于错误信息
+    fn method_parameters(&self,owner:&str,parameters:&[JavaMethodParameter]) -> Result<Vec<String>> {
+        if let Some(param) = parameters.iter().rev().skip(1).find(|p| p.varargs) {
+            return Err(validation_error(owner, JavaValidationErrorKind::MisplacedVarargs { name: param.name.clone() }));
+        }
+        let parameters = parameters.iter().map(|param| {
+            let mut text = String::new();
+            for annotation in &param.annotations {
+                text.push_str(&self.annotation(annotation));
+                text.push(' ');
+            }
+            let modifier_str = param.modifiers.gen_parameter_modifiers();
+            if !modifier_str.is_empty() {
+                text.push_str(&modifier_str);
+                text.push(' ');
             }
-            self.write_annotation(file, &param.annotations, false,false)?;
-            self.write_to(file, format_args!("{} {}",self.get_unqualified_name(param.param_type.to_string()),param.name))?;
+            let param_type = match (&param.param_type, param.varargs) {
+                (TypeName::Array(component), true) => format!("{}...", self.type_name(component)),
+                (param_type, true) => format!("{}...", self.type_name(param_type)),
+                (param_type, false) => self.type_name(param_type),
+            };
+            text.push_str(&format!("{} {}",param_type,param.name));
+            text
+        }).collect();
+        Ok(parameters)
+    }
+
+    /// throws 声明，没有异常时为空
+    fn exceptions(&self,exceptions:&[TypeName]) -> String {
+        if exceptions.is_empty() {
+            return String::new();
         }
-        
-        Ok(())
+        let exceptions: Vec<String> = exceptions.iter().map(|e| self.type_name(e)).collect();
+        format!(" throws {}",exceptions.join(", "))
     }
 
+    /// 获取类型在源码中的名称，已导入或者 java.lang 下的类使用简单类名，
+    /// 泛型参数中的类型同样处理
     pub fn get_unqualified_name(&self,name: String) -> String{
-        if !name.contains(".") {
-            return name;
+        self.type_name(&TypeName::parse(&name))
+    }
+
+    fn type_name(&self,type_name: &TypeName) -> String {
+        type_name.render(&|class_name| self.class_name(class_name))
+    }
+
+    fn class_name(&self,class_name: &ClassName) -> String {
+        self.imports.class_name(class_name)
+    }
+
+    /// 类型参数声明，如 `<K, V extends Comparable<V>>`
+    fn type_parameters(&self,type_parameters: &[TypeParameter]) -> String {
+        if type_parameters.is_empty() {
+            return String::new();
+        }
+        let type_parameters: Vec<String> = type_parameters.iter().map(|t| t.render(&|c| self.class_name(c))).collect();
+        format!("<{}>", type_parameters.join(", "))
+    }
+
+}
+
+fn has_members(type_declaration: &JavaTypeDeclaration) -> bool {
+    !type_declaration.protected_regions.is_empty()
+        || !type_declaration.enum_constants.is_empty()
+        || !type_declaration.fields.is_empty()
+        || !type_declaration.initializers.is_empty()
+        || !type_declaration.constructors.is_empty()
+        || !type_declaration.methods.is_empty()
+        || !type_declaration.types.is_empty()
+}
+
+/// 显示宽度，按字符计算
+fn width(text: &str) -> usize {
+    text.chars().count()
+}
+
+/// 拆分超出宽度的一行代码，返回每一行以及续行缩进的级数。依次尝试：链式调用的 `.` 之前、
+/// 赋值的 `=` 之后、条件表达式的 `?` 和 `:` 之前、最外层的参数列表中，拆出的部分仍然过长时继续拆分
+fn break_code(line: &str, depth: usize, fits: &dyn Fn(&str, usize) -> bool) -> Vec<(usize, String)> {
+    if fits(line, depth) {
+        return vec![(depth, line.to_string())];
+    }
+    let chain = split_chain(line);
+    if !chain.is_empty() {
+        return continue_lines(&chain, depth, fits);
+    }
+    if let Some(eq) = split_assignment(line) {
+        let mut lines = vec![(depth, line[..eq].trim_end().to_string())];
+        lines.extend(break_code(line[eq..].trim_start(), depth + 1, fits));
+        return lines;
+    }
+    let ternary = split_ternary(line);
+    if !ternary.is_empty() {
+        return continue_lines(&ternary, depth, fits);
+    }
+    if let Some((open, close)) = argument_list(line) {
+        let tail = &line[close..];
+        let mut lines = vec![(depth, line[..=open].to_string())];
+        let filled = format!("{}{}", line[open + 1..close].trim(), tail);
+        if fits(&filled, depth + 1) {
+            lines.push((depth + 1, filled));
+            return lines;
+        }
+        let arguments = split_arguments(&line[open + 1..close]);
+        for (i, argument) in arguments.iter().enumerate() {
+            let text = if i + 1 == arguments.len() { format!("{}{}", argument, tail) } else { format!("{},", argument) };
+            lines.extend(break_code(&text, depth + 1, fits));
+        }
+        return lines;
+    }
+    vec![(depth, line.to_string())]
+}
+
+/// 第一段保留在原来的级数，其余各段作为续行
+fn continue_lines(segments: &[String], depth: usize, fits: &dyn Fn(&str, usize) -> bool) -> Vec<(usize, String)> {
+    segments.iter().enumerate()
+        .flat_map(|(i, segment)| break_code(segment, if i == 0 { depth } else { depth + 1 }, fits))
+        .collect()
+}
+
+/// 依次访问一行代码中不在字符串和字符字面量中的字符，同时给出字符所在的括号层数，
+/// 括号本身按括号外的层数计
+fn scan_code(line: &str, mut visit: impl FnMut(usize, char, i32)) {
+    let mut depth = 0;
+    let mut quote: Option<char> = None;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        if let Some(q) = quote {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == q {
+                quote = None;
+            }
+            continue;
+        }
+        match c {
+            '"' | '\'' => quote = Some(c),
+            '(' | '[' | '{' => {
+                visit(i, c, depth);
+                depth += 1;
+            },
+            ')' | ']' | '}' => {
+                depth -= 1;
+                visit(i, c, depth);
+            },
+            _ => visit(i, c, depth),
+        }
+    }
+}
+
+/// 在链式调用的 `.` 之前拆分一行代码，第一个调用与接收者保留在同一行，
+/// 括号、字符串以及字符字面量中的 `.` 不拆分；少于两个调用时返回空
+fn split_chain(line: &str) -> Vec<String> {
+    let mut dots = vec![];
+    scan_code(line, |i, c, depth| {
+        if c == '.' && depth == 0 {
+            let rest = &line[i + 1..];
+            let name_len = rest.find(|c: char| !(c.is_alphanumeric() || c == '_' || c == '$')).unwrap_or(rest.len());
+            let is_call = rest.chars().next().is_some_and(|c| !c.is_ascii_digit())
+                && name_len > 0
+                && rest[name_len..].starts_with('(');
+            if is_call {
+                dots.push(i);
+            }
+        }
+    });
+    if dots.len() < 2 {
+        return vec![];
+    }
+    let mut segments = vec![];
+    let mut start = 0;
+    for &dot in &dots[1..] {
+        segments.push(line[start..dot].trim_end().to_string());
+        start = dot;
+    }
+    segments.push(line[start..].to_string());
+    segments
+}
+
+/// 最外层赋值运算符之后的位置，`==`、`!=`、`<=`、`>=` 不是赋值
+fn split_assignment(line: &str) -> Option<usize> {
+    let mut found = None;
+    scan_code(line, |i, c, depth| {
+        if found.is_none() && c == '=' && depth == 0 {
+            let previous = line[..i].chars().next_back();
+            let next = line[i + 1..].chars().next();
+            if next != Some('=') && !matches!(previous, Some('=' | '!' | '<' | '>')) {
+                found = Some(i + 1);
+            }
+        }
+    });
+    found.filter(|&eq| !line[eq..].trim().is_empty())
+}
+
+/// 在最外层条件表达式的 `?` 和 `:` 之前拆分，泛型通配符 `<?` 以及方法引用 `::` 除外；没有条件表达式时返回空
+fn split_ternary(line: &str) -> Vec<String> {
+    let mut question = None;
+    let mut colon = None;
+    let mut nested = 0;
+    scan_code(line, |i, c, depth| {
+        if depth != 0 || colon.is_some() {
+            return;
+        }
+        let previous = line[..i].trim_end().chars().next_back();
+        match c {
+            '?' if !matches!(previous, Some('<' | ',')) => {
+                if question.is_none() {
+                    question = Some(i);
+                } else {
+                    nested += 1;
+                }
+            },
+            ':' if question.is_some() && previous != Some(':') && !line[i + 1..].starts_with(':') => {
+                if nested == 0 {
+                    colon = Some(i);
+                } else {
+                    nested -= 1;
+                }
+            },
+            _ => {},
+        }
+    });
+    match (question, colon) {
+        (Some(question), Some(colon)) => vec![
+            line[..question].trim_end().to_string(),
+            line[question..colon].trim_end().to_string(),
+            line[colon..].to_string(),
+        ],
+        _ => vec![],
+    }
+}
+
+/// 最外层第一个方法调用或者构造器调用的参数列表，返回括号的位置；`if (`、类型转换等不算
+fn argument_list(line: &str) -> Option<(usize, usize)> {
+    let mut open = None;
+    let mut close = None;
+    scan_code(line, |i, c, depth| {
+        if depth != 0 || close.is_some() {
+            return;
+        }
+        match c {
+            '(' if open.is_none() => {
+                let previous = line[..i].chars().next_back();
+                if previous.is_some_and(|c| c.is_alphanumeric() || c == '_' || c == '$' || c == '>') {
+                    open = Some(i);
+                }
+            },
+            ')' if open.is_some() => close = Some(i),
+            _ => {},
+        }
+    });
+    match (open, close) {
+        (Some(open), Some(close)) if !line[open + 1..close].trim().is_empty() => Some((open, close)),
+        _ => None,
+    }
+}
+
+/// 在参数列表最外层的 `,` 处拆分，泛型参数中的 `,` 不拆分
+fn split_arguments(arguments: &str) -> Vec<String> {
+    let mut commas = vec![];
+    let mut angle = 0;
+    scan_code(arguments, |i, c, depth| {
+        if depth != 0 {
+            return;
+        }
+        match c {
+            // 只把后面跟类型名、通配符或者 `>` 的 `<` 当作泛型
+            '<' if arguments[i + 1..].starts_with(|c: char| c.is_uppercase() || c == '?' || c == '>') => angle += 1,
+            '>' if angle > 0 => angle -= 1,
+            ',' if angle == 0 => commas.push(i),
+            _ => {},
         }
-        
-        if name.starts_with("java.lang.") {
-            return  name.rsplit(".").next().to_owned().unwrap().to_string()
+    });
+    let mut segments = vec![];
+    let mut start = 0;
+    for comma in commas {
+        segments.push(arguments[start..comma].trim().to_string());
+        start = comma + 1;
+    }
+    segments.push(arguments[start..].trim().to_string());
+    segments
+}
+
+/// 按空白把一行文本拆分为不超过 width 的多行，中文字符之间也可以换行，
+/// `{@link ...}` 不拆开，后续行加上 indent，过长的单词单独成行
+fn wrap(text: &str, width: usize, indent: &str) -> Vec<String> {
+    // (单词, 与前一个单词之间是否有空格)
+    let mut words: Vec<(String, bool)> = vec![];
+    let mut open = 0;
+    for word in text.split_whitespace() {
+        match words.last_mut() {
+            Some((last, _)) if open > 0 => {
+                last.push(' ');
+                last.push_str(word);
+            },
+            _ => {
+                let mut spaced = true;
+                let mut run = String::new();
+                for c in word.chars() {
+                    if is_cjk(c) {
+                        if !run.is_empty() {
+                            words.push((std::mem::take(&mut run), spaced));
+                            spaced = false;
+                        }
+                        words.push((c.to_string(), spaced));
+                        spaced = false;
+                    } else {
+                        run.push(c);
+                    }
+                }
+                if !run.is_empty() {
+                    words.push((run, spaced));
+                }
+            },
         }
+        open += word.matches('{').count() as i32 - word.matches('}').count() as i32;
+        open = open.max(0);
+    }
+    let mut lines: Vec<String> = vec![];
+    let mut line = String::new();
+    for (word, spaced) in words {
+        let separator = if spaced { 1 } else { 0 };
+        if line.trim_start().chars().count() > 0 && line.chars().count() + separator + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+            line.push_str(indent);
+        } else if spaced && !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// 中日韩文字以及全角标点，可以在字符之间换行
+fn is_cjk(c: char) -> bool {
+    matches!(c, '\u{2e80}'..='\u{9fff}' | '\u{f900}'..='\u{faff}' | '\u{ff00}'..='\u{ffef}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{AnnotationValue, CodeBlock, JavaAnnotationAttribute, JavaMethodDeclaration, JavaMethodParameter, JavaTypeDeclaration, Modifier};
+
+    /// 写入时发现的问题都是只有一项的校验错误
+    fn validation_error(result: Result<impl fmt::Debug>) -> JavaValidationError {
+        match result {
+            Err(PGError::Validation(errors)) => {
+                assert_eq!(errors.len(), 1);
+                errors.downcast::<JavaValidationError>().next().unwrap().clone()
+            },
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+    }
+
+    /// 只有一个带注解的类 `com.example.Test` 的编译单元
+    fn annotated(annotation: JavaAnnotationDeclaration) -> String {
+        let mut type_declaration = JavaTypeDeclaration::new(Modifier::PUBLIC, "Test", None);
+        type_declaration.add_annotation(annotation);
+        let mut compilation_unit = JavaCompilationUnit::new("com.example", "Test");
+        compilation_unit.add_type_declaration(type_declaration);
+        compilation_unit.render().unwrap()
+    }
+
+    #[test]
+    fn annotation_string_is_escaped() {
+        let mut annotation = JavaAnnotationDeclaration::new("com.example.Note");
+        annotation.add_member("text", "say \"hi\" \\ 你好\n");
+        assert!(annotated(annotation).contains("@Note(text = \"say \\\"hi\\\" \\\\ 你好\\n\")\npublic class Test {"));
+    }
+
+    #[test]
+    fn single_value_shorthand() {
+        let mut annotation = JavaAnnotationDeclaration::new("com.example.Note");
+        annotation.add_member("value", "a");
+        assert!(annotated(annotation).contains("@Note(\"a\")\n"));
+
+        let mut annotation = JavaAnnotationDeclaration::new("com.example.Note");
+        annotation.add_member("value", "a");
+        annotation.add_member("level", 2);
+        assert!(annotated(annotation).contains("@Note(value = \"a\", level = 2)\n"));
+    }
+
+    #[test]
+    fn literals() {
+        let mut annotation = JavaAnnotationDeclaration::new("com.example.Note");
+        annotation.add_member("count", 3);
+        annotation.add_member("size", 4_i64);
+        annotation.add_member("active", true);
+        annotation.add_member("separator", '\'');
+        annotation.add_attribute(JavaAnnotationAttribute::of("max", AnnotationValue::Literal("Integer.MAX_VALUE".to_string())));
+        assert!(annotated(annotation).contains(
+            "@Note(count = 3, size = 4L, active = true, separator = '\\'', max = Integer.MAX_VALUE)\n"));
+    }
+
+    #[test]
+    fn one_element_array_collapses() {
+        let mut annotation = JavaAnnotationDeclaration::new("java.lang.SuppressWarnings");
+        annotation.add_attribute(JavaAnnotationAttribute::of("value", vec![AnnotationValue::from("unchecked")]));
+        assert!(annotated(annotation).contains("@SuppressWarnings(\"unchecked\")\n"));
 
-        if !self.imports.contains(&name) {
-            return name;
+        let mut annotation = JavaAnnotationDeclaration::new("java.lang.SuppressWarnings");
+        annotation.add_member("value", "unchecked");
+        annotation.add_member("value", "rawtypes");
+        assert!(annotated(annotation).contains("@SuppressWarnings({\"unchecked\", \"rawtypes\"})\n"));
+    }
+
+    #[test]
+    fn nested_annotations_in_array() {
+        let mut first = JavaAnnotationDeclaration::new("javax.persistence.Index");
+        first.add_member("columnList", "name");
+        let mut second = JavaAnnotationDeclaration::new("javax.persistence.Index");
+        second.add_member("columnList", "age");
+        second.add_member("unique", true);
+        let mut table = JavaAnnotationDeclaration::new("javax.persistence.Table");
+        table.add_member("name", "person");
+        table.add_member("indexes", vec![AnnotationValue::from(first), AnnotationValue::from(second)]);
+        assert_eq!(annotated(table), "\
+package com.example;
+
+import javax.persistence.Index;
+import javax.persistence.Table;
+
+@Table(
+    name = \"person\",
+    indexes = {@Index(columnList = \"name\"), @Index(columnList = \"age\", unique = true)})
+public class Test {}
+");
+    }
+
+    #[test]
+    fn class_and_enum_values_are_imported() {
+        let mut annotation = JavaAnnotationDeclaration::new("com.example.meta.Mapping");
+        annotation.add_member("target", AnnotationValue::Class(TypeName::from("com.example.dto.PersonDto")));
+        annotation.add_member("retention", AnnotationValue::enum_constant("java.lang.annotation.RetentionPolicy.RUNTIME"));
+        annotation.add_member("fallback", AnnotationValue::Class(TypeName::from("java.lang.String")));
+        let source = annotated(annotation);
+        assert!(source.contains(
+            "import com.example.dto.PersonDto;\nimport com.example.meta.Mapping;\nimport java.lang.annotation.RetentionPolicy;\n"));
+        assert!(source.contains(
+            "@Mapping(target = PersonDto.class, retention = RetentionPolicy.RUNTIME, fallback = String.class)\n"));
+    }
+
+    #[test]
+    fn clashing_class_value_is_qualified() {
+        let mut annotation = JavaAnnotationDeclaration::new("com.example.meta.Mapping");
+        annotation.add_member("value", AnnotationValue::Class(TypeName::from("com.example.other.Test")));
+        assert!(annotated(annotation).contains("@Mapping(com.example.other.Test.class)\n"));
+    }
+
+    /// 方法 `Test.run()` 的源码，max_width 为 None 时使用默认宽度
+    fn method_source(method: JavaMethodDeclaration, max_width: Option<usize>) -> String {
+        let mut type_declaration = JavaTypeDeclaration::new(Modifier::PUBLIC, "Test", None);
+        type_declaration.add_method(method);
+        let mut compilation_unit = JavaCompilationUnit::new("com.example", "Test");
+        compilation_unit.add_type_declaration(type_declaration);
+        let mut format = FormatOptions::new();
+        if let Some(max_width) = max_width {
+            format.set_max_width(max_width);
         }
-        name.rsplit(".").next().to_owned().unwrap().to_string()
+        let mut writer = JavaSourceCodeWriter::new();
+        writer.set_format(format);
+        let source = writer.render(&compilation_unit).unwrap();
+        let body = source.split_once("public class Test {\n").unwrap().1;
+        body.strip_suffix("}\n").unwrap().to_string()
+    }
+
+    /// 方法体只有一行代码
+    fn code_source(line: &str, max_width: Option<usize>) -> String {
+        let mut method = JavaMethodDeclaration::new("run", "void", Modifier::PUBLIC);
+        method.add_code(CodeBlock::of("$L\n", vec![line.into()]));
+        method_source(method, max_width)
+    }
+
+    #[test]
+    fn short_line_is_not_wrapped() {
+        assert_eq!(code_source("return a.b().c().d(x, y ? 1 : 2);", None), "  public void run() {
+    return a.b().c().d(x, y ? 1 : 2);
+  }
+");
+    }
+
+    #[test]
+    fn chained_calls_break_before_dots() {
+        let line = "return builder.dataSource(dataSource).packages(\"com.example.demo.entity.demo\").persistenceUnit(\"demo\").build();";
+        assert_eq!(code_source(line, None), "  public void run() {
+    return builder.dataSource(dataSource)
+        .packages(\"com.example.demo.entity.demo\")
+        .persistenceUnit(\"demo\")
+        .build();
+  }
+");
+    }
+
+    #[test]
+    fn long_assignment_breaks_after_equals() {
+        let line = "java.util.List<java.lang.String> names = java.util.Collections.unmodifiableList(request.getNamesWithDefaults());";
+        assert_eq!(code_source(line, None), "  public void run() {
+    java.util.List<java.lang.String> names =
+        java.util.Collections.unmodifiableList(request.getNamesWithDefaults());
+  }
+");
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn ternary_breaks_before_question_and_colon() {
+        let line = "Sort.Direction direction = parts.length > 1 && \"desc\".equalsIgnoreCase(parts[1]) ? Sort.Direction.DESC : Sort.Direction.ASC;";
+        assert_eq!(code_source(line, None), "  public void run() {
+    Sort.Direction direction =
+        parts.length > 1 && \"desc\".equalsIgnoreCase(parts[1])
+            ? Sort.Direction.DESC
+            : Sort.Direction.ASC;
+  }
+");
+        let line = "return value >= threshold ? positive : negative;";
+        assert_eq!(code_source(line, None), format!("  public void run() {{\n    {}\n  }}\n", line));
+        assert_eq!(code_source(line, Some(40)), "  public void run() {
+    return value >= threshold
+        ? positive
+        : negative;
+  }
+");
+    }
+
+    #[test]
+    fn arguments_fill_then_one_per_line() {
+        let line = "predicates.add(builder.like(root.<String>get(\"name\"), \"%\" + request.getNameContains() + \"%\"));";
+        assert_eq!(code_source(line, Some(90)), "  public void run() {
+    predicates.add(
+        builder.like(root.<String>get(\"name\"), \"%\" + request.getNameContains() + \"%\"));
+  }
+");
+        assert_eq!(code_source(line, Some(60)), "  public void run() {
+    predicates.add(
+        builder.like(
+            root.<String>get(\"name\"),
+            \"%\" + request.getNameContains() + \"%\"));
+  }
+");
+    }
+
+    #[test]
+    fn generic_arguments_and_strings_are_not_split() {
+        let line = "register(new java.util.HashMap<String, Integer>(), \"a, b = c ? d : e\", other);";
+        assert_eq!(code_source(line, Some(40)), "  public void run() {
+    register(
+        new java.util.HashMap<String, Integer>(),
+        \"a, b = c ? d : e\",
+        other);
+  }
+");
+    }
+
+    #[test]
+    fn comments_are_not_wrapped() {
+        let line = "// a comment that is longer than the narrow width, with = and ? and (arguments, here)";
+        assert_eq!(code_source(line, Some(40)), format!("  public void run() {{\n    {}\n  }}\n", line));
+    }
+
+    #[test]
+    fn parameters_fall_back_to_continuation_then_one_per_line() {
+        let source = |max_width| {
+            let mut method = JavaMethodDeclaration::new("findByNameAndAge", "java.util.List<Person>", Modifier::PUBLIC | Modifier::ABSTRACT);
+            method.add_parameter(JavaMethodParameter::new("name", "java.lang.String"));
+            method.add_parameter(JavaMethodParameter::new("age", "java.lang.Integer"));
+            method.add_parameter(JavaMethodParameter::new("pageable", "org.springframework.data.domain.Pageable"));
+            method_source(method, max_width)
+        };
+        assert_eq!(source(None), "  public abstract List<Person> findByNameAndAge(String name, Integer age, Pageable pageable);\n");
+        assert_eq!(source(Some(80)), "  public abstract List<Person> findByNameAndAge(
+      String name, Integer age, Pageable pageable);
+");
+        assert_eq!(source(Some(40)), "  public abstract List<Person> findByNameAndAge(
+      String name,
+      Integer age,
+      Pageable pageable);
+");
+    }
+
+    #[test]
+    fn unsupported_feature_is_a_validation_error() {
+        let mut compilation_unit = JavaCompilationUnit::new("com.example", "Point");
+        compilation_unit.add_type_declaration(JavaTypeDeclaration::new_record(Modifier::PUBLIC, "Point"));
+        let error = validation_error(JavaSourceCodeWriter::new().render(&compilation_unit));
+        assert_eq!(error.location, "Point");
+        assert_eq!(error.kind, JavaValidationErrorKind::UnsupportedFeature { feature: JavaFeature::Records, version: JavaVersion::Java8 });
+        assert_eq!(error.to_string(), "Point: records require Java 16, but the target is Java 1.8");
+    }
+
+    #[test]
+    fn broken_protected_region_is_a_validation_error() {
+        let root = std::env::temp_dir().join(format!("pg-protected-region-{}", std::process::id()));
+        let structure = JavaSourceStructure::new(root.clone());
+        let existing = "// @pg:begin custom\nkept();\n";
+        structure.write_source_file("com.example", "Test", existing.as_bytes()).unwrap();
+
+        let mut compilation_unit = JavaCompilationUnit::new("com.example", "Test");
+        compilation_unit.add_type_declaration(JavaTypeDeclaration::new(Modifier::PUBLIC, "Test", None));
+        let mut source_code = JavaSourceCode::new();
+        source_code.add_compilation_unit(compilation_unit);
+        let result = JavaSourceCodeWriter::new().write(&structure, source_code);
+        let content = structure.read_source_file("com.example", "Test").unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let error = validation_error(result);
+        assert_eq!(error.location, "com.example.Test");
+        assert_eq!(error.kind, JavaValidationErrorKind::ProtectedRegion {
+            message: "protected region `custom` is not closed by `// @pg:end`".to_string(),
+        });
+        // 手写的代码没有被覆盖
+        assert_eq!(content.as_deref(), Some(existing));
+    }
+}
@@ -0,0 +1,309 @@
+use std::fmt;
+
+/// 基本类型以及 void
+const PRIMITIVES: [&str; 9] = ["boolean", "byte", "short", "int", "long", "char", "float", "double", "void"];
+
+/// 类名，names 为从最外层开始的类名，嵌套类有多个
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ClassName {
+    pub package_name: String,
+    pub names: Vec<String>,
+}
+
+impl ClassName {
+    pub fn new(package_name: &str, name: &str) -> ClassName {
+        ClassName {
+            package_name: package_name.to_owned(),
+            names: vec![name.to_owned()],
+        }
+    }
+
+    /// 按 java 的命名习惯从全限定名推断包名和类名，小写开头的部分为包名
+    /// 如 `java.util.Map.Entry` 推断为包 `java.util`、类 `Map.Entry`
+    pub fn best_guess(canonical_name: &str) -> ClassName {
+        let parts: Vec<&str> = canonical_name.split('.').collect();
+        let first_class = parts
+            .iter()
+            .position(|p| p.starts_with(|c: char| c.is_uppercase()))
+            .unwrap_or(parts.len() - 1);
+        ClassName {
+            package_name: parts[..first_class].join("."),
+            names: parts[first_class..].iter().map(|s| s.to_string()).collect(),
+        }
+    }
+
+    /// 嵌套类
+    pub fn nested(&self, name: &str) -> ClassName {
+        let mut nested = self.clone();
+        nested.names.push(name.to_owned());
+        nested
+    }
+
+    /// 最外层的类，导入时使用
+    pub fn top_level(&self) -> ClassName {
+        ClassName {
+            package_name: self.package_name.clone(),
+            names: self.names[..1].to_vec(),
+        }
+    }
+
+    pub fn simple_name(&self) -> &str {
+        self.names.last().map(|s| s.as_str()).unwrap_or_default()
+    }
+
+    /// 包名之后的部分，如 `Map.Entry`
+    pub fn relative_name(&self) -> String {
+        self.names.join(".")
+    }
+
+    pub fn canonical_name(&self) -> String {
+        if self.package_name.is_empty() {
+            self.relative_name()
+        } else {
+            format!("{}.{}", self.package_name, self.relative_name())
+        }
+    }
+}
+
+impl fmt::Display for ClassName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.canonical_name())
+    }
+}
+
+/// 通配符 `?`、`? extends T`、`? super T`
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Wildcard {
+    Unbounded,
+    Extends(Box<TypeName>),
+    Super(Box<TypeName>),
+}
+
+/// 字段、参数、返回值以及父类型使用的类型
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TypeName {
+    Primitive(String),
+    Class(ClassName),
+    Parameterized(ClassName, Vec<TypeName>),
+    Wildcard(Wildcard),
+    Array(Box<TypeName>),
+    /// 类型变量，如 `T`
+    Variable(String),
+}
+
+impl TypeName {
+    pub fn primitive(name: &str) -> TypeName {
+        TypeName::Primitive(name.to_owned())
+    }
+
+    /// 由全限定名推断的类
+    pub fn class(canonical_name: &str) -> TypeName {
+        TypeName::Class(ClassName::best_guess(canonical_name))
+    }
+
+    pub fn parameterized(raw_type: ClassName, arguments: Vec<TypeName>) -> TypeName {
+        TypeName::Parameterized(raw_type, arguments)
+    }
+
+    pub fn array(component: TypeName) -> TypeName {
+        TypeName::Array(Box::new(component))
+    }
+
+    pub fn variable(name: &str) -> TypeName {
+        TypeName::Variable(name.to_owned())
+    }
+
+    pub fn wildcard() -> TypeName {
+        TypeName::Wildcard(Wildcard::Unbounded)
+    }
+
+    pub fn extends_wildcard(bound: TypeName) -> TypeName {
+        TypeName::Wildcard(Wildcard::Extends(Box::new(bound)))
+    }
+
+    pub fn super_wildcard(bound: TypeName) -> TypeName {
+        TypeName::Wildcard(Wildcard::Super(Box::new(bound)))
+    }
+
+    /// 解析 java 源码中的类型写法，如 `java.util.Map<java.lang.String, ? extends a.B>[]`
+    /// 没有包名的名称作为同一个包中的类或者类型变量处理，原样输出
+    pub fn parse(text: &str) -> TypeName {
+        let tokens = tokenize(text);
+        let mut parser = Parser { tokens: &tokens, position: 0 };
+        parser.type_name()
+    }
+
+    pub fn is_primitive(&self) -> bool {
+        matches!(self, TypeName::Primitive(_))
+    }
+
+    /// 类型中引用的所有类，包括类型参数中的类
+    pub fn classes(&self) -> Vec<&ClassName> {
+        match self {
+            TypeName::Primitive(_) | TypeName::Variable(_) | TypeName::Wildcard(Wildcard::Unbounded) => vec![],
+            TypeName::Class(class_name) => vec![class_name],
+            TypeName::Parameterized(class_name, arguments) => {
+                let mut classes = vec![class_name];
+                classes.extend(arguments.iter().flat_map(|a| a.classes()));
+                classes
+            },
+            TypeName::Wildcard(Wildcard::Extends(bound) | Wildcard::Super(bound)) => bound.classes(),
+            TypeName::Array(component) => component.classes(),
+        }
+    }
+
+    /// 输出类型，class_name 决定每个类在源码中的写法
+    pub fn render(&self, class_name: &dyn Fn(&ClassName) -> String) -> String {
+        match self {
+            TypeName::Primitive(name) | TypeName::Variable(name) => name.clone(),
+            TypeName::Class(name) => class_name(name),
+            TypeName::Parameterized(name, arguments) => {
+                let arguments: Vec<String> = arguments.iter().map(|a| a.render(class_name)).collect();
+                format!("{}<{}>", class_name(name), arguments.join(", "))
+            },
+            TypeName::Wildcard(Wildcard::Unbounded) => "?".to_string(),
+            TypeName::Wildcard(Wildcard::Extends(bound)) => format!("? extends {}", bound.render(class_name)),
+            TypeName::Wildcard(Wildcard::Super(bound)) => format!("? super {}", bound.render(class_name)),
+            TypeName::Array(component) => format!("{}[]", component.render(class_name)),
+        }
+    }
+}
+
+impl fmt::Display for TypeName {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.render(&|c| c.canonical_name()))
+    }
+}
+
+impl From<&str> for TypeName {
+    fn from(text: &str) -> TypeName {
+        TypeName::parse(text)
+    }
+}
+
+impl From<&String> for TypeName {
+    fn from(text: &String) -> TypeName {
+        TypeName::parse(text)
+    }
+}
+
+impl From<String> for TypeName {
+    fn from(text: String) -> TypeName {
+        TypeName::parse(&text)
+    }
+}
+
+impl From<ClassName> for TypeName {
+    fn from(class_name: ClassName) -> TypeName {
+        TypeName::Class(class_name)
+    }
+}
+
+/// 类或者方法上的类型参数，如 `T extends Comparable<T> & Serializable`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TypeParameter {
+    pub name: String,
+    pub bounds: Vec<TypeName>,
+}
+
+impl TypeParameter {
+    pub fn new(name: &str) -> TypeParameter {
+        TypeParameter {
+            name: name.to_owned(),
+            bounds: vec![],
+        }
+    }
+
+    pub fn add_bound(&mut self, bound: impl Into<TypeName>) {
+        self.bounds.push(bound.into());
+    }
+
+    pub fn render(&self, class_name: &dyn Fn(&ClassName) -> String) -> String {
+        if self.bounds.is_empty() {
+            return self.name.clone();
+        }
+        let bounds: Vec<String> = self.bounds.iter().map(|b| b.render(class_name)).collect();
+        format!("{} extends {}", self.name, bounds.join(" & "))
+    }
+}
+
+fn tokenize(text: &str) -> Vec<&str> {
+    let mut tokens = vec![];
+    let mut start = None;
+    for (index, c) in text.char_indices() {
+        if "<>,[]?&".contains(c) || c.is_whitespace() {
+            if let Some(start) = start.take() {
+                tokens.push(&text[start..index]);
+            }
+            if !c.is_whitespace() {
+                tokens.push(&text[index..index + c.len_utf8()]);
+            }
+        } else if start.is_none() {
+            start = Some(index);
+        }
+    }
+    if let Some(start) = start {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+struct Parser<'a> {
+    tokens: &'a [&'a str],
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&'a str> {
+        self.tokens.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<&'a str> {
+        let token = self.peek();
+        self.position += 1;
+        token
+    }
+
+    fn type_name(&mut self) -> TypeName {
+        let mut type_name = match self.next() {
+            Some("?") => match self.peek() {
+                Some("extends") => {
+                    self.position += 1;
+                    TypeName::extends_wildcard(self.type_name())
+                },
+                Some("super") => {
+                    self.position += 1;
+                    TypeName::super_wildcard(self.type_name())
+                },
+                _ => TypeName::wildcard(),
+            },
+            Some(name) if PRIMITIVES.contains(&name) => TypeName::primitive(name),
+            Some(name) => {
+                let class_name = ClassName::best_guess(name);
+                if self.peek() == Some("<") {
+                    self.position += 1;
+                    let mut arguments = vec![];
+                    while !matches!(self.peek(), Some(">") | None) {
+                        arguments.push(self.type_name());
+                        if self.peek() == Some(",") {
+                            self.position += 1;
+                        }
+                    }
+                    self.position += 1;
+                    TypeName::Parameterized(class_name, arguments)
+                } else {
+                    TypeName::Class(class_name)
+                }
+            },
+            None => TypeName::primitive("void"),
+        };
+        while self.peek() == Some("[") {
+            self.position += 1;
+            if self.peek() == Some("]") {
+                self.position += 1;
+            }
+            type_name = TypeName::array(type_name);
+        }
+        type_name
+    }
+}
@@ -0,0 +1,622 @@
+use std::{collections::HashSet, fmt};
+
+use failure::Fail;
+
+use super::{
+    CodeBlock,
+    JavaCompilationUnit,
+    JavaFeature,
+    JavaMethodDeclaration,
+    JavaMethodParameter,
+    JavaSourceCode,
+    JavaTypeDeclaration,
+    JavaTypeKind,
+    JavaVersion,
+    Modifier,
+    TypeName,
+    TypeParameter,
+};
+use super::java_code_block::CodePart;
+
+/// 关键字以及保留的字面量，不能用作标识符
+const RESERVED_WORDS: [&str; 54] = [
+    "abstract", "assert", "boolean", "break", "byte", "case", "catch", "char", "class", "const",
+    "continue", "default", "do", "double", "else", "enum", "extends", "final", "finally", "float",
+    "for", "goto", "if", "implements", "import", "instanceof", "int", "interface", "long", "native",
+    "new", "package", "private", "protected", "public", "return", "short", "static", "strictfp", "super",
+    "switch", "synchronized", "this", "throw", "throws", "transient", "try", "void", "volatile", "while",
+    "_", "true", "false", "null",
+];
+
+/// 不能用作类型名称的上下文关键字
+const RESTRICTED_TYPE_NAMES: [&str; 5] = ["var", "yield", "record", "sealed", "permits"];
+
+const ACCESS: Modifier = Modifier::PUBLIC.union(Modifier::PROTECTED).union(Modifier::PRIVATE);
+
+/// 校验时区分的元素
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JavaElementKind {
+    Package,
+    CompilationUnit,
+    Type(JavaTypeKind),
+    TypeParameter,
+    EnumConstant,
+    RecordComponent,
+    Field,
+    Method,
+    Constructor,
+    Parameter,
+}
+
+impl fmt::Display for JavaElementKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            JavaElementKind::Package => "package",
+            JavaElementKind::CompilationUnit => "compilation unit",
+            JavaElementKind::Type(JavaTypeKind::Class) => "class",
+            JavaElementKind::Type(JavaTypeKind::Interface) => "interface",
+            JavaElementKind::Type(JavaTypeKind::Enum) => "enum",
+            JavaElementKind::Type(JavaTypeKind::Record) => "record",
+            JavaElementKind::Type(JavaTypeKind::Annotation) => "annotation type",
+            JavaElementKind::Type(JavaTypeKind::Anonymous) => "anonymous class",
+            JavaElementKind::TypeParameter => "type parameter",
+            JavaElementKind::EnumConstant => "enum constant",
+            JavaElementKind::RecordComponent => "record component",
+            JavaElementKind::Field => "field",
+            JavaElementKind::Method => "method",
+            JavaElementKind::Constructor => "constructor",
+            JavaElementKind::Parameter => "parameter",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// 校验发现的问题
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JavaValidationErrorKind {
+    /// 名称不是合法的标识符
+    InvalidIdentifier { element: JavaElementKind, name: String },
+    /// 名称是关键字、字面量或者不能用作类型名称的上下文关键字
+    ReservedWord { element: JavaElementKind, name: String },
+    /// 元素不能使用的修饰符
+    IllegalModifiers { element: JavaElementKind, modifiers: Vec<&'static str> },
+    /// 不能同时使用的修饰符
+    ConflictingModifiers { element: JavaElementKind, modifiers: Vec<&'static str> },
+    /// abstract 方法写在了非 abstract 的类中，或者 abstract 方法有方法体
+    InvalidAbstractMethod { signature: String, reason: &'static str },
+    /// 同名的类型、字段、常量、参数，或者相同签名的方法、构造方法
+    DuplicateMember { element: JavaElementKind, signature: String },
+    /// public 的顶层类型与文件名不一致
+    FileNameMismatch { file_name: String, type_name: String },
+    /// 可变参数不是最后一个参数
+    MisplacedVarargs { name: String },
+    /// 代码块的格式与参数不匹配
+    InvalidCode { message: String },
+    /// 目标版本不支持的语法
+    UnsupportedFeature { feature: JavaFeature, version: JavaVersion },
+    /// 已有文件中的保护区域不完整，或者无法放回新生成的源码
+    ProtectedRegion { message: String },
+}
+
+impl fmt::Display for JavaValidationErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JavaValidationErrorKind::InvalidIdentifier { element, name } => {
+                write!(f, "{} name `{}` is not a valid identifier", element, name)
+            },
+            JavaValidationErrorKind::ReservedWord { element, name } => {
+                write!(f, "{} name `{}` is a reserved word", element, name)
+            },
+            JavaValidationErrorKind::IllegalModifiers { element, modifiers } => {
+                write!(f, "{} cannot be {}", element, modifiers.join(" "))
+            },
+            JavaValidationErrorKind::ConflictingModifiers { element, modifiers } => {
+                write!(f, "{} cannot combine {}", element, modifiers.join(" and "))
+            },
+            JavaValidationErrorKind::InvalidAbstractMethod { signature, reason } => {
+                write!(f, "abstract method `{}` {}", signature, reason)
+            },
+            JavaValidationErrorKind::DuplicateMember { element, signature } => {
+                write!(f, "duplicate {} `{}`", element, signature)
+            },
+            JavaValidationErrorKind::FileNameMismatch { file_name, type_name } => {
+                write!(f, "public type `{}` must be declared in {}.java", type_name, type_name)?;
+                write!(f, ", not {}.java", file_name)
+            },
+            JavaValidationErrorKind::MisplacedVarargs { name } => {
+                write!(f, "varargs parameter `{}` must be the last parameter", name)
+            },
+            JavaValidationErrorKind::InvalidCode { message } | JavaValidationErrorKind::ProtectedRegion { message } => {
+                write!(f, "{}", message)
+            },
+            JavaValidationErrorKind::UnsupportedFeature { feature, version } => {
+                write!(f, "{} require Java {}, but the target is Java {}", feature, feature.since(), version)
+            },
+        }
+    }
+}
+
+/// 校验发现的问题以及位置，location 为包名、类型的全限定名或者 `类型#成员`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JavaValidationError {
+    pub location: String,
+    pub kind: JavaValidationErrorKind,
+}
+
+impl fmt::Display for JavaValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.location, self.kind)
+    }
+}
+
+impl Fail for JavaValidationError {}
+
+impl JavaSourceCode {
+    /// 校验标识符、修饰符的组合、重复的成员以及包名和文件名，返回发现的所有问题
+    pub fn validate(&self) -> Vec<JavaValidationError> {
+        let mut validator = Validator::default();
+        let mut files = HashSet::new();
+        let mut types = HashSet::new();
+        for compilation_unit in &self.compilation_units {
+            let package_name = compilation_unit.package_name.as_str();
+            if !files.insert((package_name, compilation_unit.name.as_str())) {
+                validator.duplicate(package_name, JavaElementKind::CompilationUnit, &compilation_unit.name);
+            }
+            validator.compilation_unit(compilation_unit);
+            // 同一个包中不同文件里的同名类型，同一个文件中的重复由 compilation_unit 报告
+            let mut names = HashSet::new();
+            for type_declaration in &compilation_unit.type_declarations {
+                let name = type_declaration.name.as_str();
+                if names.insert(name) && !types.insert((package_name, name)) {
+                    validator.duplicate(package_name, JavaElementKind::Type(type_declaration.kind), name);
+                }
+            }
+        }
+        validator.errors
+    }
+}
+
+impl JavaCompilationUnit {
+    /// 校验单个编译单元，返回发现的所有问题
+    pub fn validate(&self) -> Vec<JavaValidationError> {
+        let mut validator = Validator::default();
+        validator.compilation_unit(self);
+        validator.errors
+    }
+}
+
+/// 类型所在的位置，决定类型以及成员可以使用的修饰符
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Container {
+    /// 顶层类型
+    CompilationUnit,
+    /// 类、枚举、record 以及匿名类中
+    Class,
+    /// 接口以及注解类型中
+    Interface,
+}
+
+impl Container {
+    fn of(kind: JavaTypeKind) -> Container {
+        match kind {
+            JavaTypeKind::Interface | JavaTypeKind::Annotation => Container::Interface,
+            _ => Container::Class,
+        }
+    }
+}
+
+#[derive(Default)]
+struct Validator {
+    errors: Vec<JavaValidationError>,
+}
+
+impl Validator {
+    fn error(&mut self, location: &str, kind: JavaValidationErrorKind) {
+        self.errors.push(JavaValidationError { location: location.to_owned(), kind });
+    }
+
+    fn duplicate(&mut self, location: &str, element: JavaElementKind, signature: &str) {
+        self.error(location, JavaValidationErrorKind::DuplicateMember { element, signature: signature.to_owned() });
+    }
+
+    fn compilation_unit(&mut self, compilation_unit: &JavaCompilationUnit) {
+        let package_name = compilation_unit.package_name.as_str();
+        if package_name.is_empty() {
+            self.error(package_name, JavaValidationErrorKind::InvalidIdentifier {
+                element: JavaElementKind::Package,
+                name: String::new(),
+            });
+        }
+        for segment in package_name.split('.').filter(|_| !package_name.is_empty()) {
+            self.identifier(package_name, JavaElementKind::Package, segment);
+        }
+        let location = format!("{}.{}", package_name, compilation_unit.name);
+        self.identifier(&location, JavaElementKind::CompilationUnit, &compilation_unit.name);
+
+        let mut names = HashSet::new();
+        for type_declaration in &compilation_unit.type_declarations {
+            if type_declaration.modifiers.contains(Modifier::PUBLIC) && type_declaration.name != compilation_unit.name {
+                self.error(&location, JavaValidationErrorKind::FileNameMismatch {
+                    file_name: compilation_unit.name.clone(),
+                    type_name: type_declaration.name.clone(),
+                });
+            }
+            if !names.insert(type_declaration.name.as_str()) {
+                self.duplicate(package_name, JavaElementKind::Type(type_declaration.kind), &type_declaration.name);
+            }
+            self.type_declaration(package_name, type_declaration, Container::CompilationUnit);
+        }
+    }
+
+    /// 类型以及成员，parent 为包名或者外层类型的位置
+    fn type_declaration(&mut self, parent: &str, type_declaration: &JavaTypeDeclaration, container: Container) {
+        let kind = type_declaration.kind;
+        let element = JavaElementKind::Type(kind);
+        let location = if kind == JavaTypeKind::Anonymous {
+            parent.to_owned()
+        } else {
+            let location = format!("{}.{}", parent, type_declaration.name);
+            self.type_name(&location, element, &type_declaration.name);
+            location
+        };
+        self.type_modifiers(&location, type_declaration, container);
+        self.type_parameters(&location, &type_declaration.type_parameters);
+        let members = Container::of(kind);
+
+        // 枚举常量、record 的组成部分以及字段在同一个命名空间中
+        let mut variables = HashSet::new();
+        for constant in &type_declaration.enum_constants {
+            self.identifier(&location, JavaElementKind::EnumConstant, &constant.name);
+            if !variables.insert(constant.name.as_str()) {
+                self.duplicate(&location, JavaElementKind::EnumConstant, &constant.name);
+            }
+        }
+        for component in &type_declaration.components {
+            self.identifier(&location, JavaElementKind::RecordComponent, &component.name);
+            if !variables.insert(component.name.as_str()) {
+                self.duplicate(&location, JavaElementKind::RecordComponent, &component.name);
+            }
+        }
+        for field in &type_declaration.fields {
+            let field_location = format!("{}#{}", location, field.name);
+            self.identifier(&field_location, JavaElementKind::Field, &field.name);
+            let allowed = match members {
+                Container::Interface => Modifier::PUBLIC | Modifier::STATIC | Modifier::FINAL,
+                _ => ACCESS | Modifier::STATIC | Modifier::FINAL | Modifier::TRANSIENT | Modifier::VOLATILE,
+            };
+            let modifiers = field.modifiers;
+            self.modifiers(&field_location, JavaElementKind::Field, modifiers, allowed);
+            self.conflict(&field_location, JavaElementKind::Field, modifiers, ACCESS);
+            self.conflict(&field_location, JavaElementKind::Field, modifiers, Modifier::FINAL | Modifier::VOLATILE);
+            if !variables.insert(field.name.as_str()) {
+                self.duplicate(&location, JavaElementKind::Field, &field.name);
+            }
+        }
+
+        for initializer in &type_declaration.initializers {
+            self.code(&location, &initializer.code);
+        }
+
+        let mut signatures = HashSet::new();
+        for method in type_declaration.constructors.iter().chain(type_declaration.methods.iter()) {
+            let signature = signature(type_declaration, method);
+            let method_location = format!("{}#{}", location, signature);
+            let element = if method.is_constructor() { JavaElementKind::Constructor } else { JavaElementKind::Method };
+            if !method.is_constructor() {
+                self.identifier(&method_location, element, &method.name);
+            }
+            self.method_modifiers(&method_location, type_declaration, method);
+            self.type_parameters(&method_location, &method.type_parameters);
+            self.parameters(&method_location, &method.parameters);
+            if !signatures.insert(signature.clone()) {
+                self.duplicate(&location, element, &signature);
+            }
+            self.code(&method_location, &method.code);
+        }
+
+        let mut names = HashSet::new();
+        for member in &type_declaration.types {
+            // 成员类型不能与外层类型同名
+            if member.name == type_declaration.name || !names.insert(member.name.as_str()) {
+                self.duplicate(&location, JavaElementKind::Type(member.kind), &member.name);
+            }
+            self.type_declaration(&location, member, members);
+        }
+    }
+
+    fn type_modifiers(&mut self, location: &str, type_declaration: &JavaTypeDeclaration, container: Container) {
+        let kind = type_declaration.kind;
+        let element = JavaElementKind::Type(kind);
+        let mut allowed = match kind {
+            JavaTypeKind::Class => {
+                Modifier::PUBLIC | Modifier::ABSTRACT | Modifier::FINAL | Modifier::SEALED | Modifier::NON_SEALED | Modifier::STRICTFP
+            },
+            JavaTypeKind::Interface => {
+                Modifier::PUBLIC | Modifier::ABSTRACT | Modifier::SEALED | Modifier::NON_SEALED | Modifier::STRICTFP
+            },
+            JavaTypeKind::Annotation => Modifier::PUBLIC | Modifier::ABSTRACT | Modifier::STRICTFP,
+            JavaTypeKind::Enum => Modifier::PUBLIC | Modifier::STRICTFP,
+            JavaTypeKind::Record => Modifier::PUBLIC | Modifier::FINAL | Modifier::STRICTFP,
+            JavaTypeKind::Anonymous => Modifier::new(),
+        };
+        if kind != JavaTypeKind::Anonymous {
+            // 成员类型可以是 static 以及任意的访问级别，接口中的成员类型总是 public
+            allowed |= match container {
+                Container::CompilationUnit => Modifier::new(),
+                Container::Class => ACCESS | Modifier::STATIC,
+                Container::Interface => Modifier::PUBLIC | Modifier::STATIC,
+            };
+        }
+        let modifiers = type_declaration.modifiers;
+        self.modifiers(location, element, modifiers, allowed);
+        self.conflict(location, element, modifiers, ACCESS);
+        self.conflict(location, element, modifiers, Modifier::ABSTRACT | Modifier::FINAL);
+        self.conflict(location, element, modifiers, Modifier::FINAL | Modifier::SEALED | Modifier::NON_SEALED);
+    }
+
+    fn method_modifiers(&mut self, location: &str, type_declaration: &JavaTypeDeclaration, method: &JavaMethodDeclaration) {
+        let kind = type_declaration.kind;
+        let modifiers = method.modifiers;
+        let (element, allowed) = if method.is_constructor() {
+            // 枚举的构造方法只能是 private
+            let allowed = if kind == JavaTypeKind::Enum { Modifier::PRIVATE } else { ACCESS };
+            (JavaElementKind::Constructor, allowed)
+        } else {
+            let allowed = match kind {
+                JavaTypeKind::Annotation => Modifier::PUBLIC | Modifier::ABSTRACT,
+                JavaTypeKind::Interface => {
+                    Modifier::PUBLIC | Modifier::PRIVATE | Modifier::ABSTRACT | Modifier::STATIC | Modifier::STRICTFP
+                },
+                _ => {
+                    ACCESS | Modifier::ABSTRACT | Modifier::STATIC | Modifier::FINAL
+                        | Modifier::SYNCHRONIZED | Modifier::NATIVE | Modifier::STRICTFP
+                },
+            };
+            (JavaElementKind::Method, allowed)
+        };
+        self.modifiers(location, element, modifiers, allowed);
+        self.conflict(location, element, modifiers, ACCESS);
+        if modifiers.contains(Modifier::ABSTRACT) {
+            let others = Modifier::PRIVATE | Modifier::STATIC | Modifier::FINAL
+                | Modifier::SYNCHRONIZED | Modifier::NATIVE | Modifier::STRICTFP;
+            if modifiers.intersects(others) {
+                self.error(location, JavaValidationErrorKind::ConflictingModifiers {
+                    element,
+                    modifiers: modifiers.intersection(Modifier::ABSTRACT | others).names(),
+                });
+            }
+        }
+
+        if method.is_constructor() || !method.is_abstract_in(kind) {
+            return;
+        }
+        let signature = signature(type_declaration, method);
+        if !method.code.is_empty() || method.constructor_call.is_some() {
+            self.error(location, JavaValidationErrorKind::InvalidAbstractMethod { signature, reason: "cannot have a body" });
+        } else if matches!(kind, JavaTypeKind::Class | JavaTypeKind::Anonymous | JavaTypeKind::Enum | JavaTypeKind::Record)
+            && !type_declaration.modifiers.is_abstract() {
+            self.error(location, JavaValidationErrorKind::InvalidAbstractMethod {
+                signature,
+                reason: "must be declared in an abstract class",
+            });
+        }
+    }
+
+    fn parameters(&mut self, location: &str, parameters: &[JavaMethodParameter]) {
+        let mut names = HashSet::new();
+        for parameter in parameters {
+            self.identifier(location, JavaElementKind::Parameter, &parameter.name);
+            self.modifiers(location, JavaElementKind::Parameter, parameter.modifiers, Modifier::FINAL);
+            if !names.insert(parameter.name.as_str()) {
+                self.duplicate(location, JavaElementKind::Parameter, &parameter.name);
+            }
+        }
+        if let Some(parameter) = parameters.iter().rev().skip(1).find(|p| p.varargs) {
+            self.error(location, JavaValidationErrorKind::MisplacedVarargs { name: parameter.name.clone() });
+        }
+    }
+
+    fn type_parameters(&mut self, location: &str, type_parameters: &[TypeParameter]) {
+        let mut names = HashSet::new();
+        for type_parameter in type_parameters {
+            self.type_name(location, JavaElementKind::TypeParameter, &type_parameter.name);
+            if !names.insert(type_parameter.name.as_str()) {
+                self.duplicate(location, JavaElementKind::TypeParameter, &type_parameter.name);
+            }
+        }
+    }
+
+    /// 代码块中的匿名类以及格式错误
+    fn code(&mut self, location: &str, code: &CodeBlock) {
+        for part in &code.parts {
+            match part {
+                CodePart::Class(anonymous_class) => self.type_declaration(location, anonymous_class, Container::Class),
+                CodePart::Invalid(message) => self.error(location, JavaValidationErrorKind::InvalidCode { message: message.clone() }),
+                _ => {},
+            }
+        }
+    }
+
+    fn modifiers(&mut self, location: &str, element: JavaElementKind, modifiers: Modifier, allowed: Modifier) {
+        let illegal = modifiers.difference(allowed);
+        if !illegal.is_empty() {
+            self.error(location, JavaValidationErrorKind::IllegalModifiers { element, modifiers: illegal.names() });
+        }
+    }
+
+    /// mask 中的修饰符最多只能使用一个
+    fn conflict(&mut self, location: &str, element: JavaElementKind, modifiers: Modifier, mask: Modifier) {
+        if modifiers.intersection(mask).len() > 1 {
+            self.error(location, JavaValidationErrorKind::ConflictingModifiers { element, modifiers: modifiers.intersection(mask).names() });
+        }
+    }
+
+    fn identifier(&mut self, location: &str, element: JavaElementKind, name: &str) {
+        if RESERVED_WORDS.contains(&name) {
+            self.error(location, JavaValidationErrorKind::ReservedWord { element, name: name.to_owned() });
+        } else if !is_identifier(name) {
+            self.error(location, JavaValidationErrorKind::InvalidIdentifier { element, name: name.to_owned() });
+        }
+    }
+
+    /// 类型名称还不能是 var、record 等上下文关键字
+    fn type_name(&mut self, location: &str, element: JavaElementKind, name: &str) {
+        if RESTRICTED_TYPE_NAMES.contains(&name) {
+            self.error(location, JavaValidationErrorKind::ReservedWord { element, name: name.to_owned() });
+        } else {
+            self.identifier(location, element, name);
+        }
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+}
+
+/// 方法签名，参数使用擦除后的类型，如 `put(java.lang.Object, java.util.List)`
+fn signature(type_declaration: &JavaTypeDeclaration, method: &JavaMethodDeclaration) -> String {
+    let name = if method.is_constructor() { type_declaration.name.as_str() } else { method.name.as_str() };
+    let parameters: Vec<String> = method.parameters.iter().map(|parameter| {
+        let erasure = erasure(&parameter.param_type);
+        match (&parameter.param_type, parameter.varargs) {
+            (TypeName::Array(_), true) | (_, false) => erasure,
+            (_, true) => format!("{}[]", erasure),
+        }
+    }).collect();
+    format!("{}({})", name, parameters.join(", "))
+}
+
+fn erasure(type_name: &TypeName) -> String {
+    match type_name {
+        TypeName::Parameterized(raw_type, _) => raw_type.canonical_name(),
+        TypeName::Array(component) => format!("{}[]", erasure(component)),
+        TypeName::Wildcard(_) => "java.lang.Object".to_string(),
+        type_name => type_name.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use project_gen_core::PGError;
+
+    use super::*;
+    use crate::{JavaFieldDeclaration, JavaSourceCodeWriter, JavaSourceStructure};
+
+    fn unit(package_name: &str, type_declaration: JavaTypeDeclaration) -> JavaCompilationUnit {
+        let mut compilation_unit = JavaCompilationUnit::new(package_name, &type_declaration.name.clone());
+        compilation_unit.add_type_declaration(type_declaration);
+        compilation_unit
+    }
+
+    fn kinds(compilation_unit: &JavaCompilationUnit) -> Vec<JavaValidationErrorKind> {
+        compilation_unit.validate().into_iter().map(|e| e.kind).collect()
+    }
+
+    fn put(element_type: &str) -> JavaMethodDeclaration {
+        let mut method = JavaMethodDeclaration::new("put", "void", Modifier::PUBLIC);
+        method.add_parameter(JavaMethodParameter::new("values", element_type));
+        method
+    }
+
+    #[test]
+    fn valid_unit_has_no_errors() {
+        let mut person = JavaTypeDeclaration::new(Modifier::PUBLIC | Modifier::FINAL, "Person", None);
+        person.add_field(JavaFieldDeclaration::new("name", "java.lang.String", Modifier::PRIVATE, None));
+        person.add_method(put("java.util.List<java.lang.String>"));
+        assert!(unit("com.example", person).validate().is_empty());
+    }
+
+    #[test]
+    fn abstract_final_class() {
+        let person = JavaTypeDeclaration::new(Modifier::PUBLIC | Modifier::ABSTRACT | Modifier::FINAL, "Person", None);
+        let errors = unit("com.example", person).validate();
+        assert_eq!(errors, vec![JavaValidationError {
+            location: "com.example.Person".to_string(),
+            kind: JavaValidationErrorKind::ConflictingModifiers {
+                element: JavaElementKind::Type(JavaTypeKind::Class),
+                modifiers: vec!["abstract", "final"],
+            },
+        }]);
+        assert_eq!(errors[0].to_string(), "com.example.Person: class cannot combine abstract and final");
+    }
+
+    #[test]
+    fn field_named_class() {
+        let mut person = JavaTypeDeclaration::new(Modifier::PUBLIC, "Person", None);
+        person.add_field(JavaFieldDeclaration::new("class", "java.lang.String", Modifier::PRIVATE, None));
+        assert_eq!(kinds(&unit("com.example", person)), vec![JavaValidationErrorKind::ReservedWord {
+            element: JavaElementKind::Field,
+            name: "class".to_string(),
+        }]);
+    }
+
+    #[test]
+    fn duplicate_method_signature_after_erasure() {
+        let mut person = JavaTypeDeclaration::new(Modifier::PUBLIC, "Person", None);
+        person.add_method(put("java.util.List<java.lang.String>"));
+        person.add_method(put("java.util.List<java.lang.Integer>"));
+        person.add_method(put("java.util.Set<java.lang.String>"));
+        assert_eq!(kinds(&unit("com.example", person)), vec![JavaValidationErrorKind::DuplicateMember {
+            element: JavaElementKind::Method,
+            signature: "put(java.util.List)".to_string(),
+        }]);
+    }
+
+    #[test]
+    fn invalid_package_name() {
+        let person = || JavaTypeDeclaration::new(Modifier::PUBLIC, "Person", None);
+        assert_eq!(kinds(&unit("com.1example", person())), vec![JavaValidationErrorKind::InvalidIdentifier {
+            element: JavaElementKind::Package,
+            name: "1example".to_string(),
+        }]);
+        assert_eq!(kinds(&unit("com.example.int", person())), vec![JavaValidationErrorKind::ReservedWord {
+            element: JavaElementKind::Package,
+            name: "int".to_string(),
+        }]);
+        assert_eq!(kinds(&unit("", person())), vec![JavaValidationErrorKind::InvalidIdentifier {
+            element: JavaElementKind::Package,
+            name: String::new(),
+        }]);
+    }
+
+    #[test]
+    fn misplaced_varargs_and_invalid_code() {
+        let mut method = JavaMethodDeclaration::new("log", "void", Modifier::PUBLIC);
+        let mut args = JavaMethodParameter::new("args", "java.lang.Object");
+        args.set_varargs(true);
+        method.add_parameter(args);
+        method.add_parameter(JavaMethodParameter::new("message", "java.lang.String"));
+        method.add_code(CodeBlock::of("$S", vec![]));
+        let mut logger = JavaTypeDeclaration::new(Modifier::PUBLIC, "Logger", None);
+        logger.add_method(method);
+        assert_eq!(kinds(&unit("com.example", logger)), vec![
+            JavaValidationErrorKind::MisplacedVarargs { name: "args".to_string() },
+            JavaValidationErrorKind::InvalidCode { message: "missing argument for $S in `$S`".to_string() },
+        ]);
+    }
+
+    #[test]
+    fn all_errors_are_returned_together() {
+        let mut source_code = JavaSourceCode::new();
+        source_code.add_compilation_unit(unit("com.example", JavaTypeDeclaration::new(Modifier::ABSTRACT | Modifier::FINAL, "Person", None)));
+        let mut card = JavaTypeDeclaration::new(Modifier::PUBLIC, "Card", None);
+        card.add_field(JavaFieldDeclaration::new("class", "int", Modifier::PRIVATE, None));
+        source_code.add_compilation_unit(unit("com.1example", card));
+        source_code.add_compilation_unit(unit("com.example", JavaTypeDeclaration::new(Modifier::new(), "Person", None)));
+
+        // 校验失败时不会创建任何文件
+        let root = std::env::temp_dir().join(format!("pg-validation-{}", std::process::id()));
+        let result = JavaSourceCodeWriter::new().write(&JavaSourceStructure::new(root.clone()), source_code);
+        assert!(!root.exists());
+        let errors = match result {
+            Err(PGError::Validation(errors)) => errors,
+            other => panic!("expected validation errors, got {:?}", other.err()),
+        };
+        let locations: Vec<&str> = errors.downcast::<JavaValidationError>().map(|e| e.location.as_str()).collect();
+        assert_eq!(locations, vec!["com.example.Person", "com.1example", "com.1example.Card#class", "com.example", "com.example"]);
+        assert_eq!(errors.len(), 5);
+        assert_eq!(errors.to_string().lines().count(), 5);
+    }
+}
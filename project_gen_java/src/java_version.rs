@@ -0,0 +1,88 @@
+use std::fmt;
+
+/// 生成的代码面向的java版本
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum JavaVersion {
+    #[default]
+    Java8,
+    Java11,
+    Java17,
+    Java21,
+}
+
+impl JavaVersion {
+    /// 版本号，如 8、17
+    pub fn feature(&self) -> u32 {
+        match self {
+            JavaVersion::Java8 => 8,
+            JavaVersion::Java11 => 11,
+            JavaVersion::Java17 => 17,
+            JavaVersion::Java21 => 21,
+        }
+    }
+
+    /// `maven.compiler.release` 的值
+    pub fn release(&self) -> String {
+        self.feature().to_string()
+    }
+
+    pub fn supports(&self, feature: JavaFeature) -> bool {
+        self.feature() >= feature.since()
+    }
+
+    /// 解析 `1.8`、`8`、`17` 这样的版本号
+    pub fn parse(version: &str) -> Option<JavaVersion> {
+        match version.trim() {
+            "1.8" | "8" => Some(JavaVersion::Java8),
+            "11" => Some(JavaVersion::Java11),
+            "17" => Some(JavaVersion::Java17),
+            "21" => Some(JavaVersion::Java21),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for JavaVersion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JavaVersion::Java8 => write!(f, "1.8"),
+            _ => write!(f, "{}", self.feature()),
+        }
+    }
+}
+
+/// 需要较新的java版本才能使用的语法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum JavaFeature {
+    /// switch 表达式以及 `case A ->`
+    SwitchExpressions,
+    /// 文本块 `"""`
+    TextBlocks,
+    Records,
+    /// sealed、non-sealed 以及 permits
+    SealedTypes,
+}
+
+impl JavaFeature {
+    /// 正式支持的版本
+    pub fn since(&self) -> u32 {
+        match self {
+            JavaFeature::SwitchExpressions => 14,
+            JavaFeature::TextBlocks => 15,
+            JavaFeature::Records => 16,
+            JavaFeature::SealedTypes => 17,
+        }
+    }
+}
+
+impl fmt::Display for JavaFeature {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            JavaFeature::SwitchExpressions => "switch expressions",
+            JavaFeature::TextBlocks => "text blocks",
+            JavaFeature::Records => "records",
+            JavaFeature::SealedTypes => "sealed types",
+        };
+        write!(f, "{}", name)
+    }
+}
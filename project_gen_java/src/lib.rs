@@ -2,27 +2,80 @@ mod java_pom;
 mod java_pom_writer;
 mod java_source_code;
 mod java_source_writer;
+mod java_type_name;
+mod java_import;
+mod java_code_block;
+mod java_javadoc;
+mod java_version;
+mod java_format;
+mod java_protected_region;
+mod java_validation;
+mod java_bean;
+mod java_data_generator;
 
 pub use java_pom::{
     Property,
     Exclusion,
     Build,
     Plugin,
+    Configuration,
     Dependency,
     POM
 };
 pub use java_source_code::{
     JavaSourceCode,
+    JavaLanguage,
     JavaAnnotationDeclaration,
     JavaCompilationUnit,
     JavaFieldDeclaration,
     JavaSourceStructure,
     JavaMethodDeclaration,
     JavaTypeDeclaration,
+    JavaTypeKind,
+    JavaEnumConstant,
+    JavaInitializerBlock,
+    Modifier,
     JavaMethodParameter,
     JavaMethodStatement,
     JavaAnnotationAttribute,
+    AnnotationValue,
     ValueType
 };
+pub use java_type_name::{
+    ClassName,
+    TypeName,
+    TypeParameter,
+    Wildcard
+};
+pub use java_code_block::{
+    CodeArg,
+    CodeBlock
+};
+pub use java_javadoc::Javadoc;
+pub use java_version::{
+    JavaFeature,
+    JavaVersion
+};
+pub use java_bean::BeanOptions;
+pub use java_format::{
+    BraceStyle,
+    FormatOptions,
+    IndentStyle
+};
+pub use java_protected_region::{
+    ProtectedRegions,
+    REGION_BEGIN,
+    REGION_END
+};
+pub use java_validation::{
+    JavaElementKind,
+    JavaValidationError,
+    JavaValidationErrorKind
+};
 pub use java_pom_writer::POMWriter;
-pub use java_source_writer::JavaSourceCodeWriter;
\ No newline at end of file
+pub use java_source_writer::JavaSourceCodeWriter;
+pub use java_data_generator::{
+    JavaCodeStyle,
+    RegenerationMode,
+    JavaDataGenerator
+};
\ No newline at end of file
@@ -0,0 +1,352 @@
+use serde::{
+    Deserialize,
+    Deserializer,
+    Serialize,
+    Serializer,
+    de::{MapAccess, Visitor},
+    ser::SerializeMap,
+};
+use project_gen_core::{
+    Result,
+    diagnostic::{Diagnostic, Diagnostics, ErrorCode, Span},
+};
+use super::{
+    common::DataType,
+    data::{DataDefinition, TypeList, similar_name},
+    query::{self, Operator, QueryFieldKind},
+    source::YamlSource,
+};
+use std::{fmt, fs, collections::HashSet};
+
+/// 定义接口，接口中引用的实体来自 data.yaml
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiDefinition {
+    pub apis: Vec<Api>,
+}
+
+/// 定义接口
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Api {
+    pub name: String,
+    #[serde(rename = "baseUrl")]
+    pub base_url: Option<String>,
+    pub requests: Vec<ApiRequest>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HttpMethod {
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+}
+
+/// 定义接口请求
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiRequest {
+    pub name: String,
+    pub method: HttpMethod,
+    pub path: Option<String>,
+    pub params: Option<Vec<ApiRequestParameter>>,
+    pub response: Option<ApiType>,
+    /// 按页返回，请求中增加 page 和 size
+    #[serde(default)]
+    pub paged: bool,
+    /// 允许排序的字段，请求中增加 sort，格式为 `field[,asc|desc]`
+    #[serde(default)]
+    pub sortable: Vec<String>,
+    /// 允许过滤的字段以及过滤条件
+    #[serde(default)]
+    pub filterable: Filterable,
+}
+
+/// 列表请求的过滤字段，如 `{name: [eq, like], age: [between]}`，保持 yaml 中的顺序
+#[derive(Debug, Clone, Default)]
+pub struct Filterable(pub Vec<(String, Vec<Operator>)>);
+
+impl Serialize for Filterable {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for (field, ops) in &self.0 {
+            map.serialize_entry(field, ops)?;
+        }
+        map.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for Filterable {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        struct FilterableVisitor;
+
+        impl<'de> Visitor<'de> for FilterableVisitor {
+            type Value = Filterable;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map from field name to a list of conditions")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> std::result::Result<Filterable, A::Error> {
+                let mut filters = vec![];
+                while let Some(entry) = map.next_entry()? {
+                    filters.push(entry);
+                }
+                Ok(Filterable(filters))
+            }
+        }
+
+        deserializer.deserialize_map(FilterableVisitor)
+    }
+}
+
+/// 定义接口请求参数
+/// 指定 pathVariable 的为路径参数，实体和列表为请求体，其他为查询参数
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiRequestParameter {
+    pub name: Option<String>,
+    #[serde(rename = "pathVariable")]
+    pub path_variable: Option<String>,
+    #[serde(flatten)]
+    pub value_type: ApiType,
+}
+
+/// 参数或者返回值的类型
+/// type、ref、list 三个同时只能出现一个，`type: ref` 和 `type: list` 可以和 ref、list 同时出现
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ApiType {
+    #[serde(rename = "type")]
+    pub data_type: Option<DataType>,
+    #[serde(rename = "ref")]
+    pub reference: Option<String>,
+    pub list: Option<TypeList>,
+}
+
+impl ApiRequestParameter {
+    /// 参数名称，路径参数默认使用路径变量名
+    pub fn param_name(&self) -> &str {
+        self.name
+            .as_deref()
+            .or(self.path_variable.as_deref())
+            .unwrap_or_default()
+    }
+}
+
+impl ApiType {
+    /// 引用的实体名称
+    pub(crate) fn references(&self) -> Vec<&str> {
+        self.reference
+            .iter()
+            .chain(self.list.as_ref().and_then(|l| l.ref_type.as_ref()))
+            .map(|s| s.as_str())
+            .collect()
+    }
+
+    pub(crate) fn is_valid(&self) -> bool {
+        match (&self.data_type, &self.reference, &self.list) {
+            (None | Some(DataType::Ref), Some(_), None) => true,
+            (None | Some(DataType::List), None, Some(list)) => match list.list_type {
+                DataType::List => false,
+                DataType::Ref => list.ref_type.is_some(),
+                _ => true,
+            },
+            (Some(data_type), None, None) => !matches!(data_type, DataType::Ref | DataType::List),
+            _ => false,
+        }
+    }
+}
+
+impl ApiRequest {
+    /// 声明了分页、排序或者过滤的列表请求
+    pub fn is_listing(&self) -> bool {
+        self.paged || !self.sortable.is_empty() || !self.filterable.0.is_empty()
+    }
+
+    /// 列表请求返回的实体名称
+    pub fn listed_entity(&self) -> Option<&str> {
+        let list = self.response.as_ref()?.list.as_ref()?;
+        match list.list_type {
+            DataType::Ref => list.ref_type.as_deref(),
+            _ => None,
+        }
+    }
+
+    /// 路径中的变量，如 `/{id}/cards` 中的 id
+    pub fn path_variables(&self) -> Vec<&str> {
+        let mut variables = vec![];
+        let mut rest = self.path.as_deref().unwrap_or("");
+        while let Some(start) = rest.find('{') {
+            match rest[start..].find('}') {
+                Some(end) => {
+                    variables.push(&rest[start + 1..start + end]);
+                    rest = &rest[start + end..];
+                },
+                None => break,
+            }
+        }
+        variables
+    }
+}
+
+impl ApiDefinition {
+    /// 解析 api.yaml，接口中引用的实体必须在 data 中定义
+    pub fn parse(yaml_path: &str, data: &DataDefinition) -> Result<Self> {
+        let source = YamlSource::new(yaml_path, fs::read_to_string(yaml_path)?);
+        let definition: ApiDefinition = match serde_yaml::from_str(&source.text) {
+            Ok(definition) => definition,
+            Err(e) => {
+                let span = e.location().map(|l| source.word_at(l.index()));
+                let mut diagnostics = Diagnostics::new();
+                diagnostics.add_source(&source.path, &source.text);
+                diagnostics.push(Diagnostic::new(ErrorCode::INVALID_YAML, e.to_string())
+                    .with_label(source.label(span, "")));
+                return Err(diagnostics.into());
+            }
+        };
+        definition.check(&source, data)?;
+        Ok(definition)
+    }
+
+    fn check(&self, source: &YamlSource, data: &DataDefinition) -> Result<()> {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.add_source(&source.path, &source.text);
+
+        let mut cursor = 0;
+        let mut api_names = HashSet::new();
+        for api in &self.apis {
+            let api_span = source.find("name", &api.name, cursor);
+            cursor = api_span.map(|s| s.end).unwrap_or(cursor);
+            if !api_names.insert(api.name.as_str()) {
+                diagnostics.push(Diagnostic::new(ErrorCode::DUPLICATE_API, format!("duplicate api name `{}`", api.name))
+                    .with_label(source.label(api_span, "redefined here"))
+                    .with_help("api names must be unique".to_string()));
+            }
+
+            let mut request_names = HashSet::new();
+            for request in &api.requests {
+                let request_span = source.find("name", &request.name, cursor);
+                cursor = request_span.map(|s| s.end).unwrap_or(cursor);
+                if !request_names.insert(request.name.as_str()) {
+                    diagnostics.push(Diagnostic::new(ErrorCode::DUPLICATE_API, format!("duplicate request `{}.{}`", api.name, request.name))
+                        .with_label(source.label(request_span, "redefined here"))
+                        .with_help("request names must be unique within an api".to_string()));
+                }
+
+                let path_variables = request.path_variables();
+                let mut types = vec![];
+                for param in request.params.iter().flatten() {
+                    let key = if param.name.is_some() { "name" } else { "pathVariable" };
+                    let param_span = source.find(key, param.param_name(), cursor);
+                    cursor = param_span.map(|s| s.end).unwrap_or(cursor);
+                    if let Some(variable) = param.path_variable.as_ref() {
+                        if !path_variables.contains(&variable.as_str()) {
+                            let span = source.find("pathVariable", variable, param_span.map(|s| s.start).unwrap_or(cursor));
+                            diagnostics.push(Diagnostic::new(ErrorCode::UNKNOWN_PATH_VARIABLE, format!("path variable `{}` is not part of the path of `{}.{}`", variable, api.name, request.name))
+                                .with_label(source.label(span, "not found in path"))
+                                .with_help(format!("add `{{{}}}` to the request path", variable)));
+                        }
+                    }
+                    types.push((format!("parameter `{}`", param.param_name()), &param.value_type, param_span));
+                }
+                if let Some(response) = request.response.as_ref() {
+                    let span = request_span.and_then(|s| source.find("response", "", s.end));
+                    types.push(("response".to_string(), response, span));
+                }
+
+                for (what, value_type, span) in types {
+                    if !value_type.is_valid() {
+                        diagnostics.push(Diagnostic::new(ErrorCode::INVALID_TYPE, format!("{} of `{}.{}` has an invalid type", what, api.name, request.name))
+                            .with_label(source.label(span, ""))
+                            .with_help("declare exactly one of `type`, `ref` or `list`".to_string()));
+                    }
+                    for reference in value_type.references() {
+                        if !data.entity_ref.contains_key(reference) {
+                            let ref_span = span.and_then(|s| source.find("ref", reference, s.start));
+                            diagnostics.push(Diagnostic::new(ErrorCode::UNKNOWN_REF, format!("{} of `{}.{}` refers to unknown entity `{}`", what, api.name, request.name, reference))
+                                .with_label(source.label(ref_span, "unknown entity"))
+                                .with_help(format!("declare entity `{}` in data.yaml or fix the reference", reference)));
+                        }
+                    }
+                }
+
+                if request.is_listing() {
+                    check_listing(api, request, request_span, source, data, &mut diagnostics);
+                }
+            }
+        }
+
+        diagnostics.into_result(())
+    }
+}
+
+/// 列表请求中分页和排序使用的参数
+const LISTING_PARAMS: [&str; 3] = ["page", "size", "sort"];
+
+/// 排序和过滤的字段必须是返回的实体的字段，过滤条件必须适用于字段的类型
+fn check_listing(api: &Api, request: &ApiRequest, span: Option<Span>, source: &YamlSource, data: &DataDefinition, diagnostics: &mut Diagnostics) {
+    let entity_name = match request.listed_entity() {
+        Some(entity_name) => entity_name,
+        None => {
+            diagnostics.push(Diagnostic::new(ErrorCode::INVALID_LISTING, format!("`{}.{}` declares paging, sorting or filtering but does not return a list of entities", api.name, request.name))
+                .with_label(source.label(span, "request declared here"))
+                .with_help("declare the response as `list: {type: ref, ref: <entity>}`".to_string()));
+            return;
+        },
+    };
+    // 未知的实体已经在检查返回值时报告
+    let entity = match data.entity_ref.get(entity_name) {
+        Some(entity) => entity,
+        None => return,
+    };
+    let fields = entity.query_fields();
+    let from = span.map(|s| s.end).unwrap_or(0);
+    let unknown = |field: &str, key: &str, field_span: Option<Span>| {
+        let help = match similar_name(field, fields.iter().map(|f| f.name)) {
+            Some(similar) => format!("did you mean `{}`?", similar),
+            None => format!("`{}` has no field `{}`", entity_name, field),
+        };
+        Diagnostic::new(ErrorCode::UNKNOWN_LISTING_FIELD, format!("`{}.{}` declares unknown {} field `{}`", api.name, request.name, key, field))
+            .with_label(source.label(field_span, "unknown field"))
+            .with_help(help)
+    };
+
+    let sortable_span = span.and_then(|_| source.find_key("sortable", from));
+    for field_name in &request.sortable {
+        match fields.iter().find(|f| f.name == field_name) {
+            None => diagnostics.push(unknown(field_name, "sortable", sortable_span)),
+            Some(field) if matches!(field.kind, QueryFieldKind::Ref | QueryFieldKind::List) => {
+                diagnostics.push(Diagnostic::new(ErrorCode::INVALID_LISTING, format!("`{}.{}` cannot sort by field `{}`", api.name, request.name, field_name))
+                    .with_label(source.label(sortable_span, "declared here"))
+                    .with_help("only scalar fields can be sorted".to_string()));
+            },
+            Some(_) => {},
+        }
+    }
+
+    let filterable_span = span.and_then(|_| source.find_key("filterable", from));
+    for (field_name, ops) in &request.filterable.0 {
+        let field_span = filterable_span.and_then(|s| source.find_key(field_name, s.end));
+        let field = match fields.iter().find(|f| f.name == field_name) {
+            Some(field) => field,
+            None => {
+                diagnostics.push(unknown(field_name, "filterable", field_span));
+                continue;
+            },
+        };
+        for op in ops {
+            let reserved = op.filter_params(field_name).into_iter().find(|p| LISTING_PARAMS.contains(&p.as_str()));
+            if let Some(param) = reserved {
+                diagnostics.push(Diagnostic::new(ErrorCode::INVALID_LISTING, format!("filter parameter `{}` of `{}.{}` conflicts with the paging parameters", param, api.name, request.name))
+                    .with_label(source.label(field_span, "filter declared here"))
+                    .with_help(format!("`{}` are reserved in list requests, filter with another condition", LISTING_PARAMS.join("`, `"))));
+            }
+            if query::check_operator(field, *op).is_ok() {
+                continue;
+            }
+            let op = op.name();
+            diagnostics.push(Diagnostic::new(ErrorCode::INVALID_LISTING, format!("`{}.{}` cannot filter field `{}` with `{}`", api.name, request.name, field_name, op))
+                .with_label(source.label(field_span, "filter declared here"))
+                .with_help("text matching needs a string field, `isTrue`/`isFalse` a bool field, list fields cannot be filtered".to_string()));
+        }
+    }
+}
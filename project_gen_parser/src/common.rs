@@ -17,3 +17,41 @@ pub enum DataType {
     List,
     Ref
 }
+
+/// 实体或字段的说明文字
+/// label 为简短的名称，用于表单标签；description 为详细说明，用于文档；
+/// comment 为数据库注释，未指定时使用 label 或 description
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct Documentation {
+    pub label: Option<String>,
+    pub description: Option<String>,
+    pub comment: Option<String>,
+}
+
+impl Documentation {
+    /// 文档注释的文字，label 作为摘要，description 作为正文
+    pub fn doc(&self) -> Option<String> {
+        match (self.label.as_ref(), self.description.as_ref()) {
+            (Some(label), Some(description)) => Some(format!("{}\n\n{}", label, description)),
+            (Some(text), None) | (None, Some(text)) => Some(text.clone()),
+            (None, None) => self.comment.clone(),
+        }
+    }
+
+    /// 数据库注释
+    pub fn db_comment(&self) -> Option<&str> {
+        self.comment
+            .as_deref()
+            .or(self.label.as_deref())
+            .or(self.description.as_deref())
+    }
+
+    /// 表单标签，未指定 label 时依次使用 comment 以及 default
+    pub fn label_or<'a>(&'a self, default: &'a str) -> &'a str {
+        self.label.as_deref().or(self.comment.as_deref()).unwrap_or(default)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.label.is_none() && self.description.is_none() && self.comment.is_none()
+    }
+}
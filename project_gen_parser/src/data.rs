@@ -1,17 +1,42 @@
 use serde::{Deserialize, Serialize};
-use project_gen_core::Result;
-use super::common::DataType;
-use std::{fs::File, io::BufReader, collections::HashMap};
+use project_gen_core::{
+    Result,
+    naming,
+    diagnostic::{Diagnostic, Diagnostics, ErrorCode, Span},
+};
+use super::{
+    common::{DataType, Documentation},
+    dto::{self, Dto},
+    model::FieldRole,
+    query::{Query, QueryError, QueryField, QueryFieldKind},
+    seed::{self, SeedRow},
+    source::YamlSource,
+};
+use std::{fs, collections::HashMap};
 
 #[derive(Debug, Serialize, Deserialize,Clone)]
 pub struct DataDefinition {
     pub datasources: Vec<DataSource>,
+    /// 从实体派生的 DTO
+    #[serde(default)]
+    pub dtos: Vec<Dto>,
     #[serde(skip)]
     pub entity_ref: HashMap<String, Entity>,
+    /// 实体名称 -> 所属数据源名称
+    #[serde(skip)]
+    pub entity_datasource: HashMap<String, String>,
 }
 
 #[derive(Debug, Serialize, Deserialize,Clone)]
 pub struct DataSource {
+    /// 数据源名称，用于生成子包名、bean名称以及配置前缀
+    #[serde(default = "default_name")]
+    pub name: String,
+    /// 子包名，默认使用数据源名称
+    pub package: Option<String>,
+    /// 是否为主数据源，未指定时第一个数据源为主数据源
+    #[serde(default)]
+    pub primary: bool,
     #[serde(default = "default_host")]
     pub host: String,
     #[serde(default = "default_port")]
@@ -26,18 +51,38 @@ pub struct DataSource {
 pub struct Entity {
     pub name: String,
     pub table: String,
-    pub fields: Vec<Field>
+    #[serde(flatten)]
+    pub documentation: Documentation,
+    pub fields: Vec<Field>,
+    /// 逻辑删除，自动添加 deleted 字段，删除时只更新该字段
+    #[serde(default, rename = "softDelete")]
+    pub soft_delete: bool,
+    /// 自动添加创建、修改的时间以及操作人
+    #[serde(default)]
+    pub audited: bool,
+    /// 乐观锁，自动添加 version 字段
+    #[serde(default)]
+    pub versioned: bool,
+    /// 查询方法
+    #[serde(default)]
+    pub queries: Vec<Query>,
+    /// 初始化数据，每行为 `字段名: 值`，未指定主键时按行号从 1 开始编号
+    #[serde(default)]
+    pub seed: Vec<serde_yaml::Mapping>,
 }
 
 #[derive(Debug, Serialize, Deserialize,Clone)]
 pub struct Field {
     pub name: String,
-    pub column: String,
+    /// 列名，默认使用字段名的 snake_case 形式，ref 字段默认为 `<字段名>_id`
+    pub column: Option<String>,
     #[serde(rename = "type")]
     pub field_type: DataType,
     #[serde(rename = "ref")]
     pub reference: Option<String>,
-    pub list: Option<TypeList>
+    pub list: Option<TypeList>,
+    #[serde(flatten)]
+    pub documentation: Documentation,
 }
 
 #[derive(Debug, Serialize, Deserialize,Clone)]
@@ -56,26 +101,484 @@ fn default_host() -> String {
     "127.0.0.1".to_string()
 }
 
+fn default_name() -> String {
+    "primary".to_string()
+}
+
+impl DataSource {
+    /// 数据源对应的子包名
+    pub fn package_name(&self) -> String {
+        match self.package.as_ref() {
+            Some(package) => package.clone(),
+            None => naming::snake_case(&self.name).replace('_', ""),
+        }
+    }
+}
+
+impl Field {
+    /// ref 类型必须指定 ref，list 类型必须指定 list
+    fn check(&self, entity: &str, source: &YamlSource, at: Option<usize>, diagnostics: &mut Diagnostics) {
+        let type_span = at.and_then(|at| source.find("type", &data_type_name(&self.field_type), at));
+        match self.field_type {
+            DataType::Ref if self.reference.is_none() => {
+                diagnostics.push(Diagnostic::new(ErrorCode::MISSING_REF, format!("field `{}.{}` of type `ref` requires `ref`", entity, self.name))
+                    .with_label(source.label(type_span, "declared as `ref` here"))
+                    .with_help("add `ref: <entity name>` to the field".to_string()));
+            },
+            DataType::List => match self.list.as_ref() {
+                None => {
+                    diagnostics.push(Diagnostic::new(ErrorCode::MISSING_LIST, format!("field `{}.{}` of type `list` requires `list`", entity, self.name))
+                        .with_label(source.label(type_span, "declared as `list` here"))
+                        .with_help("add a `list:` block with the element `type` to the field".to_string()));
+                },
+                Some(list) => match list.list_type {
+                    DataType::List => {
+                        let span = type_span.and_then(|s| source.find("type", "list", s.end));
+                        diagnostics.push(Diagnostic::new(ErrorCode::NESTED_LIST, format!("field `{}.{}` cannot be a list of lists", entity, self.name))
+                            .with_label(source.label(span, "nested list"))
+                            .with_help("wrap the inner list in an entity and use a list of `ref`".to_string()));
+                    },
+                    DataType::Ref if list.ref_type.is_none() => {
+                        let span = type_span.and_then(|s| source.find("type", "ref", s.end));
+                        diagnostics.push(Diagnostic::new(ErrorCode::MISSING_REF, format!("field `{}.{}` is a list of `ref` and requires `list.ref`", entity, self.name))
+                            .with_label(source.label(span, "declared as `ref` here"))
+                            .with_help("add `ref: <entity name>` to the `list:` block".to_string()));
+                    },
+                    _ => {},
+                },
+            },
+            _ => {},
+        }
+    }
+}
+
+impl Entity {
+    /// 实体的主键字段
+    pub fn id_field(&self) -> Option<&Field> {
+        self.fields.iter().find(|f| matches!(f.field_type, DataType::AutoId))
+    }
+
+    /// 行为标志自动添加的字段
+    pub fn generated_fields(&self) -> Vec<FieldRole> {
+        let mut roles = vec![];
+        if self.soft_delete {
+            roles.push(FieldRole::Deleted);
+        }
+        if self.audited {
+            roles.extend([FieldRole::CreatedDate, FieldRole::CreatedBy, FieldRole::LastModifiedDate, FieldRole::LastModifiedBy]);
+        }
+        if self.versioned {
+            roles.push(FieldRole::Version);
+        }
+        roles
+    }
+
+    /// 查询可以使用的字段，包括行为标志添加的字段，顺序与语义模型中的字段一致
+    pub(crate) fn query_fields(&self) -> Vec<QueryField<'_>> {
+        let mut fields: Vec<QueryField> = self.fields
+            .iter()
+            .map(|f| QueryField {
+                name: &f.name,
+                kind: match f.field_type {
+                    DataType::String => QueryFieldKind::String,
+                    DataType::Bool => QueryFieldKind::Bool,
+                    DataType::Ref => QueryFieldKind::Ref,
+                    DataType::List => QueryFieldKind::List,
+                    _ => QueryFieldKind::Scalar,
+                },
+            })
+            .collect();
+        for role in self.generated_fields() {
+            let kind = match role {
+                FieldRole::Deleted => QueryFieldKind::Bool,
+                FieldRole::CreatedBy | FieldRole::LastModifiedBy => QueryFieldKind::String,
+                _ => QueryFieldKind::Scalar,
+            };
+            fields.push(QueryField { name: role.field_name(), kind });
+        }
+        fields
+    }
+
+    /// 每行 seed 的主键
+    pub fn seed_ids(&self) -> Vec<i64> {
+        let id_field = self.id_field().map(|f| f.name.as_str()).unwrap_or_default();
+        self.seed
+            .iter()
+            .enumerate()
+            .map(|(index, row)| row.get(id_field).and_then(|v| v.as_i64()).unwrap_or(index as i64 + 1))
+            .collect()
+    }
+
+    /// 转换后的 seed，无法转换的值被忽略，需要先经过 check
+    pub(crate) fn seed_rows(&self) -> Vec<SeedRow> {
+        let id_field = self.id_field().map(|f| f.name.as_str()).unwrap_or_default();
+        self.seed
+            .iter()
+            .zip(self.seed_ids())
+            .map(|(row, id)| {
+                let values = self.fields
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, field)| field.name != id_field)
+                    .filter_map(|(index, field)| {
+                        let value = row.get(field.name.as_str())?;
+                        seed::seed_value(field, value).ok().map(|value| (index, value))
+                    })
+                    .collect();
+                SeedRow { id, values }
+            })
+            .collect()
+    }
+
+    /// 字段引用的实体名称，包括 ref 以及 list 中的 ref
+    fn references(&self) -> Vec<(&Field, &str)> {
+        let mut references = vec![];
+        for field in &self.fields {
+            if let Some(reference) = field.reference.as_ref() {
+                references.push((field, reference.as_str()));
+            }
+            if let Some(reference) = field.list.as_ref().and_then(|l| l.ref_type.as_ref()) {
+                references.push((field, reference.as_str()));
+            }
+        }
+        references
+    }
+}
+
 impl DataDefinition {
     pub fn parse(yaml_path: &str) -> Result<Self> {
-        let file = File::open(yaml_path)?;
-        let reader = BufReader::new(file);
-        let mut definition: DataDefinition = serde_yaml::from_reader(reader)?;
-        definition.check()?;
+        let source = YamlSource::new(yaml_path, fs::read_to_string(yaml_path)?);
+        let mut definition: DataDefinition = match serde_yaml::from_str(&source.text) {
+            Ok(definition) => definition,
+            Err(e) => {
+                let span = e.location().map(|l| source.word_at(l.index()));
+                let mut diagnostics = Diagnostics::new();
+                diagnostics.add_source(&source.path, &source.text);
+                diagnostics.push(Diagnostic::new(ErrorCode::INVALID_YAML, e.to_string())
+                    .with_label(source.label(span, "")));
+                return Err(diagnostics.into());
+            }
+        };
+        definition.check(&source)?;
         Ok(definition)
     }
 
+    /// 主数据源，显式指定 primary 的优先，否则为第一个数据源
+    pub fn primary_datasource(&self) -> Option<&DataSource> {
+        self.datasources
+            .iter()
+            .find(|ds| ds.primary)
+            .or(self.datasources.first())
+    }
+
     /// 整理数据将data抽取到Map中，方便其他对象引用
-    /// 同时检测是否有数据不正确
-    fn check(&mut self) -> Result<()> {
-        // todo check
-        self.datasources.iter().for_each(|ds|{
-           ds.entities.iter().for_each(|x|{
-               self.entity_ref.insert(x.name.clone(), x.clone());
-           });
-        });
-
-        Ok(())
+    /// 同时检测是否有数据不正确，所有错误一起返回
+    fn check(&mut self, source: &YamlSource) -> Result<()> {
+        let mut diagnostics = Diagnostics::new();
+        diagnostics.add_source(&source.path, &source.text);
+
+        // 按文档顺序定位数据源、实体以及字段的名称
+        let mut cursor = 0;
+        let mut locate = |value: &str| -> Option<usize> {
+            let span = source.find("name", value, cursor)?;
+            cursor = span.end;
+            Some(span.start)
+        };
+        let mut datasource_at = vec![];
+        let mut entity_at: HashMap<String, Option<usize>> = HashMap::new();
+        let mut field_at: HashMap<(String, String), Option<usize>> = HashMap::new();
+        for ds in &self.datasources {
+            datasource_at.push(locate(&ds.name));
+            for entity in &ds.entities {
+                let at = locate(&entity.name);
+                entity_at.entry(entity.name.clone()).or_insert(at);
+                for field in &entity.fields {
+                    let at = locate(&field.name);
+                    field_at.entry((entity.name.clone(), field.name.clone())).or_insert(at);
+                }
+            }
+        }
+        let name_span = |at: Option<usize>, name: &str| at.map(|at| Span::new(at, at + name.len()));
+
+        let mut names: HashMap<&str, Option<usize>> = HashMap::new();
+        let mut packages: HashMap<String, (&str, Option<usize>)> = HashMap::new();
+        let mut primary: Option<(&str, Option<usize>)> = None;
+        for (ds, at) in self.datasources.iter().zip(datasource_at.iter().copied()) {
+            let span = name_span(at, &ds.name);
+            if let Some(first) = names.get(ds.name.as_str()) {
+                diagnostics.push(Diagnostic::new(ErrorCode::DUPLICATE_DATASOURCE, format!("duplicate datasource name `{}`", ds.name))
+                    .with_label(source.label(span, "redefined here"))
+                    .with_related(source.label(name_span(*first, &ds.name), "first defined here"))
+                    .with_help("datasource names must be unique".to_string()));
+            } else {
+                names.insert(&ds.name, at);
+            }
+            let package = ds.package_name();
+            if let Some((other, other_at)) = packages.get(&package) {
+                if *other != ds.name {
+                    let package_span = at.and_then(|at| source.find("package", &package, at)).or(span);
+                    diagnostics.push(Diagnostic::new(ErrorCode::DUPLICATE_PACKAGE, format!("datasource `{}` uses package `{}` which is already taken by `{}`", ds.name, package, other))
+                        .with_label(source.label(package_span, "package already in use"))
+                        .with_related(source.label(name_span(*other_at, other), "used by this datasource"))
+                        .with_help("set a different `package` for one of the datasources".to_string()));
+                }
+            } else {
+                packages.insert(package, (&ds.name, at));
+            }
+            if ds.primary {
+                match primary {
+                    Some((other, other_at)) => {
+                        let primary_span = at.and_then(|at| source.find("primary", "true", at));
+                        diagnostics.push(Diagnostic::new(ErrorCode::MULTIPLE_PRIMARY, format!("datasource `{}` is marked primary but `{}` already is", ds.name, other))
+                            .with_label(source.label(primary_span, "second primary datasource"))
+                            .with_related(source.label(name_span(other_at, other), "first primary datasource"))
+                            .with_help("only one datasource can be primary".to_string()));
+                    },
+                    None => primary = Some((&ds.name, at)),
+                }
+            }
+        }
+
+        for ds in &self.datasources {
+            for entity in &ds.entities {
+                if self.entity_datasource.contains_key(&entity.name) {
+                    let first = entity_at.get(&entity.name).copied().flatten();
+                    let span = first
+                        .and_then(|first| source.find("name", &entity.name, first + entity.name.len()));
+                    diagnostics.push(Diagnostic::new(ErrorCode::DUPLICATE_ENTITY, format!("duplicate entity name `{}`", entity.name))
+                        .with_label(source.label(span, "redefined here"))
+                        .with_related(source.label(name_span(first, &entity.name), "first defined here"))
+                        .with_help("entity names must be unique across all datasources".to_string()));
+                    continue;
+                }
+                self.entity_ref.insert(entity.name.clone(), entity.clone());
+                self.entity_datasource.insert(entity.name.clone(), ds.name.clone());
+            }
+        }
+
+        // 不同数据源使用不同的 EntityManagerFactory，实体之间不能跨数据源关联
+        for ds in &self.datasources {
+            for entity in &ds.entities {
+                let entity_span = name_span(entity_at.get(&entity.name).copied().flatten(), &entity.name);
+                if entity.id_field().is_none() {
+                    diagnostics.push(Diagnostic::new(ErrorCode::MISSING_ID, format!("entity `{}` has no `autoId` field", entity.name))
+                        .with_label(source.label(entity_span, "entity without primary key"))
+                        .with_help("add a field with `type: autoId`".to_string()));
+                }
+                for field in &entity.fields {
+                    let at = field_at.get(&(entity.name.clone(), field.name.clone())).copied().flatten();
+                    field.check(&entity.name, source, at, &mut diagnostics);
+                }
+                for role in entity.generated_fields() {
+                    let conflict = entity.fields.iter().find(|f| {
+                        f.name == role.field_name() || f.column.as_deref().unwrap_or(&naming::snake_case(&f.name)) == role.column()
+                    });
+                    if let Some(field) = conflict {
+                        let at = field_at.get(&(entity.name.clone(), field.name.clone())).copied().flatten();
+                        diagnostics.push(Diagnostic::new(ErrorCode::RESERVED_FIELD, format!(
+                            "field `{}.{}` conflicts with the `{}` field added by `{}: true`", entity.name, field.name, role.field_name(), role.flag()))
+                            .with_label(source.label(name_span(at, &field.name), "conflicting field"))
+                            .with_help(format!("remove the field or rename it and its column, `{}` uses column `{}`", role.field_name(), role.column())));
+                    }
+                }
+                for (field, reference) in entity.references() {
+                    let at = field_at.get(&(entity.name.clone(), field.name.clone())).copied().flatten();
+                    let ref_span = at.and_then(|at| source.find("ref", reference, at));
+                    match self.entity_datasource.get(reference) {
+                        None => {
+                            let mut diagnostic = Diagnostic::new(ErrorCode::UNKNOWN_REF, format!("field `{}.{}` refers to unknown entity `{}`", entity.name, field.name, reference))
+                                .with_label(source.label(ref_span, "unknown entity"));
+                            let candidates = ds.entities.iter().map(|e| e.name.as_str());
+                            diagnostic = match similar_name(reference, candidates) {
+                                Some(similar) => diagnostic.with_help(format!("did you mean `{}`?", similar)),
+                                None => diagnostic.with_help(format!("declare entity `{}` in datasource `{}` or fix the reference", reference, ds.name)),
+                            };
+                            diagnostics.push(diagnostic);
+                        },
+                        Some(target) if *target != ds.name => {
+                            let target_span = name_span(entity_at.get(reference).copied().flatten(), reference);
+                            diagnostics.push(Diagnostic::new(ErrorCode::CROSS_DATASOURCE_REF, format!(
+                                "field `{}.{}` refers to entity `{}` of datasource `{}`", entity.name, field.name, reference, target))
+                                .with_label(source.label(ref_span, "entity of another datasource"))
+                                .with_related(source.label(target_span, &format!("`{}` is declared in datasource `{}`", reference, target)))
+                                .with_help("relations across datasources are not supported, store the id instead".to_string()));
+                        },
+                        _ => {}
+                    }
+                }
+            }
+        }
+
+        for ds in &self.datasources {
+            for entity in ds.entities.iter().filter(|e| !e.seed.is_empty()) {
+                let at = entity_at.get(&entity.name).copied().flatten();
+                self.check_seed(entity, source, at, &mut diagnostics);
+            }
+        }
+        for ds in &self.datasources {
+            for entity in ds.entities.iter().filter(|e| !e.queries.is_empty()) {
+                let at = entity_at.get(&entity.name).copied().flatten();
+                check_queries(entity, source, at, &mut diagnostics);
+            }
+        }
+        dto::check_dtos(self, source, &mut diagnostics);
+
+        diagnostics.into_result(())
+    }
+
+    /// seed 中的字段必须存在，值与字段类型相符，ref 必须指向被引用实体的 seed 行
+    fn check_seed(&self, entity: &Entity, source: &YamlSource, at: Option<usize>, diagnostics: &mut Diagnostics) {
+        let from = at.unwrap_or(0);
+        let value_span = |key: &str, value: &serde_yaml::Value| {
+            at.and_then(|_| source.find(key, &value_text(value), from))
+        };
+        let ids = entity.seed_ids();
+        for (index, row) in entity.seed.iter().enumerate() {
+            for (key, value) in row {
+                let key = match key.as_str() {
+                    Some(key) => key,
+                    None => {
+                        diagnostics.push(Diagnostic::new(ErrorCode::SEED_UNKNOWN_FIELD, format!("seed row {} of `{}` has a non-string key `{}`", index + 1, entity.name, value_text(key)))
+                            .with_help("seed rows are written as `<field name>: <value>`".to_string()));
+                        continue;
+                    },
+                };
+                let span = value_span(key, value);
+                let field = match entity.fields.iter().find(|f| f.name == key) {
+                    Some(field) => field,
+                    None => {
+                        let mut diagnostic = Diagnostic::new(ErrorCode::SEED_UNKNOWN_FIELD, format!("seed row {} of `{}` sets unknown field `{}`", index + 1, entity.name, key))
+                            .with_label(source.label(span, "unknown field"));
+                        diagnostic = match similar_name(key, entity.fields.iter().map(|f| f.name.as_str())) {
+                            Some(similar) => diagnostic.with_help(format!("did you mean `{}`?", similar)),
+                            None => diagnostic.with_help(format!("declare field `{}` in entity `{}` or remove it from the seed", key, entity.name)),
+                        };
+                        diagnostics.push(diagnostic);
+                        continue;
+                    },
+                };
+                if let Err(expected) = seed::seed_value(field, value) {
+                    diagnostics.push(Diagnostic::new(ErrorCode::SEED_INVALID_VALUE, format!("seed value `{}` of `{}.{}` is not a valid {}", value_text(value), entity.name, field.name, expected))
+                        .with_label(source.label(span, &format!("expected {}", expected)))
+                        .with_help(seed_help(&field.field_type).to_string()));
+                    continue;
+                }
+                let reference = field.reference.as_ref().or(field.list.as_ref().and_then(|l| l.ref_type.as_ref()));
+                let target = match reference.and_then(|r| self.entity_ref.get(r)) {
+                    Some(target) => target,
+                    None => continue,
+                };
+                let target_ids = target.seed_ids();
+                let values = match value {
+                    serde_yaml::Value::Sequence(values) => values.iter().collect(),
+                    value => vec![value],
+                };
+                for id in values.iter().filter_map(|v| v.as_i64()) {
+                    if !target_ids.contains(&id) {
+                        diagnostics.push(Diagnostic::new(ErrorCode::SEED_UNKNOWN_REF, format!("seed value `{}` of `{}.{}` does not match any seed row of `{}`", id, entity.name, field.name, target.name))
+                            .with_label(source.label(span, "no such row"))
+                            .with_help(format!("add a seed row with id {} to `{}`", id, target.name)));
+                    }
+                }
+            }
+            if ids[..index].contains(&ids[index]) {
+                let id_field = entity.id_field().map(|f| f.name.as_str()).unwrap_or_default();
+                let span = row.get(id_field).and_then(|value| value_span(id_field, value));
+                diagnostics.push(Diagnostic::new(ErrorCode::SEED_DUPLICATE_ID, format!("seed row {} of `{}` reuses id {}", index + 1, entity.name, ids[index]))
+                    .with_label(source.label(span, "duplicate id"))
+                    .with_help("rows without an explicit id are numbered by position starting at 1".to_string()));
+            }
+        }
+    }
+
+}
+
+/// 查询名称不重复，方法名或者 where 中的字段、条件可以解析
+fn check_queries(entity: &Entity, source: &YamlSource, at: Option<usize>, diagnostics: &mut Diagnostics) {
+    let fields = entity.query_fields();
+    let mut names: HashMap<&str, Option<Span>> = HashMap::new();
+    let mut cursor = at.unwrap_or(0);
+    for query in &entity.queries {
+        let span = at.and_then(|_| source.find("name", &query.name, cursor));
+        if let Some(span) = span {
+            cursor = span.end;
+        }
+        if let Some(first) = names.get(query.name.as_str()) {
+            diagnostics.push(Diagnostic::new(ErrorCode::DUPLICATE_QUERY, format!("duplicate query `{}` in entity `{}`", query.name, entity.name))
+                .with_label(source.label(span, "redefined here"))
+                .with_related(source.label(*first, "first defined here"))
+                .with_help("query names must be unique within an entity".to_string()));
+            continue;
+        }
+        names.insert(&query.name, span);
+        let diagnostic = match query.resolve(&fields) {
+            Ok(_) => continue,
+            Err(QueryError::Syntax(message)) => Diagnostic::new(ErrorCode::INVALID_QUERY, format!("query `{}.{}` is invalid: {}", entity.name, query.name, message))
+                .with_label(source.label(span, "query declared here"))
+                .with_help("see the Spring Data query method naming rules, or use `where`, `sort` and `limit`".to_string()),
+            Err(QueryError::UnknownField(field)) => {
+                let field_span = at.and_then(|_| span.and_then(|s| source.find("field", &field, s.end))).or(span);
+                let help = match similar_name(&field, fields.iter().map(|f| f.name)) {
+                    Some(similar) => format!("did you mean `{}`?", similar),
+                    None => format!("`{}` has no field `{}`", entity.name, field),
+                };
+                Diagnostic::new(ErrorCode::UNKNOWN_QUERY_FIELD, format!("query `{}.{}` uses unknown field `{}`", entity.name, query.name, field))
+                    .with_label(source.label(field_span, "unknown field"))
+                    .with_help(help)
+            },
+            Err(QueryError::Operator { field, op }) => {
+                Diagnostic::new(ErrorCode::INVALID_QUERY_OPERATOR, format!("query `{}.{}` cannot apply `{}` to field `{}`", entity.name, query.name, op.name(), field))
+                    .with_label(source.label(span, "query declared here"))
+                    .with_help("text matching needs a string field, `isTrue`/`isFalse` a bool field, list fields cannot be queried".to_string())
+            },
+        };
+        diagnostics.push(diagnostic);
     }
+}
+
+/// yaml 中数据类型的名称
+fn data_type_name(data_type: &DataType) -> String {
+    serde_yaml::to_string(data_type)
+        .map(|s| s.trim().to_string())
+        .unwrap_or_default()
+}
+
+/// yaml 值的文本形式
+fn value_text(value: &serde_yaml::Value) -> String {
+    serde_yaml::to_string(value)
+        .map(|s| s.trim().to_string())
+        .unwrap_or_default()
+}
+
+fn seed_help(data_type: &DataType) -> &'static str {
+    match data_type {
+        DataType::DateTime => "write date times as `yyyy-MM-dd HH:mm:ss` or `yyyy-MM-dd`",
+        DataType::Ref => "refer to a row by its id",
+        DataType::List => "write list values as a yaml sequence",
+        DataType::Object => "write object values as a json string",
+        _ => "change the value to match the field type",
+    }
+}
+
+/// 编辑距离最近的名称，用于提示拼写错误
+pub(crate) fn similar_name<'a>(name: &str, candidates: impl Iterator<Item = &'a str>) -> Option<&'a str> {
+    candidates
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= name.chars().count() / 3 + 1)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
 
-}
\ No newline at end of file
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            current.push((previous[j] + cost).min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
@@ -0,0 +1,182 @@
+//! 从实体派生的 DTO，在 data.yaml 的 `dtos:` 中声明
+//!
+//! ```yaml
+//! dtos:
+//! - dto: personSummary
+//!   from: person
+//!   include: [id, name]
+//!   rename:
+//!     name: fullName
+//!   computed:
+//!   - name: cardCount
+//!     type: int32
+//!     expression: entity.getCards().size()
+//! ```
+
+use std::collections::{HashMap, HashSet};
+
+use serde::{Deserialize, Serialize};
+use project_gen_core::diagnostic::{Diagnostic, Diagnostics, ErrorCode, Span};
+
+use super::{
+    api::ApiType,
+    common::Documentation,
+    data::{DataDefinition, Entity, similar_name},
+    source::YamlSource,
+};
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Dto {
+    #[serde(rename = "dto")]
+    pub name: String,
+    /// 源实体名称
+    pub from: String,
+    #[serde(flatten)]
+    pub documentation: Documentation,
+    /// 包含的实体字段，未指定时包含所有字段，不能和 exclude 同时使用
+    pub include: Option<Vec<String>>,
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// 实体字段名 -> DTO 中的字段名
+    #[serde(default)]
+    pub rename: HashMap<String, String>,
+    #[serde(default)]
+    pub computed: Vec<ComputedField>,
+}
+
+/// 由实体计算得到的字段，只能从实体转换为 DTO
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ComputedField {
+    pub name: String,
+    #[serde(flatten)]
+    pub value_type: ApiType,
+    /// java 表达式，源实体为 `entity`
+    pub expression: String,
+    #[serde(flatten)]
+    pub documentation: Documentation,
+}
+
+impl Dto {
+    /// 包含的实体字段名
+    pub fn selected_fields<'a>(&self, entity: &'a Entity) -> Vec<&'a str> {
+        entity.fields
+            .iter()
+            .map(|f| f.name.as_str())
+            .chain(entity.generated_fields().iter().map(|r| r.field_name()))
+            .filter(|name| match self.include.as_ref() {
+                Some(include) => include.iter().any(|i| i == name),
+                None => !self.exclude.iter().any(|e| e == name),
+            })
+            .collect()
+    }
+
+    /// 实体字段在 DTO 中的名称
+    pub fn field_name<'a>(&'a self, field: &'a str) -> &'a str {
+        self.rename.get(field).map(|s| s.as_str()).unwrap_or(field)
+    }
+}
+
+/// DTO 名称唯一，源实体存在，选择、重命名的字段属于源实体，DTO 中的字段名不重复
+pub(crate) fn check_dtos(definition: &DataDefinition, source: &YamlSource, diagnostics: &mut Diagnostics) {
+    let mut names: HashMap<&str, Option<Span>> = HashMap::new();
+    let mut cursor = 0;
+    for dto in &definition.dtos {
+        let span = source.find("dto", &dto.name, cursor);
+        if let Some(span) = span {
+            cursor = span.end;
+        }
+        if let Some(first) = names.get(dto.name.as_str()) {
+            diagnostics.push(Diagnostic::new(ErrorCode::DUPLICATE_DTO, format!("duplicate dto name `{}`", dto.name))
+                .with_label(source.label(span, "redefined here"))
+                .with_related(source.label(*first, "first defined here"))
+                .with_help("dto names must be unique".to_string()));
+            continue;
+        }
+        names.insert(&dto.name, span);
+        let from_span = span.and_then(|s| source.find("from", &dto.from, s.end));
+
+        let entity = match definition.entity_ref.get(&dto.from) {
+            Some(entity) => entity,
+            None => {
+                let candidates = definition.entity_ref.keys().map(|k| k.as_str());
+                let help = match similar_name(&dto.from, candidates) {
+                    Some(similar) => format!("did you mean `{}`?", similar),
+                    None => format!("declare entity `{}` or fix `from`", dto.from),
+                };
+                diagnostics.push(Diagnostic::new(ErrorCode::UNKNOWN_REF, format!("dto `{}` is derived from unknown entity `{}`", dto.name, dto.from))
+                    .with_label(source.label(from_span, "unknown entity"))
+                    .with_help(help));
+                continue;
+            },
+        };
+
+        if dto.include.is_some() && !dto.exclude.is_empty() {
+            diagnostics.push(Diagnostic::new(ErrorCode::INVALID_DTO, format!("dto `{}` uses both `include` and `exclude`", dto.name))
+                .with_label(source.label(span, "dto declared here"))
+                .with_help("keep only one of `include` and `exclude`".to_string()));
+        }
+
+        let entity_fields: Vec<&str> = entity.fields
+            .iter()
+            .map(|f| f.name.as_str())
+            .chain(entity.generated_fields().iter().map(|r| r.field_name()))
+            .collect();
+        let listed = dto.include.iter().flatten()
+            .map(|f| ("include", f))
+            .chain(dto.exclude.iter().map(|f| ("exclude", f)))
+            .chain(dto.rename.keys().map(|f| ("rename", f)));
+        for (key, field) in listed {
+            if entity_fields.contains(&field.as_str()) {
+                continue;
+            }
+            let help = match similar_name(field, entity_fields.iter().copied()) {
+                Some(similar) => format!("did you mean `{}`?", similar),
+                None => format!("`{}` has fields {}", entity.name, quoted(&entity_fields)),
+            };
+            diagnostics.push(Diagnostic::new(ErrorCode::UNKNOWN_DTO_FIELD, format!("`{}` of dto `{}` lists unknown field `{}.{}`", key, dto.name, entity.name, field))
+                .with_label(source.label(span, "dto declared here"))
+                .with_help(help));
+        }
+        let selected = dto.selected_fields(entity);
+        for field in dto.rename.keys() {
+            if entity_fields.contains(&field.as_str()) && !selected.contains(&field.as_str()) {
+                diagnostics.push(Diagnostic::new(ErrorCode::UNKNOWN_DTO_FIELD, format!("dto `{}` renames field `{}` which is not included", dto.name, field))
+                    .with_label(source.label(span, "dto declared here"))
+                    .with_help(format!("include `{}` or remove it from `rename`", field)));
+            }
+        }
+
+        let mut dto_fields: HashSet<&str> = HashSet::new();
+        let dto_names = selected
+            .iter()
+            .map(|f| dto.field_name(f))
+            .chain(dto.computed.iter().map(|c| c.name.as_str()));
+        for name in dto_names {
+            if !dto_fields.insert(name) {
+                diagnostics.push(Diagnostic::new(ErrorCode::DUPLICATE_DTO_FIELD, format!("dto `{}` has more than one field named `{}`", dto.name, name))
+                    .with_label(source.label(span, "dto declared here"))
+                    .with_help("rename one of the fields".to_string()));
+            }
+        }
+
+        for computed in &dto.computed {
+            let computed_span = span.and_then(|s| source.find("name", &computed.name, s.end));
+            if !computed.value_type.is_valid() {
+                diagnostics.push(Diagnostic::new(ErrorCode::INVALID_TYPE, format!("computed field `{}.{}` must have exactly one of `type`, `ref` or `list`", dto.name, computed.name))
+                    .with_label(source.label(computed_span, "computed field declared here"))
+                    .with_help("use `type: <type>`, `ref: <entity>` or a `list:` block".to_string()));
+            }
+            for reference in computed.value_type.references() {
+                if !definition.entity_ref.contains_key(reference) {
+                    diagnostics.push(Diagnostic::new(ErrorCode::UNKNOWN_REF, format!("computed field `{}.{}` refers to unknown entity `{}`", dto.name, computed.name, reference))
+                        .with_label(source.label(computed_span, "computed field declared here"))
+                        .with_help(format!("declare entity `{}` or fix the reference", reference)));
+                }
+            }
+        }
+    }
+}
+
+fn quoted(names: &[&str]) -> String {
+    names.iter().map(|n| format!("`{}`", n)).collect::<Vec<_>>().join(", ")
+}
@@ -1,2 +1,61 @@
+mod api;
 mod common;
-mod data;
\ No newline at end of file
+mod data;
+mod dto;
+mod model;
+mod query;
+mod seed;
+mod source;
+
+pub use common::{DataType, Documentation};
+pub use seed::{SeedRow, SeedValue};
+pub use dto::{Dto, ComputedField};
+pub use query::{
+    Query,
+    Condition,
+    Predicate,
+    Operator,
+    Sort,
+    Direction,
+    QuerySubject,
+    ConditionNode
+};
+pub use data::{
+    DataDefinition,
+    DataSource,
+    Entity,
+    Field,
+    TypeList
+};
+pub use api::{
+    ApiDefinition,
+    Api,
+    ApiRequest,
+    ApiRequestParameter,
+    ApiType,
+    Filterable,
+    HttpMethod
+};
+pub use model::{
+    Model,
+    DatasourceId,
+    DatasourceNode,
+    EntityId,
+    EntityNode,
+    FieldNode,
+    FieldRole,
+    FieldType,
+    FilterNode,
+    ListingNode,
+    QueryNode,
+    ScalarType,
+    Relation,
+    Cardinality,
+    DtoNode,
+    DtoFieldNode,
+    DtoFieldSource,
+    ApiNode,
+    RequestNode,
+    ParamNode,
+    ParamSource
+};
@@ -0,0 +1,601 @@
+//! 解析并校验后的语义模型，所有的代码生成器都基于这个模型生成代码
+//!
+//! yaml 中的名称引用在这里被解析为 id，字段类型解析为类型树，
+//! 实体之间的关联作为图的边单独保存
+
+use std::collections::HashMap;
+
+use project_gen_core::naming;
+
+use super::{
+    api::{ApiDefinition, ApiRequest, ApiType, HttpMethod},
+    common::{DataType, Documentation},
+    data::{DataDefinition, Field, TypeList},
+    query::{ConditionNode, Direction, Operator, QuerySubject},
+    seed::SeedRow,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct DatasourceId(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct EntityId(pub usize);
+
+/// 基础类型
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ScalarType {
+    Id,
+    String,
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    Float32,
+    Float64,
+    Bool,
+    DateTime,
+    Object,
+}
+
+/// 解析后的类型
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldType {
+    Scalar(ScalarType),
+    Entity(EntityId),
+    List(Box<FieldType>),
+}
+
+impl FieldType {
+    /// 类型树中引用的实体
+    pub fn entity(&self) -> Option<EntityId> {
+        match self {
+            FieldType::Scalar(_) => None,
+            FieldType::Entity(id) => Some(*id),
+            FieldType::List(element) => element.entity(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DatasourceNode {
+    pub id: DatasourceId,
+    pub name: String,
+    /// 子包名
+    pub package: String,
+    pub primary: bool,
+    pub host: String,
+    pub port: u16,
+    pub username: String,
+    pub password: String,
+    pub database: String,
+    pub entities: Vec<EntityId>,
+}
+
+/// 字段的用途，除 Data 之外都是由实体的行为标志自动添加的
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FieldRole {
+    Data,
+    /// `softDelete`
+    Deleted,
+    /// `audited`
+    CreatedDate,
+    CreatedBy,
+    LastModifiedDate,
+    LastModifiedBy,
+    /// `versioned`
+    Version,
+}
+
+impl FieldRole {
+    pub fn field_name(&self) -> &'static str {
+        match self {
+            FieldRole::Data => "",
+            FieldRole::Deleted => "deleted",
+            FieldRole::CreatedDate => "createdAt",
+            FieldRole::CreatedBy => "createdBy",
+            FieldRole::LastModifiedDate => "updatedAt",
+            FieldRole::LastModifiedBy => "updatedBy",
+            FieldRole::Version => "version",
+        }
+    }
+
+    pub fn column(&self) -> String {
+        naming::snake_case(self.field_name())
+    }
+
+    /// 添加该字段的行为标志
+    pub fn flag(&self) -> &'static str {
+        match self {
+            FieldRole::Data => "",
+            FieldRole::Deleted => "softDelete",
+            FieldRole::CreatedDate
+            | FieldRole::CreatedBy
+            | FieldRole::LastModifiedDate
+            | FieldRole::LastModifiedBy => "audited",
+            FieldRole::Version => "versioned",
+        }
+    }
+
+    fn scalar_type(&self) -> ScalarType {
+        match self {
+            FieldRole::Data => ScalarType::Object,
+            FieldRole::Deleted => ScalarType::Bool,
+            FieldRole::CreatedDate | FieldRole::LastModifiedDate => ScalarType::DateTime,
+            FieldRole::CreatedBy | FieldRole::LastModifiedBy => ScalarType::String,
+            FieldRole::Version => ScalarType::Int64,
+        }
+    }
+
+    fn label(&self) -> &'static str {
+        match self {
+            FieldRole::Data => "",
+            FieldRole::Deleted => "是否删除",
+            FieldRole::CreatedDate => "创建时间",
+            FieldRole::CreatedBy => "创建人",
+            FieldRole::LastModifiedDate => "修改时间",
+            FieldRole::LastModifiedBy => "修改人",
+            FieldRole::Version => "版本号",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FieldNode {
+    pub name: String,
+    /// 列名，ref 字段为外键列
+    pub column: String,
+    pub field_type: FieldType,
+    pub documentation: Documentation,
+    pub role: FieldRole,
+}
+
+#[derive(Debug, Clone)]
+pub struct EntityNode {
+    pub id: EntityId,
+    pub name: String,
+    pub table: String,
+    pub datasource: DatasourceId,
+    pub documentation: Documentation,
+    pub fields: Vec<FieldNode>,
+    /// 主键字段在 fields 中的下标
+    pub id_field: usize,
+    pub queries: Vec<QueryNode>,
+    pub seed: Vec<SeedRow>,
+}
+
+impl EntityNode {
+    pub fn id_field(&self) -> &FieldNode {
+        &self.fields[self.id_field]
+    }
+
+    /// 行为标志添加的字段
+    pub fn role_field(&self, role: FieldRole) -> Option<&FieldNode> {
+        self.fields.iter().find(|f| f.role == role)
+    }
+
+    /// 基础类型列表字段单独保存的表
+    pub fn collection_table(&self, field: &FieldNode) -> String {
+        format!("{}_{}", self.table, naming::snake_case(&field.name))
+    }
+
+    /// 列表字段的表中指向实体的外键列
+    pub fn collection_join_column(&self) -> String {
+        format!("{}_id", naming::snake_case(&self.name))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cardinality {
+    /// `type: ref`，外键在 from 实体的表中
+    ManyToOne,
+    /// `list` 中的 ref，外键在 to 实体的表中
+    OneToMany,
+}
+
+/// 实体之间的关联，from 实体的 field 字段指向 to 实体
+#[derive(Debug, Clone)]
+pub struct Relation {
+    pub from: EntityId,
+    pub field: usize,
+    pub to: EntityId,
+    pub cardinality: Cardinality,
+    /// 外键列名，ManyToOne 时位于 from 实体的表，OneToMany 时位于 to 实体的表
+    pub join_column: String,
+}
+
+/// 实体上声明的查询，字段为实体 fields 中的下标
+#[derive(Debug, Clone)]
+pub struct QueryNode {
+    pub name: String,
+    pub documentation: Documentation,
+    pub subject: QuerySubject,
+    /// 由方法名解析得到，可以直接作为 Spring Data 的查询方法
+    pub derived: bool,
+    pub condition: Option<ConditionNode>,
+    pub sort: Vec<(usize, Direction)>,
+    pub limit: Option<u32>,
+    pub unique: bool,
+}
+
+impl QueryNode {
+    /// 按顺序排列的参数，参数名、字段以及条件
+    pub fn params(&self) -> Vec<(&str, usize, Operator)> {
+        let mut params = vec![];
+        for (field, op, names) in self.condition.iter().flat_map(|c| c.predicates()) {
+            params.extend(names.iter().map(|name| (name.as_str(), field, op)));
+        }
+        params
+    }
+}
+
+/// DTO 字段的来源
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DtoFieldSource {
+    /// 源实体 fields 中的下标
+    Field(usize),
+    /// java 表达式，源实体为 `entity`
+    Computed(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct DtoFieldNode {
+    pub name: String,
+    pub field_type: FieldType,
+    pub documentation: Documentation,
+    pub source: DtoFieldSource,
+}
+
+/// 从实体派生的 DTO
+#[derive(Debug, Clone)]
+pub struct DtoNode {
+    pub name: String,
+    pub entity: EntityId,
+    pub documentation: Documentation,
+    pub fields: Vec<DtoFieldNode>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParamSource {
+    Path,
+    Query,
+    Body,
+}
+
+#[derive(Debug, Clone)]
+pub struct ParamNode {
+    pub name: String,
+    pub source: ParamSource,
+    pub param_type: FieldType,
+}
+
+/// 列表请求的过滤条件，params 为请求中对应的参数名
+#[derive(Debug, Clone)]
+pub struct FilterNode {
+    pub field: usize,
+    pub op: Operator,
+    pub params: Vec<String>,
+}
+
+/// 列表请求的分页、排序以及过滤，字段为返回的实体 fields 中的下标
+#[derive(Debug, Clone)]
+pub struct ListingNode {
+    pub entity: EntityId,
+    pub paged: bool,
+    pub sortable: Vec<usize>,
+    pub filters: Vec<FilterNode>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RequestNode {
+    pub name: String,
+    pub method: HttpMethod,
+    pub path: String,
+    pub params: Vec<ParamNode>,
+    pub response: Option<FieldType>,
+    pub listing: Option<ListingNode>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ApiNode {
+    pub name: String,
+    pub base_url: String,
+    pub requests: Vec<RequestNode>,
+}
+
+impl ApiNode {
+    /// 接口涉及的实体，包括参数和返回值
+    pub fn entities(&self) -> Vec<EntityId> {
+        let mut entities = vec![];
+        for request in &self.requests {
+            let types = request.params.iter().map(|p| &p.param_type).chain(request.response.iter());
+            for entity in types.filter_map(|t| t.entity()) {
+                if !entities.contains(&entity) {
+                    entities.push(entity);
+                }
+            }
+        }
+        entities
+    }
+}
+
+/// 语义模型
+#[derive(Debug, Clone, Default)]
+pub struct Model {
+    pub datasources: Vec<DatasourceNode>,
+    pub entities: Vec<EntityNode>,
+    pub relations: Vec<Relation>,
+    pub dtos: Vec<DtoNode>,
+    pub apis: Vec<ApiNode>,
+    entity_names: HashMap<String, EntityId>,
+}
+
+impl Model {
+    /// 由校验过的定义构建模型
+    pub fn new(data: &DataDefinition, api: Option<&ApiDefinition>) -> Model {
+        let mut model = Model::default();
+        let primary = data.primary_datasource().map(|ds| ds.name.clone());
+        for (ds_index, ds) in data.datasources.iter().enumerate() {
+            let mut node = DatasourceNode {
+                id: DatasourceId(ds_index),
+                name: ds.name.clone(),
+                package: ds.package_name(),
+                primary: primary.as_ref() == Some(&ds.name),
+                host: ds.host.clone(),
+                port: ds.port,
+                username: ds.username.clone(),
+                password: ds.password.clone(),
+                database: ds.database.clone(),
+                entities: vec![],
+            };
+            for entity in &ds.entities {
+                let id = EntityId(model.entities.len());
+                model.entity_names.insert(entity.name.clone(), id);
+                node.entities.push(id);
+                model.entities.push(EntityNode {
+                    id,
+                    name: entity.name.clone(),
+                    table: entity.table.clone(),
+                    datasource: node.id,
+                    documentation: entity.documentation.clone(),
+                    fields: vec![],
+                    id_field: 0,
+                    queries: vec![],
+                    seed: entity.seed_rows(),
+                });
+            }
+            model.datasources.push(node);
+        }
+
+        // 所有实体注册之后再解析字段，字段可以引用后面定义的实体
+        let entities = data.datasources.iter().flat_map(|ds| ds.entities.iter());
+        for (index, entity) in entities.enumerate() {
+            let id = EntityId(index);
+            let mut fields = vec![];
+            for (field_index, field) in entity.fields.iter().enumerate() {
+                let field_type = model.resolve_field(field);
+                let column = match (&field.column, &field_type) {
+                    (Some(column), _) => column.clone(),
+                    (None, FieldType::Entity(_)) => format!("{}_id", naming::snake_case(&field.name)),
+                    (None, _) => naming::snake_case(&field.name),
+                };
+                match &field_type {
+                    FieldType::Entity(to) => model.relations.push(Relation {
+                        from: id,
+                        field: field_index,
+                        to: *to,
+                        cardinality: Cardinality::ManyToOne,
+                        join_column: column.clone(),
+                    }),
+                    FieldType::List(element) => if let FieldType::Entity(to) = element.as_ref() {
+                        model.relations.push(Relation {
+                            from: id,
+                            field: field_index,
+                            to: *to,
+                            cardinality: Cardinality::OneToMany,
+                            join_column: format!("{}_id", naming::snake_case(&entity.name)),
+                        });
+                    },
+                    _ => {}
+                }
+                fields.push(FieldNode {
+                    name: field.name.clone(),
+                    column,
+                    field_type,
+                    documentation: field.documentation.clone(),
+                    role: FieldRole::Data,
+                });
+            }
+            for role in entity.generated_fields() {
+                fields.push(FieldNode {
+                    name: role.field_name().to_string(),
+                    column: role.column(),
+                    field_type: FieldType::Scalar(role.scalar_type()),
+                    documentation: Documentation { label: Some(role.label().to_string()), ..Documentation::default() },
+                    role,
+                });
+            }
+            let query_fields = entity.query_fields();
+            let queries = entity.queries.iter().filter_map(|query| {
+                let resolved = query.resolve(&query_fields).ok()?;
+                Some(QueryNode {
+                    name: query.name.clone(),
+                    documentation: query.documentation.clone(),
+                    subject: resolved.subject,
+                    derived: resolved.derived,
+                    condition: resolved.condition,
+                    sort: resolved.sort,
+                    limit: resolved.limit,
+                    unique: resolved.unique,
+                })
+            }).collect();
+            let node = &mut model.entities[index];
+            node.queries = queries;
+            node.id_field = entity.fields.iter().position(|f| matches!(f.field_type, DataType::AutoId)).unwrap_or(0);
+            node.fields = fields;
+        }
+
+        for dto in &data.dtos {
+            let id = match model.entity_by_name(&dto.from) {
+                Some(id) => id,
+                None => continue,
+            };
+            let entity = model.entity(id);
+            let selected = data.entity_ref.get(&dto.from).map(|e| dto.selected_fields(e)).unwrap_or_default();
+            let mut fields: Vec<DtoFieldNode> = entity.fields
+                .iter()
+                .enumerate()
+                .filter(|(_, field)| selected.contains(&field.name.as_str()))
+                .map(|(index, field)| DtoFieldNode {
+                    name: dto.field_name(&field.name).to_string(),
+                    field_type: field.field_type.clone(),
+                    documentation: field.documentation.clone(),
+                    source: DtoFieldSource::Field(index),
+                })
+                .collect();
+            for computed in &dto.computed {
+                fields.push(DtoFieldNode {
+                    name: computed.name.clone(),
+                    field_type: model.resolve_api_type(&computed.value_type),
+                    documentation: computed.documentation.clone(),
+                    source: DtoFieldSource::Computed(computed.expression.clone()),
+                });
+            }
+            model.dtos.push(DtoNode {
+                name: dto.name.clone(),
+                entity: id,
+                documentation: dto.documentation.clone(),
+                fields,
+            });
+        }
+
+        for api in api.iter().flat_map(|a| a.apis.iter()) {
+            let requests = api.requests.iter().map(|request| {
+                let params = request.params.iter().flatten().map(|param| {
+                    let param_type = model.resolve_api_type(&param.value_type);
+                    let source = match (&param.path_variable, &param_type) {
+                        (Some(_), _) => ParamSource::Path,
+                        (None, FieldType::Scalar(_)) => ParamSource::Query,
+                        (None, _) => ParamSource::Body,
+                    };
+                    ParamNode { name: param.param_name().to_string(), source, param_type }
+                }).collect();
+                RequestNode {
+                    name: request.name.clone(),
+                    method: request.method,
+                    path: request.path.clone().unwrap_or_default(),
+                    params,
+                    response: request.response.as_ref().map(|r| model.resolve_api_type(r)),
+                    listing: model.resolve_listing(request),
+                }
+            }).collect();
+            model.apis.push(ApiNode {
+                name: api.name.clone(),
+                base_url: api.base_url.clone().unwrap_or_default(),
+                requests,
+            });
+        }
+        model
+    }
+
+    /// 列表请求的分页、排序以及过滤，字段已在 api.yaml 中校验
+    fn resolve_listing(&self, request: &ApiRequest) -> Option<ListingNode> {
+        if !request.is_listing() {
+            return None;
+        }
+        let entity = self.entity(self.entity_by_name(request.listed_entity()?)?);
+        let field_index = |name: &str| entity.fields.iter().position(|f| f.name == name);
+        let mut filters = vec![];
+        for (field_name, ops) in &request.filterable.0 {
+            let field = field_index(field_name)?;
+            filters.extend(ops.iter().map(|op| FilterNode { field, op: *op, params: op.filter_params(field_name) }));
+        }
+        Some(ListingNode {
+            entity: entity.id,
+            paged: request.paged,
+            sortable: request.sortable.iter().filter_map(|name| field_index(name)).collect(),
+            filters,
+        })
+    }
+
+    pub fn entity(&self, id: EntityId) -> &EntityNode {
+        &self.entities[id.0]
+    }
+
+    pub fn datasource(&self, id: DatasourceId) -> &DatasourceNode {
+        &self.datasources[id.0]
+    }
+
+    pub fn entity_by_name(&self, name: &str) -> Option<EntityId> {
+        self.entity_names.get(name).copied()
+    }
+
+    /// 实体发出的关联
+    pub fn outgoing(&self, id: EntityId) -> impl Iterator<Item = &Relation> {
+        self.relations.iter().filter(move |r| r.from == id)
+    }
+
+    /// 指向实体的关联
+    pub fn incoming(&self, id: EntityId) -> impl Iterator<Item = &Relation> {
+        self.relations.iter().filter(move |r| r.to == id)
+    }
+
+    /// 数据源中是否有实体声明了 seed
+    pub fn has_seed(&self, datasource: &DatasourceNode) -> bool {
+        datasource.entities.iter().any(|id| !self.entity(*id).seed.is_empty())
+    }
+
+    /// 主数据源
+    pub fn primary_datasource(&self) -> Option<&DatasourceNode> {
+        self.datasources.iter().find(|ds| ds.primary)
+    }
+
+    fn resolve_field(&self, field: &Field) -> FieldType {
+        match field.field_type {
+            DataType::Ref => self.resolve_ref(field.reference.as_deref()),
+            DataType::List => self.resolve_list(field.list.as_ref()),
+            ref data_type => FieldType::Scalar(scalar_type(data_type)),
+        }
+    }
+
+    fn resolve_api_type(&self, api_type: &ApiType) -> FieldType {
+        if api_type.reference.is_some() {
+            return self.resolve_ref(api_type.reference.as_deref());
+        }
+        if api_type.list.is_some() {
+            return self.resolve_list(api_type.list.as_ref());
+        }
+        FieldType::Scalar(api_type.data_type.as_ref().map(scalar_type).unwrap_or(ScalarType::Object))
+    }
+
+    fn resolve_ref(&self, reference: Option<&str>) -> FieldType {
+        reference
+            .and_then(|r| self.entity_by_name(r))
+            .map(FieldType::Entity)
+            .unwrap_or(FieldType::Scalar(ScalarType::Object))
+    }
+
+    fn resolve_list(&self, list: Option<&TypeList>) -> FieldType {
+        let element = match list {
+            Some(TypeList { list_type: DataType::Ref, ref_type }) => self.resolve_ref(ref_type.as_deref()),
+            Some(TypeList { list_type, .. }) => FieldType::Scalar(scalar_type(list_type)),
+            None => FieldType::Scalar(ScalarType::Object),
+        };
+        FieldType::List(Box::new(element))
+    }
+}
+
+fn scalar_type(data_type: &DataType) -> ScalarType {
+    match data_type {
+        DataType::AutoId => ScalarType::Id,
+        DataType::String => ScalarType::String,
+        DataType::Int8 => ScalarType::Int8,
+        DataType::Int16 => ScalarType::Int16,
+        DataType::Int32 => ScalarType::Int32,
+        DataType::Int64 => ScalarType::Int64,
+        DataType::Float32 => ScalarType::Float32,
+        DataType::Float64 => ScalarType::Float64,
+        DataType::Bool => ScalarType::Bool,
+        DataType::DateTime => ScalarType::DateTime,
+        DataType::Object | DataType::List | DataType::Ref => ScalarType::Object,
+    }
+}
@@ -0,0 +1,595 @@
+//! 实体上声明的查询方法
+//!
+//! 可以直接使用 Spring Data 风格的方法名，也可以使用结构化的 where、sort、limit：
+//!
+//! ```yaml
+//! queries:
+//! - name: findByNameAndAgeGreaterThan
+//! - name: findAdults
+//!   where:
+//!   - field: age
+//!     op: gte
+//!     param: minAge
+//!   - any:
+//!     - field: name
+//!       op: startsWith
+//!   sort:
+//!   - field: name
+//!     direction: desc
+//!   limit: 10
+//! ```
+
+use serde::{Deserialize, Serialize};
+
+use super::common::Documentation;
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Query {
+    pub name: String,
+    /// 未指定时根据方法名解析查询条件
+    #[serde(rename = "where")]
+    pub conditions: Option<Vec<Condition>>,
+    #[serde(default)]
+    pub sort: Vec<Sort>,
+    pub limit: Option<u32>,
+    /// 只返回一个结果
+    #[serde(default)]
+    pub unique: bool,
+    #[serde(flatten)]
+    pub documentation: Documentation,
+}
+
+/// where 中的条件之间为 and 关系，any 中的条件之间为 or 关系
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(untagged)]
+pub enum Condition {
+    Any { any: Vec<Predicate> },
+    Predicate(Predicate),
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Predicate {
+    pub field: String,
+    #[serde(default)]
+    pub op: Operator,
+    /// 参数名，默认使用字段名
+    pub param: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum Operator {
+    #[default]
+    Eq,
+    Ne,
+    Gt,
+    Gte,
+    Lt,
+    Lte,
+    Between,
+    Like,
+    StartsWith,
+    EndsWith,
+    Contains,
+    In,
+    NotIn,
+    IsNull,
+    IsNotNull,
+    IsTrue,
+    IsFalse,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Sort {
+    pub field: String,
+    #[serde(default)]
+    pub direction: Direction,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum Direction {
+    #[default]
+    Asc,
+    Desc,
+}
+
+impl Operator {
+    /// yaml 中的名称
+    pub fn name(&self) -> &'static str {
+        match self {
+            Operator::Eq => "eq",
+            Operator::Ne => "ne",
+            Operator::Gt => "gt",
+            Operator::Gte => "gte",
+            Operator::Lt => "lt",
+            Operator::Lte => "lte",
+            Operator::Between => "between",
+            Operator::Like => "like",
+            Operator::StartsWith => "startsWith",
+            Operator::EndsWith => "endsWith",
+            Operator::Contains => "contains",
+            Operator::In => "in",
+            Operator::NotIn => "notIn",
+            Operator::IsNull => "isNull",
+            Operator::IsNotNull => "isNotNull",
+            Operator::IsTrue => "isTrue",
+            Operator::IsFalse => "isFalse",
+        }
+    }
+
+    /// 生成的文档中使用的条件说明，如 `包含`、`大于等于`
+    pub fn description(&self) -> &'static str {
+        match self {
+            Operator::Eq => "等于",
+            Operator::Ne => "不等于",
+            Operator::Gt => "大于",
+            Operator::Gte => "大于等于",
+            Operator::Lt => "小于",
+            Operator::Lte => "小于等于",
+            Operator::Between => "介于",
+            Operator::Like => "匹配",
+            Operator::StartsWith => "开头为",
+            Operator::EndsWith => "结尾为",
+            Operator::Contains => "包含",
+            Operator::In => "属于",
+            Operator::NotIn => "不属于",
+            Operator::IsNull => "为空",
+            Operator::IsNotNull => "不为空",
+            Operator::IsTrue => "为 true",
+            Operator::IsFalse => "为 false",
+        }
+    }
+
+    /// 条件需要的参数个数
+    pub fn arity(&self) -> usize {
+        match self {
+            Operator::Between => 2,
+            Operator::IsNull | Operator::IsNotNull | Operator::IsTrue | Operator::IsFalse => 0,
+            _ => 1,
+        }
+    }
+
+    /// 只能用于字符串字段
+    pub fn is_text(&self) -> bool {
+        matches!(self, Operator::Like | Operator::StartsWith | Operator::EndsWith | Operator::Contains)
+    }
+
+    /// 只能用于布尔字段
+    pub fn is_bool(&self) -> bool {
+        matches!(self, Operator::IsTrue | Operator::IsFalse)
+    }
+
+    /// 列表请求中过滤条件对应的参数名，eq 使用字段名，其他条件在字段名后加上条件名
+    /// 如 `nameLike`，between 拆分为 `ageFrom` 和 `ageTo`
+    pub fn filter_params(&self, field: &str) -> Vec<String> {
+        let suffix = match self {
+            Operator::Eq => return vec![field.to_string()],
+            Operator::Between => return vec![format!("{}From", field), format!("{}To", field)],
+            Operator::Ne => "Ne",
+            Operator::Gt => "Gt",
+            Operator::Gte => "Gte",
+            Operator::Lt => "Lt",
+            Operator::Lte => "Lte",
+            Operator::Like => "Like",
+            Operator::StartsWith => "StartsWith",
+            Operator::EndsWith => "EndsWith",
+            Operator::Contains => "Contains",
+            Operator::In => "In",
+            Operator::NotIn => "NotIn",
+            Operator::IsNull => "IsNull",
+            Operator::IsNotNull => "IsNotNull",
+            Operator::IsTrue => "IsTrue",
+            Operator::IsFalse => "IsFalse",
+        };
+        vec![format!("{}{}", field, suffix)]
+    }
+
+    /// 过滤参数的说明，label 为字段的标签，index 为参数在 `filter_params` 中的位置
+    pub fn filter_doc(&self, label: &str, index: usize) -> String {
+        match (self, index) {
+            (Operator::Eq, _) => label.to_string(),
+            (Operator::Between, 0) => format!("{}下限", label),
+            (Operator::Between, _) => format!("{}上限", label),
+            _ if self.arity() == 0 => format!("为 true 时只查询{}{}的数据", label, self.description()),
+            _ => format!("{}{}", label, self.description()),
+        }
+    }
+
+    /// 方法名中的关键字，按长度从长到短匹配
+    fn keywords() -> &'static [(&'static str, Operator)] {
+        &[
+            ("GreaterThanEqual", Operator::Gte),
+            ("LessThanEqual", Operator::Lte),
+            ("GreaterThan", Operator::Gt),
+            ("StartingWith", Operator::StartsWith),
+            ("EndingWith", Operator::EndsWith),
+            ("Containing", Operator::Contains),
+            ("StartsWith", Operator::StartsWith),
+            ("EndsWith", Operator::EndsWith),
+            ("Contains", Operator::Contains),
+            ("LessThan", Operator::Lt),
+            ("NotNull", Operator::IsNotNull),
+            ("Between", Operator::Between),
+            ("Equals", Operator::Eq),
+            ("Before", Operator::Lt),
+            ("After", Operator::Gt),
+            ("NotIn", Operator::NotIn),
+            ("False", Operator::IsFalse),
+            ("True", Operator::IsTrue),
+            ("Null", Operator::IsNull),
+            ("Like", Operator::Like),
+            ("Not", Operator::Ne),
+            ("In", Operator::In),
+            ("", Operator::Eq),
+        ]
+    }
+}
+
+/// 查询的返回值
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuerySubject {
+    Find,
+    Count,
+    Exists,
+}
+
+/// 用于解析查询的字段信息
+pub(crate) struct QueryField<'a> {
+    pub name: &'a str,
+    pub kind: QueryFieldKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum QueryFieldKind {
+    String,
+    Bool,
+    Scalar,
+    Ref,
+    List,
+}
+
+/// 解析后的条件，字段为实体 fields 中的下标
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConditionNode {
+    Predicate { field: usize, op: Operator, params: Vec<String> },
+    And(Vec<ConditionNode>),
+    Or(Vec<ConditionNode>),
+}
+
+impl ConditionNode {
+    /// 按出现顺序排列的条件
+    pub fn predicates(&self) -> Vec<(usize, Operator, &[String])> {
+        match self {
+            ConditionNode::Predicate { field, op, params } => vec![(*field, *op, params.as_slice())],
+            ConditionNode::And(items) | ConditionNode::Or(items) => items.iter().flat_map(|c| c.predicates()).collect(),
+        }
+    }
+}
+
+/// 解析失败的原因，field 为出错的字段名
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum QueryError {
+    Syntax(String),
+    UnknownField(String),
+    Operator { field: String, op: Operator },
+}
+
+/// 解析后的查询
+#[derive(Debug, Clone)]
+pub(crate) struct ResolvedQuery {
+    pub subject: QuerySubject,
+    pub derived: bool,
+    pub condition: Option<ConditionNode>,
+    pub sort: Vec<(usize, Direction)>,
+    pub limit: Option<u32>,
+    pub unique: bool,
+}
+
+impl Query {
+    pub(crate) fn resolve(&self, fields: &[QueryField]) -> Result<ResolvedQuery, QueryError> {
+        if self.limit == Some(0) {
+            return Err(QueryError::Syntax("`limit` must be greater than 0".to_string()));
+        }
+        let mut names = ParamNames::default();
+        match self.conditions.as_ref() {
+            None if self.sort.is_empty() && self.limit.is_none() && !self.unique => derive(&self.name, fields, &mut names),
+            None => Err(QueryError::Syntax("`sort`, `limit` and `unique` require a `where` block".to_string())),
+            Some(conditions) => {
+                let mut items = vec![];
+                for condition in conditions {
+                    match condition {
+                        Condition::Predicate(predicate) => items.push(predicate_node(predicate, fields, &mut names)?),
+                        Condition::Any { any } => {
+                            let any = any
+                                .iter()
+                                .map(|p| predicate_node(p, fields, &mut names))
+                                .collect::<Result<Vec<_>, _>>()?;
+                            items.push(ConditionNode::Or(any));
+                        },
+                    }
+                }
+                let sort = self.sort
+                    .iter()
+                    .map(|s| field_index(&s.field, fields).map(|index| (index, s.direction)))
+                    .collect::<Result<Vec<_>, _>>()?;
+                Ok(ResolvedQuery {
+                    subject: QuerySubject::Find,
+                    derived: false,
+                    condition: (!items.is_empty()).then_some(ConditionNode::And(items)),
+                    sort,
+                    limit: self.limit,
+                    unique: self.unique,
+                })
+            },
+        }
+    }
+}
+
+/// 参数名去重，重复时添加序号
+#[derive(Default)]
+struct ParamNames {
+    used: Vec<String>,
+}
+
+impl ParamNames {
+    fn next(&mut self, name: &str) -> String {
+        let mut candidate = name.to_string();
+        let mut index = 2;
+        while self.used.contains(&candidate) {
+            candidate = format!("{}{}", name, index);
+            index += 1;
+        }
+        self.used.push(candidate.clone());
+        candidate
+    }
+
+    fn params(&mut self, name: &str, op: Operator) -> Vec<String> {
+        match op.arity() {
+            0 => vec![],
+            1 => vec![self.next(name)],
+            _ => vec![self.next(&format!("{}From", name)), self.next(&format!("{}To", name))],
+        }
+    }
+}
+
+fn field_index(name: &str, fields: &[QueryField]) -> Result<usize, QueryError> {
+    fields
+        .iter()
+        .position(|f| f.name == name)
+        .ok_or_else(|| QueryError::UnknownField(name.to_string()))
+}
+
+/// 检查操作符是否适用于字段
+pub(crate) fn check_operator(field: &QueryField, op: Operator) -> Result<(), QueryError> {
+    let valid = match field.kind {
+        QueryFieldKind::List => false,
+        QueryFieldKind::String => !op.is_bool(),
+        QueryFieldKind::Bool => !op.is_text() && !matches!(op, Operator::Gt | Operator::Gte | Operator::Lt | Operator::Lte | Operator::Between),
+        QueryFieldKind::Scalar => !op.is_text() && !op.is_bool(),
+        QueryFieldKind::Ref => matches!(op, Operator::Eq | Operator::Ne | Operator::In | Operator::NotIn | Operator::IsNull | Operator::IsNotNull),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(QueryError::Operator { field: field.name.to_string(), op })
+    }
+}
+
+fn predicate_node(predicate: &Predicate, fields: &[QueryField], names: &mut ParamNames) -> Result<ConditionNode, QueryError> {
+    let index = field_index(&predicate.field, fields)?;
+    check_operator(&fields[index], predicate.op)?;
+    let name = predicate.param.as_deref().unwrap_or(&predicate.field);
+    Ok(ConditionNode::Predicate { field: index, op: predicate.op, params: names.params(name, predicate.op) })
+}
+
+/// 按 Spring Data 的规则解析方法名，如 `findTop10ByNameAndAgeGreaterThanOrderByAgeDesc`
+fn derive(name: &str, fields: &[QueryField], names: &mut ParamNames) -> Result<ResolvedQuery, QueryError> {
+    let (subject, rest) = [
+        ("find", QuerySubject::Find),
+        ("read", QuerySubject::Find),
+        ("get", QuerySubject::Find),
+        ("query", QuerySubject::Find),
+        ("count", QuerySubject::Count),
+        ("exists", QuerySubject::Exists),
+    ]
+        .iter()
+        .find_map(|(prefix, subject)| name.strip_prefix(prefix).map(|rest| (*subject, rest)))
+        .ok_or_else(|| QueryError::Syntax(format!(
+            "`{}` must start with `find`, `read`, `get`, `query`, `count` or `exists`, or declare a `where` block", name)))?;
+    // 先找 OrderBy，条件的 By 只在 OrderBy 之前查找，避免匹配 OrderBy 中的 By
+    let (head, order) = match keyword_position(rest, "OrderBy") {
+        Some(position) => (&rest[..position], Some(&rest[position + 7..])),
+        None => (rest, None),
+    };
+    // 只有 OrderBy 没有 By 时为无条件查询，如 `findAllOrderByName`
+    let (subject_part, criteria) = match (keyword_position(head, "By"), order) {
+        (Some(by), _) => (&head[..by], &head[by + 2..]),
+        (None, Some(_)) => (head, ""),
+        (None, None) => return Err(QueryError::Syntax(format!("`{}` has no `By` before its criteria", name))),
+    };
+    let limit = limit(subject_part);
+
+    let mut groups = vec![];
+    for group in split_keyword(criteria, "Or") {
+        let mut items = vec![];
+        for part in split_keyword(group, "And") {
+            items.push(derived_predicate(part, fields, names)?);
+        }
+        groups.push(match items.len() {
+            1 => items.remove(0),
+            _ => ConditionNode::And(items),
+        });
+    }
+    let condition = match groups.len() {
+        0 => None,
+        1 => Some(groups.remove(0)),
+        _ => Some(ConditionNode::Or(groups)),
+    };
+
+    let mut sort = vec![];
+    let mut order = order.unwrap_or("");
+    while !order.is_empty() {
+        let (index, rest) = longest_field(order, fields)
+            .ok_or_else(|| QueryError::UnknownField(naming_camel(order)))?;
+        let (direction, rest) = if let Some(rest) = rest.strip_prefix("Desc") {
+            (Direction::Desc, rest)
+        } else {
+            (Direction::Asc, rest.strip_prefix("Asc").unwrap_or(rest))
+        };
+        sort.push((index, direction));
+        order = rest;
+    }
+
+    Ok(ResolvedQuery {
+        subject,
+        derived: true,
+        condition,
+        sort,
+        limit,
+        unique: subject == QuerySubject::Find && limit == Some(1),
+    })
+}
+
+fn derived_predicate(part: &str, fields: &[QueryField], names: &mut ParamNames) -> Result<ConditionNode, QueryError> {
+    let mut candidates: Vec<(usize, &QueryField)> = fields
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| part.starts_with(&pascal(f.name)))
+        .collect();
+    candidates.sort_by_key(|(_, f)| std::cmp::Reverse(f.name.len()));
+    let (index, field) = match candidates.first() {
+        Some(candidate) => *candidate,
+        None => return Err(QueryError::UnknownField(naming_camel(part))),
+    };
+    let rest = &part[pascal(field.name).len()..];
+    let rest = rest.strip_prefix("Is").unwrap_or(rest);
+    let op = Operator::keywords()
+        .iter()
+        .find(|(keyword, _)| *keyword == rest)
+        .map(|(_, op)| *op)
+        .ok_or_else(|| QueryError::Syntax(format!("unknown condition `{}` on field `{}`", rest, field.name)))?;
+    check_operator(field, op)?;
+    Ok(ConditionNode::Predicate { field: index, op, params: names.params(field.name, op) })
+}
+
+/// `First`、`Top` 后面的数量，没有数量时为 1
+fn limit(subject: &str) -> Option<u32> {
+    let position = subject.find("First").map(|p| p + 5).or(subject.find("Top").map(|p| p + 3))?;
+    let digits: String = subject[position..].chars().take_while(|c| c.is_ascii_digit()).collect();
+    Some(digits.parse().unwrap_or(1))
+}
+
+/// 关键字后面必须是大写字母或者结尾，避免匹配字段名中的片段
+fn keyword_position(text: &str, keyword: &str) -> Option<usize> {
+    let mut from = 0;
+    while let Some(position) = text[from..].find(keyword).map(|p| p + from) {
+        let next = text[position + keyword.len()..].chars().next();
+        if next.is_none_or(|c| c.is_ascii_uppercase()) {
+            return Some(position);
+        }
+        from = position + keyword.len();
+    }
+    None
+}
+
+fn split_keyword<'a>(text: &'a str, keyword: &str) -> Vec<&'a str> {
+    let mut parts = vec![];
+    let mut rest = text;
+    // 第一个字符属于字段名，不作为关键字
+    while let Some(position) = rest.get(1..).and_then(|r| keyword_position(r, keyword)).map(|p| p + 1) {
+        parts.push(&rest[..position]);
+        rest = &rest[position + keyword.len()..];
+    }
+    if !rest.is_empty() {
+        parts.push(rest);
+    }
+    parts
+}
+
+fn longest_field<'a>(text: &'a str, fields: &[QueryField]) -> Option<(usize, &'a str)> {
+    fields
+        .iter()
+        .enumerate()
+        .filter(|(_, f)| text.starts_with(&pascal(f.name)))
+        .max_by_key(|(_, f)| f.name.len())
+        .map(|(index, f)| (index, &text[pascal(f.name).len()..]))
+}
+
+fn pascal(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// 方法名中无法识别的片段，转换为字段名的形式用于提示
+fn naming_camel(text: &str) -> String {
+    let end = text
+        .get(1..)
+        .and_then(|rest| rest.find(|c: char| c.is_ascii_uppercase()))
+        .map(|p| p + 1)
+        .unwrap_or(text.len());
+    let mut chars = text[..end].chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields() -> Vec<QueryField<'static>> {
+        vec![
+            QueryField { name: "name", kind: QueryFieldKind::String },
+            QueryField { name: "age", kind: QueryFieldKind::Scalar },
+            QueryField { name: "active", kind: QueryFieldKind::Bool },
+        ]
+    }
+
+    fn derive_query(name: &str) -> ResolvedQuery {
+        derive(name, &fields(), &mut ParamNames::default()).unwrap()
+    }
+
+    #[test]
+    fn order_by_without_criteria() {
+        let query = derive_query("findAllOrderByName");
+        assert_eq!(query.subject, QuerySubject::Find);
+        assert_eq!(query.condition, None);
+        assert_eq!(query.sort, vec![(0, Direction::Asc)]);
+        assert_eq!(query.limit, None);
+    }
+
+    #[test]
+    fn criteria_with_limit_and_order_by() {
+        let query = derive_query("findTop3ByAgeGreaterThanOrderByNameDesc");
+        assert_eq!(query.limit, Some(3));
+        assert!(!query.unique);
+        assert_eq!(query.condition, Some(ConditionNode::Predicate {
+            field: 1,
+            op: Operator::Gt,
+            params: vec!["age".to_string()],
+        }));
+        assert_eq!(query.sort, vec![(0, Direction::Desc)]);
+    }
+
+    #[test]
+    fn count_with_boolean_criteria() {
+        let query = derive_query("countByActiveTrue");
+        assert_eq!(query.subject, QuerySubject::Count);
+        assert_eq!(query.condition, Some(ConditionNode::Predicate {
+            field: 2,
+            op: Operator::IsTrue,
+            params: vec![],
+        }));
+        assert!(query.sort.is_empty());
+    }
+
+    #[test]
+    fn missing_by() {
+        assert!(derive("findAll", &fields(), &mut ParamNames::default()).is_err());
+    }
+}
@@ -0,0 +1,115 @@
+//! 实体的初始化数据，在 data.yaml 中通过 `seed:` 声明
+//!
+//! ```yaml
+//! - name: role
+//!   table: role
+//!   fields: ...
+//!   seed:
+//!   - id: 1
+//!     name: admin
+//! ```
+
+use serde_yaml::Value;
+
+use super::{common::DataType, data::Field};
+
+/// 校验并转换后的 seed 值，ref 保存为被引用实体的主键
+#[derive(Debug, Clone, PartialEq)]
+pub enum SeedValue {
+    Null,
+    Bool(bool),
+    Int(i64),
+    Float(f64),
+    String(String),
+    /// `yyyy-MM-dd HH:mm:ss`
+    DateTime(String),
+    List(Vec<SeedValue>),
+}
+
+/// 一行 seed 数据，values 按字段下标保存，不包括主键
+#[derive(Debug, Clone, PartialEq)]
+pub struct SeedRow {
+    pub id: i64,
+    pub values: Vec<(usize, SeedValue)>,
+}
+
+impl SeedRow {
+    pub fn value(&self, field: usize) -> Option<&SeedValue> {
+        self.values.iter().find(|(index, _)| *index == field).map(|(_, value)| value)
+    }
+}
+
+/// 按字段类型转换 seed 值，失败时返回期望的类型
+pub(crate) fn seed_value(field: &Field, value: &Value) -> Result<SeedValue, String> {
+    match (&field.field_type, value) {
+        (DataType::List, Value::Sequence(values)) => {
+            let list = field.list.as_ref().ok_or_else(|| "list".to_string())?;
+            values
+                .iter()
+                .map(|value| scalar_value(&list.list_type, value))
+                .collect::<Result<Vec<_>, _>>()
+                .map(SeedValue::List)
+                .map_err(|expected| format!("list of {}", expected))
+        },
+        (DataType::List, Value::Null) => Ok(SeedValue::List(vec![])),
+        (DataType::List, _) => Err("list".to_string()),
+        (data_type, value) => scalar_value(data_type, value),
+    }
+}
+
+fn scalar_value(data_type: &DataType, value: &Value) -> Result<SeedValue, String> {
+    let expected = || match data_type {
+        DataType::Ref => "id of the referenced row".to_string(),
+        DataType::Object => "string".to_string(),
+        _ => serde_yaml::to_string(data_type).map(|s| s.trim().to_string()).unwrap_or_default(),
+    };
+    let integer = |min: i64, max: i64| value
+        .as_i64()
+        .filter(|v| (min..=max).contains(v))
+        .map(SeedValue::Int)
+        .ok_or_else(expected);
+    if value.is_null() {
+        return match data_type {
+            DataType::AutoId => Err(expected()),
+            _ => Ok(SeedValue::Null),
+        };
+    }
+    match data_type {
+        DataType::AutoId | DataType::Ref | DataType::Int64 => integer(i64::MIN, i64::MAX),
+        DataType::Int8 => integer(i8::MIN as i64, i8::MAX as i64),
+        DataType::Int16 => integer(i16::MIN as i64, i16::MAX as i64),
+        DataType::Int32 => integer(i32::MIN as i64, i32::MAX as i64),
+        DataType::Float32 | DataType::Float64 => value.as_f64().map(SeedValue::Float).ok_or_else(expected),
+        DataType::Bool => value.as_bool().map(SeedValue::Bool).ok_or_else(expected),
+        DataType::String | DataType::Object => value.as_str().map(|s| SeedValue::String(s.to_string())).ok_or_else(expected),
+        DataType::DateTime => value.as_str().and_then(date_time).map(SeedValue::DateTime).ok_or_else(expected),
+        DataType::List => Err(expected()),
+    }
+}
+
+/// 支持 `yyyy-MM-dd`、`yyyy-MM-dd HH:mm[:ss]` 以及 `T` 分隔的写法
+fn date_time(value: &str) -> Option<String> {
+    let (date, time) = match value.split_once([' ', 'T']) {
+        Some((date, time)) => (date, time),
+        None => (value, "00:00:00"),
+    };
+    let date: Vec<u32> = numbers(date, '-', 3, 3)?;
+    let mut time: Vec<u32> = numbers(time, ':', 2, 3)?;
+    time.resize(3, 0);
+    let valid = date[0] > 0 && date[0] <= 9999
+        && (1..=12).contains(&date[1])
+        && (1..=31).contains(&date[2])
+        && time[0] < 24 && time[1] < 60 && time[2] < 60;
+    valid.then(|| format!("{:04}-{:02}-{:02} {:02}:{:02}:{:02}", date[0], date[1], date[2], time[0], time[1], time[2]))
+}
+
+fn numbers(value: &str, separator: char, min: usize, max: usize) -> Option<Vec<u32>> {
+    let parts: Vec<&str> = value.split(separator).collect();
+    if parts.len() < min || parts.len() > max {
+        return None;
+    }
+    parts
+        .iter()
+        .map(|part| part.parse().ok().filter(|_| part.chars().all(|c| c.is_ascii_digit())))
+        .collect()
+}
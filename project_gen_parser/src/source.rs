@@ -0,0 +1,76 @@
+use project_gen_core::diagnostic::{Label, Span};
+
+/// yaml 源文件，用于给诊断信息定位
+/// serde_yaml 反序列化后不保留位置，这里按 `key: value` 在文本中查找
+pub struct YamlSource {
+    pub path: String,
+    pub text: String,
+}
+
+impl YamlSource {
+    pub fn new(path: &str, text: String) -> YamlSource {
+        YamlSource {
+            path: path.to_owned(),
+            text,
+        }
+    }
+
+    /// 从 from 开始查找 `key: value`，返回 value 的位置
+    /// 支持列表项前缀 `- ` 以及带引号的值
+    pub fn find(&self, key: &str, value: &str, from: usize) -> Option<Span> {
+        let mut offset = 0;
+        for line in self.text.split_inclusive('\n') {
+            let line_start = offset;
+            offset += line.len();
+            if line_start < from {
+                continue;
+            }
+            let trimmed = line.trim_start_matches([' ', '-']);
+            let rest = match trimmed.strip_prefix(key).and_then(|r| r.strip_prefix(':')) {
+                Some(rest) => rest,
+                None => continue,
+            };
+            let value_text = rest.trim_start();
+            let value_start = line_start + (line.len() - value_text.len());
+            let value_text = value_text.split('#').next().unwrap_or("").trim_end();
+            let unquoted = value_text.trim_matches(|c| c == '"' || c == '\'');
+            if unquoted == value {
+                let quote = (value_text.len() - unquoted.len()) / 2;
+                return Some(Span::new(value_start + quote, value_start + quote + unquoted.len()));
+            }
+        }
+        None
+    }
+
+    /// 从 from 开始查找 `key:`，返回 key 的位置，用于值为列表或者映射的 key
+    pub fn find_key(&self, key: &str, from: usize) -> Option<Span> {
+        let mut offset = 0;
+        for line in self.text.split_inclusive('\n') {
+            let line_start = offset;
+            offset += line.len();
+            if line_start < from {
+                continue;
+            }
+            let trimmed = line.trim_start_matches([' ', '-']);
+            if trimmed.strip_prefix(key).is_some_and(|r| r.starts_with(':')) {
+                let key_start = line_start + (line.len() - trimmed.len());
+                return Some(Span::new(key_start, key_start + key.len()));
+            }
+        }
+        None
+    }
+
+    /// 第 index 个字节所在的单词的位置
+    pub fn word_at(&self, index: usize) -> Span {
+        let start = index.min(self.text.len());
+        let end = self.text[start..]
+            .find(|c: char| c.is_whitespace() || c == ':')
+            .map(|i| start + i)
+            .unwrap_or(self.text.len());
+        Span::new(start, end)
+    }
+
+    pub fn label(&self, span: Option<Span>, message: &str) -> Option<Label> {
+        span.map(|span| Label::new(&self.path, span, message))
+    }
+}
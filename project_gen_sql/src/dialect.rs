@@ -0,0 +1,39 @@
+/// 数据库方言，决定标识符的引用方式以及字面量的写法
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    #[default]
+    MySql,
+    PostgreSql,
+    H2,
+}
+
+impl Dialect {
+    pub fn quote(&self, identifier: &str) -> String {
+        match self {
+            Dialect::MySql => format!("`{}`", identifier.replace('`', "``")),
+            Dialect::PostgreSql => format!("\"{}\"", identifier.replace('"', "\"\"")),
+            // H2 中带引号的标识符区分大小写，与未加引号的建表语句不一致
+            Dialect::H2 => identifier.to_string(),
+        }
+    }
+
+    /// 字符串字面量
+    pub fn string(&self, value: &str) -> String {
+        match self {
+            Dialect::MySql => format!("'{}'", value.replace('\\', "\\\\").replace('\'', "''")),
+            Dialect::PostgreSql | Dialect::H2 => format!("'{}'", value.replace('\'', "''")),
+        }
+    }
+
+    pub fn bool(&self, value: bool) -> &'static str {
+        if value { "TRUE" } else { "FALSE" }
+    }
+
+    /// value 为 `yyyy-MM-dd HH:mm:ss`
+    pub fn date_time(&self, value: &str) -> String {
+        match self {
+            Dialect::MySql => self.string(value),
+            Dialect::PostgreSql | Dialect::H2 => format!("TIMESTAMP {}", self.string(value)),
+        }
+    }
+}
@@ -0,0 +1,7 @@
+mod dialect;
+mod sql_schema_generator;
+mod sql_seed_generator;
+
+pub use dialect::Dialect;
+pub use sql_schema_generator::SqlSchemaGenerator;
+pub use sql_seed_generator::SqlSeedGenerator;
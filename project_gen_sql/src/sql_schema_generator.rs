@@ -0,0 +1,140 @@
+use project_gen_parser::{
+    Cardinality,
+    DatasourceNode,
+    EntityNode,
+    FieldNode,
+    FieldRole,
+    FieldType,
+    Model,
+    ScalarType,
+};
+
+use super::Dialect;
+
+/// 建表语句目前只支持 MySQL
+const DIALECT: Dialect = Dialect::MySql;
+
+/// 根据语义模型生成建表语句，每个数据源一个脚本
+pub struct SqlSchemaGenerator {}
+
+impl Default for SqlSchemaGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SqlSchemaGenerator {
+    pub fn new() -> SqlSchemaGenerator {
+        SqlSchemaGenerator {}
+    }
+
+    /// 数据源中所有实体的建表语句
+    pub fn generate(&self, model: &Model, datasource: &DatasourceNode) -> String {
+        let mut tables = vec![];
+        for entity in datasource.entities.iter().map(|id| model.entity(*id)) {
+            tables.push(self.create_table(model, entity));
+            for field in &entity.fields {
+                if let FieldType::List(element) = &field.field_type {
+                    if let FieldType::Scalar(scalar) = element.as_ref() {
+                        tables.push(self.create_collection_table(entity, field, *scalar));
+                    }
+                }
+            }
+        }
+        tables.join("\n")
+    }
+
+    fn create_table(&self, model: &Model, entity: &EntityNode) -> String {
+        let mut columns: Vec<String> = vec![];
+        let mut names: Vec<&str> = vec![];
+        for (index, field) in entity.fields.iter().enumerate() {
+            let definition = match &field.field_type {
+                _ if index == entity.id_field => format!("{} NOT NULL AUTO_INCREMENT", column_type(ScalarType::Id)),
+                _ if field.role == FieldRole::Deleted => format!("{} NOT NULL DEFAULT FALSE", column_type(ScalarType::Bool)),
+                _ if field.role == FieldRole::Version => format!("{} NOT NULL DEFAULT 0", column_type(ScalarType::Int64)),
+                FieldType::Scalar(scalar) => column_type(*scalar).to_string(),
+                FieldType::Entity(to) => column_type(id_type(model.entity(*to))).to_string(),
+                // 列表保存在关联表或者单独的表中
+                FieldType::List(_) => continue,
+            };
+            columns.push(column(&field.column, &definition, field.documentation.db_comment()));
+            names.push(&field.column);
+        }
+        // 一对多关联的外键在当前表中
+        for relation in model.incoming(entity.id).filter(|r| r.cardinality == Cardinality::OneToMany) {
+            if names.contains(&relation.join_column.as_str()) {
+                continue;
+            }
+            let from = model.entity(relation.from);
+            let comment = from.documentation.db_comment().map(|c| format!("{} id", c));
+            columns.push(column(&relation.join_column, column_type(id_type(from)), comment.as_deref()));
+            names.push(&relation.join_column);
+        }
+        columns.push(format!("    PRIMARY KEY ({})", quote(&entity.id_field().column)));
+
+        format!(
+            "CREATE TABLE IF NOT EXISTS {} (\n{}\n){};\n",
+            quote(&entity.table),
+            columns.join(",\n"),
+            table_options(entity.documentation.db_comment()))
+    }
+
+    fn create_collection_table(&self, entity: &EntityNode, field: &FieldNode, scalar: ScalarType) -> String {
+        let columns = [
+            column(&entity.collection_join_column(), &format!("{} NOT NULL", column_type(id_type(entity))), None),
+            column(&field.column, column_type(scalar), field.documentation.db_comment()),
+        ];
+        format!(
+            "CREATE TABLE IF NOT EXISTS {} (\n{}\n){};\n",
+            quote(&entity.collection_table(field)),
+            columns.join(",\n"),
+            table_options(field.documentation.db_comment()))
+    }
+}
+
+fn id_type(entity: &EntityNode) -> ScalarType {
+    match entity.id_field().field_type {
+        FieldType::Scalar(scalar) => scalar,
+        _ => ScalarType::Id,
+    }
+}
+
+fn column_type(scalar: ScalarType) -> &'static str {
+    match scalar {
+        ScalarType::Id => "BIGINT",
+        ScalarType::String => "VARCHAR(255)",
+        ScalarType::Int8 => "TINYINT",
+        ScalarType::Int16 => "SMALLINT",
+        ScalarType::Int32 => "INT",
+        ScalarType::Int64 => "BIGINT",
+        ScalarType::Float32 => "FLOAT",
+        ScalarType::Float64 => "DOUBLE",
+        ScalarType::Bool => "BIT(1)",
+        ScalarType::DateTime => "DATETIME",
+        ScalarType::Object => "JSON",
+    }
+}
+
+fn column(name: &str, definition: &str, comment: Option<&str>) -> String {
+    match comment {
+        Some(comment) => format!("    {} {} COMMENT {}", quote(name), definition, literal(comment)),
+        None => format!("    {} {}", quote(name), definition),
+    }
+}
+
+/// 使用 utf8mb4 保证中文等字符可以完整保存
+fn table_options(comment: Option<&str>) -> String {
+    let mut options = String::from(" ENGINE=InnoDB DEFAULT CHARSET=utf8mb4");
+    if let Some(comment) = comment {
+        options.push_str(&format!(" COMMENT={}", literal(comment)));
+    }
+    options
+}
+
+fn quote(identifier: &str) -> String {
+    DIALECT.quote(identifier)
+}
+
+fn literal(value: &str) -> String {
+    DIALECT.string(value)
+}
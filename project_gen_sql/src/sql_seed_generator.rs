@@ -0,0 +1,150 @@
+use project_gen_parser::{
+    DatasourceNode,
+    EntityNode,
+    FieldType,
+    Model,
+    SeedRow,
+    SeedValue,
+};
+
+use super::Dialect;
+
+/// 根据实体的 seed 生成初始化数据脚本
+/// 按主键覆盖已有的行，脚本可以重复执行，适合作为 Flyway 的 repeatable migration
+pub struct SqlSeedGenerator {
+    pub dialect: Dialect,
+}
+
+impl Default for SqlSeedGenerator {
+    fn default() -> Self {
+        Self::new(Dialect::default())
+    }
+}
+
+impl SqlSeedGenerator {
+    pub fn new(dialect: Dialect) -> SqlSeedGenerator {
+        SqlSeedGenerator { dialect }
+    }
+
+    /// 数据源中所有实体的初始化数据，列表字段在所有的行插入之后再写入
+    pub fn generate(&self, model: &Model, datasource: &DatasourceNode) -> String {
+        let entities: Vec<&EntityNode> = datasource.entities
+            .iter()
+            .map(|id| model.entity(*id))
+            .filter(|entity| !entity.seed.is_empty())
+            .collect();
+        let mut statements = vec![];
+        for entity in &entities {
+            let mut comment = format!("-- {}", entity.table);
+            if let Some(label) = entity.documentation.label.as_ref() {
+                comment.push_str(&format!(" {}", label));
+            }
+            statements.push(comment);
+            for row in &entity.seed {
+                statements.push(self.upsert(entity, row));
+            }
+            statements.push(String::new());
+        }
+        for entity in &entities {
+            for row in &entity.seed {
+                for (index, value) in &row.values {
+                    if let SeedValue::List(values) = value {
+                        statements.extend(self.list(model, entity, row, *index, values));
+                    }
+                }
+            }
+        }
+        let mut script = statements.join("\n");
+        if !script.ends_with('\n') {
+            script.push('\n');
+        }
+        script
+    }
+
+    fn upsert(&self, entity: &EntityNode, row: &SeedRow) -> String {
+        let id_column = self.dialect.quote(&entity.id_field().column);
+        let mut columns = vec![id_column.clone()];
+        let mut values = vec![row.id.to_string()];
+        for (index, value) in &row.values {
+            if matches!(value, SeedValue::List(_)) {
+                continue;
+            }
+            columns.push(self.dialect.quote(&entity.fields[*index].column));
+            values.push(self.literal(value));
+        }
+        let table = self.dialect.quote(&entity.table);
+        // 主键之外的列，已存在时更新
+        let updated = &columns[1..];
+        let all_columns = columns.join(", ");
+        let all_values = values.join(", ");
+        match self.dialect {
+            Dialect::MySql if updated.is_empty() => format!("INSERT IGNORE INTO {} ({}) VALUES ({});", table, all_columns, all_values),
+            Dialect::MySql => {
+                let updates: Vec<String> = updated.iter().map(|c| format!("{} = VALUES({})", c, c)).collect();
+                format!("INSERT INTO {} ({}) VALUES ({}) ON DUPLICATE KEY UPDATE {};", table, all_columns, all_values, updates.join(", "))
+            },
+            Dialect::PostgreSql if updated.is_empty() => format!(
+                "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT ({}) DO NOTHING;", table, all_columns, all_values, id_column),
+            Dialect::PostgreSql => {
+                let updates: Vec<String> = updated.iter().map(|c| format!("{} = EXCLUDED.{}", c, c)).collect();
+                format!("INSERT INTO {} ({}) VALUES ({}) ON CONFLICT ({}) DO UPDATE SET {};", table, all_columns, all_values, id_column, updates.join(", "))
+            },
+            Dialect::H2 => format!("MERGE INTO {} ({}) KEY ({}) VALUES ({});", table, all_columns, id_column, all_values),
+        }
+    }
+
+    /// 基础类型的列表先清空再插入，ref 列表更新被引用行的外键
+    fn list(&self, model: &Model, entity: &EntityNode, row: &SeedRow, index: usize, values: &[SeedValue]) -> Vec<String> {
+        let field = &entity.fields[index];
+        let element = match &field.field_type {
+            FieldType::List(element) => element.as_ref(),
+            _ => return vec![],
+        };
+        let mut statements = vec![];
+        match element {
+            FieldType::Entity(to) => {
+                let relation = match model.outgoing(entity.id).find(|r| r.field == index) {
+                    Some(relation) => relation,
+                    None => return vec![],
+                };
+                let target = model.entity(*to);
+                if !values.is_empty() {
+                    let ids: Vec<String> = values.iter().map(|v| self.literal(v)).collect();
+                    statements.push(format!(
+                        "UPDATE {} SET {} = {} WHERE {} IN ({});",
+                        self.dialect.quote(&target.table),
+                        self.dialect.quote(&relation.join_column),
+                        row.id,
+                        self.dialect.quote(&target.id_field().column),
+                        ids.join(", ")));
+                }
+            },
+            _ => {
+                let table = self.dialect.quote(&entity.collection_table(field));
+                let join_column = self.dialect.quote(&entity.collection_join_column());
+                statements.push(format!("DELETE FROM {} WHERE {} = {};", table, join_column, row.id));
+                for value in values {
+                    statements.push(format!(
+                        "INSERT INTO {} ({}, {}) VALUES ({}, {});",
+                        table,
+                        join_column,
+                        self.dialect.quote(&field.column),
+                        row.id,
+                        self.literal(value)));
+                }
+            },
+        }
+        statements
+    }
+
+    fn literal(&self, value: &SeedValue) -> String {
+        match value {
+            SeedValue::Null | SeedValue::List(_) => "NULL".to_string(),
+            SeedValue::Bool(value) => self.dialect.bool(*value).to_string(),
+            SeedValue::Int(value) => value.to_string(),
+            SeedValue::Float(value) => value.to_string(),
+            SeedValue::String(value) => self.dialect.string(value),
+            SeedValue::DateTime(value) => self.dialect.date_time(value),
+        }
+    }
+}
@@ -0,0 +1,17 @@
+mod ts_source_code;
+mod ts_source_writer;
+mod ts_data_generator;
+
+pub use ts_source_code::{
+    TsSourceCode,
+    TsModule,
+    TsImport,
+    TsInterface,
+    TsProperty,
+    TsConst,
+    TsTypeAlias,
+    TsDeclaration,
+    TsSourceStructure
+};
+pub use ts_source_writer::TsSourceCodeWriter;
+pub use ts_data_generator::TsDataGenerator;
@@ -0,0 +1,302 @@
+use project_gen_core::{Result, naming};
+use project_gen_parser::{
+    ApiNode,
+    Documentation,
+    DtoNode,
+    EntityId,
+    EntityNode,
+    FieldType,
+    ListingNode,
+    Model,
+    Operator,
+    ScalarType,
+};
+
+use super::{
+    TsConst,
+    TsDeclaration,
+    TsImport,
+    TsInterface,
+    TsModule,
+    TsProperty,
+    TsSourceCode,
+    TsSourceCodeWriter,
+    TsSourceStructure,
+    TsTypeAlias,
+};
+
+const MODEL_DIRECTORY: &str = "model";
+const DTO_DIRECTORY: &str = "dto";
+const API_DIRECTORY: &str = "api";
+/// 与 Java 的 Service 中未指定 size 时的默认值一致
+const DEFAULT_PAGE_SIZE: u32 = 20;
+/// 分页返回值所在的模块
+const PAGE_MODULE: &str = "common/page";
+
+/// 根据语义模型生成实体以及 DTO 对应的 TS 类型，表单使用的字段标签，
+/// 以及列表请求的参数和返回值类型
+pub struct TsDataGenerator {}
+
+impl Default for TsDataGenerator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TsDataGenerator {
+    pub fn new() -> TsDataGenerator {
+        TsDataGenerator {}
+    }
+
+    pub fn write(&self, structure: &TsSourceStructure, model: &Model) -> Result<()> {
+        let mut writer = TsSourceCodeWriter::new();
+        writer.write(structure, self.generate(model))
+    }
+
+    pub fn generate(&self, model: &Model) -> TsSourceCode {
+        let mut source_code = TsSourceCode::new();
+        for entity in &model.entities {
+            source_code.add_module(self.generate_entity(model, entity));
+        }
+        for dto in &model.dtos {
+            source_code.add_module(self.generate_dto(model, dto));
+        }
+        let mut paged = false;
+        for api in &model.apis {
+            let listings: Vec<(&str, &ListingNode)> = api.requests
+                .iter()
+                .filter_map(|r| Some((r.name.as_str(), r.listing.as_ref()?)))
+                .collect();
+            if listings.is_empty() {
+                continue;
+            }
+            paged |= listings.iter().any(|(_, listing)| listing.paged);
+            source_code.add_module(self.generate_api(model, api, &listings));
+        }
+        if paged {
+            source_code.add_module(self.generate_page());
+        }
+        source_code
+    }
+
+    /// 接口的列表请求类型所在模块相对源码目录的路径
+    pub fn api_module(&self, api: &ApiNode) -> String {
+        format!("{}/{}", API_DIRECTORY, module_name(&api.name))
+    }
+
+    /// DTO 所在模块相对源码目录的路径
+    pub fn dto_module(&self, dto: &DtoNode) -> String {
+        format!("{}/{}", DTO_DIRECTORY, module_name(&dto.name))
+    }
+
+    /// 实体所在模块相对源码目录的路径
+    pub fn entity_module(&self, model: &Model, id: EntityId) -> String {
+        format!("{}/{}", MODEL_DIRECTORY, module_name(&model.entity(id).name))
+    }
+
+    /// 类型对应的 TS 类型
+    pub fn ts_type(&self, model: &Model, field_type: &FieldType) -> String {
+        match field_type {
+            FieldType::Scalar(scalar) => scalar_type(*scalar).to_string(),
+            FieldType::Entity(id) => naming::pascal_case(&model.entity(*id).name),
+            FieldType::List(element) => format!("{}[]", self.ts_type(model, element)),
+        }
+    }
+
+    fn generate_entity(&self, model: &Model, entity: &EntityNode) -> TsModule {
+        let fields = entity.fields.iter().map(|f| (f.name.as_str(), &f.field_type, &f.documentation)).collect();
+        self.generate_interface(model, &self.entity_module(model, entity.id), &entity.name, &entity.documentation, fields)
+    }
+
+    fn generate_dto(&self, model: &Model, dto: &DtoNode) -> TsModule {
+        let fields = dto.fields.iter().map(|f| (f.name.as_str(), &f.field_type, &f.documentation)).collect();
+        self.generate_interface(model, &self.dto_module(dto), &dto.name, &dto.documentation, fields)
+    }
+
+    /// 列表请求的参数以及返回值，参数与 Java 中的请求类一致
+    fn generate_api(&self, model: &Model, api: &ApiNode, listings: &[(&str, &ListingNode)]) -> TsModule {
+        let path = self.api_module(api);
+        let mut module = TsModule::new(&path);
+        if listings.iter().any(|(_, listing)| listing.paged) {
+            module.add_import(TsImport::new(vec!["Page"], &relative_path(&path, PAGE_MODULE)));
+        }
+        let mut imported: Vec<EntityId> = vec![];
+        for (_, listing) in listings {
+            if !imported.contains(&listing.entity) {
+                imported.push(listing.entity);
+                let from = relative_path(&path, &self.entity_module(model, listing.entity));
+                module.add_import(TsImport::new(vec![&naming::pascal_case(&model.entity(listing.entity).name)], &from));
+            }
+        }
+
+        for (request, listing) in listings {
+            let entity = model.entity(listing.entity);
+            let name = naming::pascal_case(&format!("{}_{}", api.name, request));
+            let mut interface = TsInterface::new(&format!("{}Request", name));
+            interface.set_doc(&format!("{}.{} 请求的参数", api.name, request));
+            for filter in &listing.filters {
+                let field = &entity.fields[filter.field];
+                let value_type = match field.field_type.entity() {
+                    Some(target) => self.ts_type(model, &model.entity(target).id_field().field_type),
+                    None => self.ts_type(model, &field.field_type),
+                };
+                let label = field.documentation.label_or(&field.name);
+                for (index, param) in filter.params.iter().enumerate() {
+                    let property_type = match filter.op {
+                        _ if filter.op.arity() == 0 => "boolean".to_string(),
+                        Operator::In | Operator::NotIn => format!("{}[]", value_type),
+                        _ => value_type.clone(),
+                    };
+                    let mut property = TsProperty::new(param, &property_type);
+                    property.set_optional(true);
+                    property.set_doc(&filter.op.filter_doc(label, index));
+                    interface.add_property(property);
+                }
+            }
+            if listing.paged {
+                for (property_name, doc) in [("page", "页码，从 0 开始".to_string()), ("size", format!("每页数量，默认 {}", DEFAULT_PAGE_SIZE))] {
+                    let mut property = TsProperty::new(property_name, "number");
+                    property.set_optional(true);
+                    property.set_doc(&doc);
+                    interface.add_property(property);
+                }
+            }
+            if !listing.sortable.is_empty() {
+                let mut property = TsProperty::new("sort", "string[]");
+                property.set_optional(true);
+                property.set_doc("排序，格式为 `field[,asc|desc]`");
+                interface.add_property(property);
+            }
+            module.add_declaration(TsDeclaration::Interface(interface));
+
+            if !listing.sortable.is_empty() {
+                let fields: Vec<String> = listing.sortable.iter().map(|f| literal(&entity.fields[*f].name)).collect();
+                let mut sortable = TsConst::new(&format!("{}Sortable", naming::camel_case(&name)), None, &format!("[{}] as const", fields.join(", ")));
+                sortable.set_doc(&format!("{}.{} 可排序的字段", api.name, request));
+                module.add_declaration(TsDeclaration::Const(sortable));
+            }
+
+            let entity_type = naming::pascal_case(&entity.name);
+            let response = match listing.paged {
+                true => format!("Page<{}>", entity_type),
+                false => format!("{}[]", entity_type),
+            };
+            let mut alias = TsTypeAlias::new(&format!("{}Response", name), &response);
+            alias.set_doc(&format!("{}.{} 请求的返回值", api.name, request));
+            module.add_declaration(TsDeclaration::TypeAlias(alias));
+        }
+        module
+    }
+
+    /// 分页查询的结果，与 Java 中的 PageResponse 一致
+    fn generate_page(&self) -> TsModule {
+        let mut module = TsModule::new(PAGE_MODULE);
+        let mut interface = TsInterface::new("Page<T>");
+        interface.set_doc("分页查询的结果");
+        let properties = [
+            ("content", "T[]", "当前页的数据"),
+            ("page", "number", "页码，从 0 开始"),
+            ("size", "number", "每页数量"),
+            ("totalElements", "number", "总数量"),
+            ("totalPages", "number", "总页数"),
+        ];
+        for (name, property_type, doc) in properties {
+            let mut property = TsProperty::new(name, property_type);
+            property.set_doc(doc);
+            interface.add_property(property);
+        }
+        module.add_declaration(TsDeclaration::Interface(interface));
+        module
+    }
+
+    /// 接口以及字段标签，引用的实体从 model 目录导入
+    fn generate_interface(
+        &self,
+        model: &Model,
+        path: &str,
+        name: &str,
+        documentation: &Documentation,
+        fields: Vec<(&str, &FieldType, &Documentation)>,
+    ) -> TsModule {
+        let interface_name = naming::pascal_case(name);
+        let mut module = TsModule::new(path);
+
+        let mut referenced: Vec<EntityId> = vec![];
+        for reference in fields.iter().filter_map(|(_, field_type, _)| field_type.entity()) {
+            let module_path = self.entity_module(model, reference);
+            if module_path != path && !referenced.contains(&reference) {
+                referenced.push(reference);
+            }
+        }
+        for reference in referenced {
+            let from = relative_path(path, &self.entity_module(model, reference));
+            module.add_import(TsImport::new(vec![&naming::pascal_case(&model.entity(reference).name)], &from));
+        }
+
+        let mut interface = TsInterface::new(&interface_name);
+        if let Some(doc) = documentation.doc() {
+            interface.set_doc(&doc);
+        }
+        for (field_name, field_type, field_documentation) in &fields {
+            let mut property = TsProperty::new(field_name, &self.ts_type(model, field_type));
+            if let Some(doc) = field_documentation.doc() {
+                property.set_doc(&doc);
+            }
+            interface.add_property(property);
+        }
+        module.add_declaration(TsDeclaration::Interface(interface));
+
+        // 表单中使用的字段标签，未指定 label 时使用字段名
+        let labels: Vec<String> = fields
+            .iter()
+            .map(|(field_name, _, field_documentation)| format!("  {}: {},", field_name, literal(field_documentation.label_or(field_name))))
+            .collect();
+        let mut labels = TsConst::new(
+            &format!("{}Labels", naming::camel_case(name)),
+            Some(&format!("Record<keyof {}, string>", interface_name)),
+            &format!("{{\n{}\n}}", labels.join("\n")));
+        labels.set_doc(&format!("{} 字段标签", documentation.label_or(&interface_name)));
+        module.add_declaration(TsDeclaration::Const(labels));
+
+        module
+    }
+}
+
+/// 同一个源码目录下 from 模块导入 to 模块时使用的路径
+fn relative_path(from: &str, to: &str) -> String {
+    let from_dir: Vec<&str> = from.split('/').collect();
+    let from_dir = &from_dir[..from_dir.len() - 1];
+    let to: Vec<&str> = to.split('/').collect();
+    let common = from_dir.iter().zip(to.iter()).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<&str> = vec![];
+    if common == from_dir.len() {
+        parts.push(".");
+    }
+    parts.extend(std::iter::repeat_n("..", from_dir.len() - common));
+    parts.extend(&to[common..]);
+    parts.join("/")
+}
+
+fn module_name(name: &str) -> String {
+    naming::snake_case(name).replace('_', "-")
+}
+
+fn scalar_type(scalar: ScalarType) -> &'static str {
+    match scalar {
+        ScalarType::Id
+        | ScalarType::Int8
+        | ScalarType::Int16
+        | ScalarType::Int32
+        | ScalarType::Int64
+        | ScalarType::Float32
+        | ScalarType::Float64 => "number",
+        ScalarType::String | ScalarType::DateTime => "string",
+        ScalarType::Bool => "boolean",
+        ScalarType::Object => "unknown",
+    }
+}
+
+/// 单引号字符串字面量
+fn literal(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'").replace('\n', "\\n"))
+}
@@ -0,0 +1,199 @@
+use std::{
+    path::PathBuf,
+    fs::{File, OpenOptions, self},
+};
+
+use project_gen_core::Result;
+
+/// `import { Card } from './card';`
+pub struct TsImport {
+    pub names: Vec<String>,
+    pub from: String,
+}
+
+impl TsImport {
+    pub fn new(names: Vec<&str>, from: &str) -> TsImport {
+        TsImport {
+            names: names.iter().map(|&s| s.to_owned()).collect(),
+            from: from.to_owned(),
+        }
+    }
+}
+
+pub struct TsProperty {
+    pub name: String,
+    pub property_type: String,
+    pub optional: bool,
+    pub doc: Option<String>,
+}
+
+impl TsProperty {
+    pub fn new(name: &str, property_type: &str) -> TsProperty {
+        TsProperty {
+            name: name.to_owned(),
+            property_type: property_type.to_owned(),
+            optional: false,
+            doc: None,
+        }
+    }
+
+    pub fn set_optional(&mut self, optional: bool) {
+        self.optional = optional;
+    }
+
+    pub fn set_doc(&mut self, doc: &str) {
+        self.doc = Some(doc.to_owned());
+    }
+}
+
+/// `export interface Person { ... }`
+pub struct TsInterface {
+    pub name: String,
+    pub doc: Option<String>,
+    pub properties: Vec<TsProperty>,
+}
+
+impl TsInterface {
+    pub fn new(name: &str) -> TsInterface {
+        TsInterface {
+            name: name.to_owned(),
+            doc: None,
+            properties: vec![],
+        }
+    }
+
+    pub fn add_property(&mut self, property: TsProperty) {
+        self.properties.push(property);
+    }
+
+    pub fn set_doc(&mut self, doc: &str) {
+        self.doc = Some(doc.to_owned());
+    }
+}
+
+/// `export const name: type = value;`，value 原样输出
+pub struct TsConst {
+    pub name: String,
+    pub const_type: Option<String>,
+    pub value: String,
+    pub doc: Option<String>,
+}
+
+impl TsConst {
+    pub fn new(name: &str, const_type: Option<&str>, value: &str) -> TsConst {
+        TsConst {
+            name: name.to_owned(),
+            const_type: const_type.map(|s| s.to_owned()),
+            value: value.to_owned(),
+            doc: None,
+        }
+    }
+
+    pub fn set_doc(&mut self, doc: &str) {
+        self.doc = Some(doc.to_owned());
+    }
+}
+
+/// `export type name = value;`
+pub struct TsTypeAlias {
+    pub name: String,
+    pub value: String,
+    pub doc: Option<String>,
+}
+
+impl TsTypeAlias {
+    pub fn new(name: &str, value: &str) -> TsTypeAlias {
+        TsTypeAlias {
+            name: name.to_owned(),
+            value: value.to_owned(),
+            doc: None,
+        }
+    }
+
+    pub fn set_doc(&mut self, doc: &str) {
+        self.doc = Some(doc.to_owned());
+    }
+}
+
+pub enum TsDeclaration {
+    Interface(TsInterface),
+    Const(TsConst),
+    TypeAlias(TsTypeAlias),
+}
+
+/// 一个 .ts 文件，path 为相对源码目录的路径，不包括扩展名
+pub struct TsModule {
+    pub path: String,
+    pub imports: Vec<TsImport>,
+    pub declarations: Vec<TsDeclaration>,
+}
+
+impl TsModule {
+    pub fn new(path: &str) -> TsModule {
+        TsModule {
+            path: path.to_owned(),
+            imports: vec![],
+            declarations: vec![],
+        }
+    }
+
+    pub fn add_import(&mut self, import: TsImport) {
+        self.imports.push(import);
+    }
+
+    pub fn add_declaration(&mut self, declaration: TsDeclaration) {
+        self.declarations.push(declaration);
+    }
+}
+
+pub struct TsSourceCode {
+    pub modules: Vec<TsModule>,
+}
+
+impl Default for TsSourceCode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TsSourceCode {
+    pub fn new() -> TsSourceCode {
+        TsSourceCode {
+            modules: vec![],
+        }
+    }
+
+    pub fn add_module(&mut self, module: TsModule) {
+        self.modules.push(module);
+    }
+}
+
+pub struct TsSourceStructure {
+    pub root_directory: PathBuf,
+    pub source_directory: PathBuf,
+    pub file_extension: String,
+}
+
+impl TsSourceStructure {
+    pub fn new(root_directory: PathBuf) -> TsSourceStructure {
+        let source_directory = root_directory.join("src");
+        TsSourceStructure {
+            root_directory,
+            source_directory,
+            file_extension: "ts".to_string(),
+        }
+    }
+
+    pub fn create_source_file(&self, path: &str) -> Result<File> {
+        let to = self.source_directory.join(format!("{}.{}", path, self.file_extension));
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(to)
+            .map_err(|e| e.into())
+    }
+}
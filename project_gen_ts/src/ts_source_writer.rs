@@ -0,0 +1,95 @@
+use std::{fs::File, io::Write};
+
+use project_gen_core::Result;
+
+use super::{
+    TsSourceStructure,
+    TsSourceCode,
+    TsModule,
+    TsDeclaration,
+    TsInterface,
+    TsConst,
+    TsTypeAlias,
+};
+
+const INDENT: &str = "  ";
+
+#[derive(Debug, Clone, Default)]
+pub struct TsSourceCodeWriter {}
+
+impl TsSourceCodeWriter {
+    pub fn new() -> TsSourceCodeWriter {
+        TsSourceCodeWriter {}
+    }
+
+    pub fn write(&mut self, structure: &TsSourceStructure, source_code: TsSourceCode) -> Result<()> {
+        for module in &source_code.modules {
+            let mut file = structure.create_source_file(&module.path)?;
+            self.write_module(&mut file, module)?;
+        }
+        Ok(())
+    }
+
+    fn write_module(&mut self, file: &mut File, module: &TsModule) -> Result<()> {
+        for import in &module.imports {
+            writeln!(file, "import {{ {} }} from '{}';", import.names.join(", "), import.from)?;
+        }
+        for (i, declaration) in module.declarations.iter().enumerate() {
+            // 导入之后以及声明之间空一行
+            if i > 0 || !module.imports.is_empty() {
+                writeln!(file)?;
+            }
+            match declaration {
+                TsDeclaration::Interface(interface) => self.write_interface(file, interface)?,
+                TsDeclaration::Const(constant) => self.write_const(file, constant)?,
+                TsDeclaration::TypeAlias(alias) => self.write_type_alias(file, alias)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn write_interface(&mut self, file: &mut File, interface: &TsInterface) -> Result<()> {
+        write_doc(file, "", interface.doc.as_deref())?;
+        writeln!(file, "export interface {} {{", interface.name)?;
+        for property in &interface.properties {
+            write_doc(file, INDENT, property.doc.as_deref())?;
+            let optional = if property.optional { "?" } else { "" };
+            writeln!(file, "{}{}{}: {};", INDENT, property.name, optional, property.property_type)?;
+        }
+        writeln!(file, "}}")?;
+        Ok(())
+    }
+
+    fn write_const(&mut self, file: &mut File, constant: &TsConst) -> Result<()> {
+        write_doc(file, "", constant.doc.as_deref())?;
+        match constant.const_type.as_ref() {
+            Some(const_type) => writeln!(file, "export const {}: {} = {};", constant.name, const_type, constant.value)?,
+            None => writeln!(file, "export const {} = {};", constant.name, constant.value)?,
+        }
+        Ok(())
+    }
+
+    fn write_type_alias(&mut self, file: &mut File, alias: &TsTypeAlias) -> Result<()> {
+        write_doc(file, "", alias.doc.as_deref())?;
+        writeln!(file, "export type {} = {};", alias.name, alias.value)?;
+        Ok(())
+    }
+}
+
+/// TSDoc 注释
+fn write_doc(file: &mut File, indent: &str, doc: Option<&str>) -> Result<()> {
+    let doc = match doc {
+        Some(doc) => doc.replace("*/", "*\\/"),
+        None => return Ok(()),
+    };
+    writeln!(file, "{}/**", indent)?;
+    for line in doc.lines() {
+        if line.trim().is_empty() {
+            writeln!(file, "{} *", indent)?;
+        } else {
+            writeln!(file, "{} * {}", indent, line.trim_end())?;
+        }
+    }
+    writeln!(file, "{} */", indent)?;
+    Ok(())
+}